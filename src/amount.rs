@@ -0,0 +1,617 @@
+use crate::currency::CurrencyCode;
+use crate::error::{Result, RippleError};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Value, json};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use xrpl::models::{Amount, IssuedCurrencyAmount, XRPAmount};
+
+const DROPS_PER_XRP: u64 = 1_000_000;
+// Total XRP supply in drops; no amount can exceed it
+const MAX_DROPS: u64 = 100_000_000_000 * DROPS_PER_XRP;
+
+// Issued values are stored as a 16-digit mantissa and an exponent
+const MIN_MANTISSA: u64 = 1_000_000_000_000_000;
+const MAX_MANTISSA: u64 = 9_999_999_999_999_999;
+const MANTISSA_DIGITS: usize = 16;
+const MIN_EXPONENT: i64 = -96;
+const MAX_EXPONENT: i64 = 80;
+
+/// XRP amount in drops (1 XRP = 1,000,000 drops)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Drops(u64);
+
+impl Drops {
+    pub const ZERO: Drops = Drops(0);
+
+    pub fn new(drops: u64) -> Result<Self> {
+        if drops > MAX_DROPS {
+            return Err(RippleError::InvalidInput(format!(
+                "{} drops is more than the total XRP supply",
+                drops
+            )));
+        }
+        Ok(Self(drops))
+    }
+
+    /// Parse a decimal XRP amount ("1.5") with at most 6 decimal places
+    pub fn from_xrp(xrp: &str) -> Result<Self> {
+        let invalid = || RippleError::InvalidInput(format!("Invalid XRP amount {:?}", xrp));
+        let (whole, fraction) = xrp.split_once('.').unwrap_or((xrp, ""));
+        if whole.is_empty() || !is_digits(whole) || !(fraction.is_empty() || is_digits(fraction)) {
+            return Err(invalid());
+        }
+        if fraction.len() > 6 {
+            return Err(RippleError::InvalidInput(format!(
+                "{} has more decimal places than a drop",
+                xrp
+            )));
+        }
+        let whole: u64 = whole.parse().map_err(|_| invalid())?;
+        let fraction: u64 = format!("{:0<6}", fraction).parse().map_err(|_| invalid())?;
+        whole
+            .checked_mul(DROPS_PER_XRP)
+            .and_then(|drops| drops.checked_add(fraction))
+            .ok_or_else(invalid)
+            .and_then(Self::new)
+    }
+
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_add(self, other: Drops) -> Option<Drops> {
+        self.0.checked_add(other.0).filter(|&drops| drops <= MAX_DROPS).map(Drops)
+    }
+
+    pub fn checked_sub(self, other: Drops) -> Option<Drops> {
+        self.0.checked_sub(other.0).map(Drops)
+    }
+
+    /// Whole drops for a number of drops, rounded up; None when negative or above the supply
+    pub(crate) fn from_value_rounded_up(value: &IssuedValue) -> Option<Drops> {
+        if value.negative {
+            return None;
+        }
+        if value.is_zero() {
+            return Some(Drops(0));
+        }
+        let drops = if value.exponent >= 0 {
+            10u64.checked_pow(value.exponent as u32)?.checked_mul(value.mantissa)?
+        } else {
+            // A mantissa has 16 digits, so 20 or more decimals leave less than one drop
+            let divisor = 10u64.checked_pow(value.exponent.unsigned_abs()).unwrap_or(u64::MAX);
+            value.mantissa / divisor + u64::from(!value.mantissa.is_multiple_of(divisor))
+        };
+        Drops::new(drops).ok()
+    }
+
+    /// Amount in XRP, without trailing zeros ("1.5")
+    pub fn to_xrp(self) -> String {
+        let fraction = format!("{:06}", self.0 % DROPS_PER_XRP);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            (self.0 / DROPS_PER_XRP).to_string()
+        } else {
+            format!("{}.{}", self.0 / DROPS_PER_XRP, fraction)
+        }
+    }
+}
+
+impl FromStr for Drops {
+    type Err = RippleError;
+
+    /// Whole drops, as rippled writes XRP amounts ("1000000")
+    fn from_str(drops: &str) -> Result<Self> {
+        if !is_digits(drops) {
+            return Err(RippleError::InvalidInput(format!("Invalid drops amount {:?}", drops)));
+        }
+        let drops = drops
+            .parse()
+            .map_err(|_| RippleError::InvalidInput(format!("{} drops is more than the total XRP supply", drops)))?;
+        Self::new(drops)
+    }
+}
+
+impl fmt::Display for Drops {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Value of an issued currency amount: up to 16 significant digits and an exponent
+/// between -96 and 80, as the ledger stores it. Equal values compare equal whatever
+/// their notation ("100", "100.0", "1e2").
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct IssuedValue {
+    negative: bool,
+    // 0, or MIN_MANTISSA..=MAX_MANTISSA
+    mantissa: u64,
+    exponent: i32,
+}
+
+impl IssuedValue {
+    pub const ZERO: IssuedValue = IssuedValue { negative: false, mantissa: 0, exponent: 0 };
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_positive(&self) -> bool {
+        !self.negative && !self.is_zero()
+    }
+
+    /// This value times `numerator / denominator`, rounded away from zero to 16 digits
+    /// (e.g. the amount a sender spends once an issuer's transfer fee is added)
+    pub fn mul_ratio(&self, numerator: u32, denominator: u32) -> Result<Self> {
+        if denominator == 0 {
+            return Err(RippleError::InvalidInput("Ratio denominator cannot be zero".to_string()));
+        }
+        if self.is_zero() || numerator == 0 {
+            return Ok(Self::ZERO);
+        }
+
+        // Ten extra digits keep at least 16 significant digits in the quotient
+        const EXTRA_DIGITS: i64 = 10;
+        let scaled = self.mantissa as u128 * numerator as u128 * 10u128.pow(EXTRA_DIGITS as u32);
+        let mut mantissa = scaled / denominator as u128;
+        let mut inexact = !scaled.is_multiple_of(denominator as u128);
+        let mut exponent = self.exponent as i64 - EXTRA_DIGITS;
+        while mantissa > MAX_MANTISSA as u128 {
+            inexact |= !mantissa.is_multiple_of(10);
+            mantissa /= 10;
+            exponent += 1;
+        }
+        while mantissa < MIN_MANTISSA as u128 {
+            mantissa *= 10;
+            exponent -= 1;
+        }
+        if inexact {
+            mantissa += 1;
+            if mantissa > MAX_MANTISSA as u128 {
+                mantissa /= 10;
+                exponent += 1;
+            }
+        }
+        if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent) {
+            return Err(RippleError::InvalidInput(format!(
+                "{} times {}/{} is out of range for an issued currency",
+                self, numerator, denominator
+            )));
+        }
+
+        Ok(Self {
+            negative: self.negative,
+            mantissa: mantissa as u64,
+            exponent: exponent as i32,
+        })
+    }
+
+    /// Sum truncated to 16 digits, the way rippled adds amounts; None when it is too large
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        if self.is_zero() {
+            return Some(*other);
+        }
+        if other.is_zero() {
+            return Some(*self);
+        }
+
+        // Bring both to the larger exponent, dropping the digits that no longer fit
+        let signed = |value: &Self| if value.negative { -(value.mantissa as i128) } else { value.mantissa as i128 };
+        let (mut a, mut b) = (signed(self), signed(other));
+        let exponent = self.exponent.max(other.exponent);
+        for _ in self.exponent..exponent {
+            a /= 10;
+        }
+        for _ in other.exponent..exponent {
+            b /= 10;
+        }
+
+        let sum = a + b;
+        Self::from_parts(sum < 0, sum.unsigned_abs(), exponent as i64)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let negated = if other.is_zero() { *other } else { Self { negative: !other.negative, ..*other } };
+        self.checked_add(&negated)
+    }
+
+    /// Product truncated to 16 digits; None when it is too large
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Self::from_parts(
+            self.negative != other.negative,
+            self.mantissa as u128 * other.mantissa as u128,
+            self.exponent as i64 + other.exponent as i64,
+        )
+    }
+
+    /// Quotient truncated to 16 digits; None when dividing by zero or when it is too large
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        // 17 extra digits keep a full mantissa whatever the two mantissas are
+        const EXTRA_DIGITS: u32 = 17;
+        Self::from_parts(
+            self.negative != other.negative,
+            self.mantissa as u128 * 10u128.pow(EXTRA_DIGITS) / other.mantissa as u128,
+            self.exponent as i64 - other.exponent as i64 - EXTRA_DIGITS as i64,
+        )
+    }
+
+    // Truncate to 16 digits. Values too small for the ledger become zero, too large ones None.
+    fn from_parts(negative: bool, mut mantissa: u128, mut exponent: i64) -> Option<Self> {
+        if mantissa == 0 {
+            return Some(Self::ZERO);
+        }
+        while mantissa > MAX_MANTISSA as u128 {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        while mantissa < MIN_MANTISSA as u128 {
+            mantissa *= 10;
+            exponent -= 1;
+        }
+        if exponent > MAX_EXPONENT {
+            return None;
+        }
+        if exponent < MIN_EXPONENT {
+            return Some(Self::ZERO);
+        }
+        Some(Self {
+            negative,
+            mantissa: mantissa as u64,
+            exponent: exponent as i32,
+        })
+    }
+}
+
+// Drops as a plain number, to compute with XRP and issued amounts alike. Refused when the drop
+// count has more than 16 significant digits, which an issued value cannot hold exactly.
+impl TryFrom<Drops> for IssuedValue {
+    type Error = RippleError;
+
+    fn try_from(drops: Drops) -> Result<Self> {
+        let mut significant = drops.0;
+        while significant != 0 && significant.is_multiple_of(10) {
+            significant /= 10;
+        }
+        if significant > MAX_MANTISSA {
+            return Err(RippleError::InvalidInput(format!(
+                "{} drops has more than 16 significant digits",
+                drops
+            )));
+        }
+        IssuedValue::from_parts(false, drops.0 as u128, 0)
+            .ok_or_else(|| RippleError::InvalidInput(format!("{} drops is out of range", drops)))
+    }
+}
+
+impl FromStr for IssuedValue {
+    type Err = RippleError;
+
+    /// Parse the decimal format rippled accepts: "100", "-0.25", "1.5e-3"
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = || RippleError::InvalidInput(format!("Invalid issued currency value {:?}", value));
+
+        let (negative, unsigned) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let (decimal, exponent) = match unsigned.split_once(['e', 'E']) {
+            Some((decimal, exponent)) => {
+                let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                if !is_digits(digits) || digits.len() > 4 {
+                    return Err(invalid());
+                }
+                (decimal, exponent.parse::<i64>().map_err(|_| invalid())?)
+            }
+            None => (unsigned, 0),
+        };
+        let (whole, fraction) = decimal.split_once('.').unwrap_or((decimal, ""));
+        let leading_zero = whole.len() > 1 && whole.starts_with('0');
+        if !is_digits(whole) || leading_zero || (decimal.contains('.') && !is_digits(fraction)) {
+            return Err(invalid());
+        }
+
+        let digits = format!("{}{}", whole, fraction);
+        let significant = digits.trim_start_matches('0');
+        let trimmed = significant.trim_end_matches('0');
+        if trimmed.is_empty() {
+            return Ok(Self::ZERO);
+        }
+        if trimmed.len() > MANTISSA_DIGITS {
+            return Err(RippleError::InvalidInput(format!(
+                "{} has more than {} significant digits",
+                value, MANTISSA_DIGITS
+            )));
+        }
+
+        // Scale the significant digits up to a full 16-digit mantissa
+        let padding = MANTISSA_DIGITS - trimmed.len();
+        let mantissa: u64 = format!("{}{}", trimmed, "0".repeat(padding)).parse().map_err(|_| invalid())?;
+        let exponent = exponent - fraction.len() as i64 + (significant.len() - trimmed.len()) as i64 - padding as i64;
+        if exponent > MAX_EXPONENT {
+            return Err(RippleError::InvalidInput(format!("{} is too large for an issued currency", value)));
+        }
+        if exponent < MIN_EXPONENT {
+            return Err(RippleError::InvalidInput(format!("{} is too small for an issued currency", value)));
+        }
+        debug_assert!((MIN_MANTISSA..=MAX_MANTISSA).contains(&mantissa));
+
+        Ok(Self {
+            negative,
+            mantissa,
+            exponent: exponent as i32,
+        })
+    }
+}
+
+impl fmt::Display for IssuedValue {
+    /// Same text rippled produces: plain decimals for moderate exponents,
+    /// `mantissa` e `exponent` otherwise
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        let sign = if self.negative { "-" } else { "" };
+        if self.exponent != 0 && !(-25..=-5).contains(&self.exponent) {
+            return write!(f, "{}{}e{}", sign, self.mantissa, self.exponent);
+        }
+
+        let digits = self.mantissa.to_string();
+        let point = MANTISSA_DIGITS as i32 + self.exponent;
+        let (whole, fraction) = if point <= 0 {
+            ("0".to_string(), format!("{}{}", "0".repeat(-point as usize), digits))
+        } else {
+            let (whole, fraction) = digits.split_at(point as usize);
+            (whole.to_string(), fraction.to_string())
+        };
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, whole)
+        } else {
+            write!(f, "{}{}.{}", sign, whole, fraction)
+        }
+    }
+}
+
+impl Ord for IssuedValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let magnitude = |value: &Self| (!value.is_zero(), value.exponent, value.mantissa);
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => magnitude(self).cmp(&magnitude(other)),
+            (true, true) => magnitude(other).cmp(&magnitude(self)),
+        }
+    }
+}
+
+impl PartialOrd for IssuedValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// XRP or an issued currency amount, as found in `Amount`, `SendMax`, `delivered_amount`, ...
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CurrencyAmount {
+    Xrp(Drops),
+    Issued {
+        currency: CurrencyCode,
+        issuer: String,
+        value: IssuedValue,
+    },
+}
+
+impl CurrencyAmount {
+    pub fn drops(drops: u64) -> Result<Self> {
+        Drops::new(drops).map(CurrencyAmount::Xrp)
+    }
+
+    /// Issued currency amount; `value` is parsed as an `IssuedValue`
+    pub fn issued(currency: &CurrencyCode, issuer: &str, value: &str) -> Result<Self> {
+        Ok(CurrencyAmount::Issued {
+            currency: currency.clone(),
+            issuer: issuer.to_string(),
+            value: value.parse()?,
+        })
+    }
+
+    /// Read rippled's JSON form: a drops string, or `{currency, issuer, value}`
+    pub fn from_json(amount: &Value) -> Result<Self> {
+        match amount {
+            Value::String(drops) => drops.parse().map(CurrencyAmount::Xrp),
+            Value::Object(fields) => {
+                let field = |name: &str| {
+                    fields
+                        .get(name)
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| RippleError::InvalidInput(format!("Amount has no {}", name)))
+                };
+                Ok(CurrencyAmount::Issued {
+                    currency: field("currency")?.parse()?,
+                    issuer: field("issuer")?.to_string(),
+                    value: field("value")?.parse()?,
+                })
+            }
+            other => Err(RippleError::InvalidInput(format!("Invalid amount {}", other))),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            CurrencyAmount::Xrp(drops) => json!(drops.to_string()),
+            CurrencyAmount::Issued { currency, issuer, value } => json!({
+                "currency": currency,
+                "issuer": issuer,
+                "value": value.to_string(),
+            }),
+        }
+    }
+
+    /// "XRP" or the currency code
+    pub fn currency(&self) -> &str {
+        match self {
+            CurrencyAmount::Xrp(_) => "XRP",
+            CurrencyAmount::Issued { currency, .. } => currency.as_str(),
+        }
+    }
+
+    pub fn issuer(&self) -> Option<&str> {
+        match self {
+            CurrencyAmount::Xrp(_) => None,
+            CurrencyAmount::Issued { issuer, .. } => Some(issuer),
+        }
+    }
+
+    /// Drops for XRP, the decimal value for issued currencies
+    pub fn value_text(&self) -> String {
+        match self {
+            CurrencyAmount::Xrp(drops) => drops.to_string(),
+            CurrencyAmount::Issued { value, .. } => value.to_string(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            CurrencyAmount::Xrp(drops) => drops.is_zero(),
+            CurrencyAmount::Issued { value, .. } => value.is_zero(),
+        }
+    }
+
+    /// More than zero; XRP amounts cannot be negative
+    pub fn is_positive(&self) -> bool {
+        match self {
+            CurrencyAmount::Xrp(drops) => !drops.is_zero(),
+            CurrencyAmount::Issued { value, .. } => value.is_positive(),
+        }
+    }
+
+    /// Zero of the same currency and issuer
+    pub fn zero(&self) -> Self {
+        match self {
+            CurrencyAmount::Xrp(_) => CurrencyAmount::Xrp(Drops::default()),
+            CurrencyAmount::Issued { currency, issuer, .. } => CurrencyAmount::Issued {
+                currency: currency.clone(),
+                issuer: issuer.clone(),
+                value: IssuedValue::ZERO,
+            },
+        }
+    }
+
+    /// Sum of two amounts of the same currency and issuer; None for different currencies or on overflow
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        self.combine(other, Drops::checked_add, IssuedValue::checked_add)
+    }
+
+    /// Difference of two amounts of the same currency and issuer; None for different currencies,
+    /// or when XRP would go below zero
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.combine(other, Drops::checked_sub, IssuedValue::checked_sub)
+    }
+
+    fn combine(
+        &self,
+        other: &Self,
+        xrp: fn(Drops, Drops) -> Option<Drops>,
+        issued: fn(&IssuedValue, &IssuedValue) -> Option<IssuedValue>,
+    ) -> Option<Self> {
+        match (self, other) {
+            (CurrencyAmount::Xrp(a), CurrencyAmount::Xrp(b)) => xrp(*a, *b).map(CurrencyAmount::Xrp),
+            (
+                CurrencyAmount::Issued { currency, issuer, value },
+                CurrencyAmount::Issued { currency: other_currency, issuer: other_issuer, value: other_value },
+            ) if currency == other_currency && issuer == other_issuer => Some(CurrencyAmount::Issued {
+                currency: currency.clone(),
+                issuer: issuer.clone(),
+                value: issued(value, other_value)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Only amounts of the same currency and issuer are ordered
+impl PartialOrd for CurrencyAmount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (CurrencyAmount::Xrp(a), CurrencyAmount::Xrp(b)) => Some(a.cmp(b)),
+            (
+                CurrencyAmount::Issued { currency, issuer, value },
+                CurrencyAmount::Issued { currency: other_currency, issuer: other_issuer, value: other_value },
+            ) if currency == other_currency && issuer == other_issuer => Some(value.cmp(other_value)),
+            _ => None,
+        }
+    }
+}
+
+impl From<&CurrencyAmount> for Amount<'static> {
+    fn from(amount: &CurrencyAmount) -> Self {
+        match amount {
+            CurrencyAmount::Xrp(drops) => Amount::XRPAmount(XRPAmount(Cow::Owned(drops.to_string()))),
+            CurrencyAmount::Issued { currency, issuer, value } => Amount::IssuedCurrencyAmount(IssuedCurrencyAmount::new(
+                Cow::Owned(currency.to_string()),
+                Cow::Owned(issuer.clone()),
+                Cow::Owned(value.to_string()),
+            )),
+        }
+    }
+}
+
+// Drops and issued values travel as strings in rippled's JSON
+macro_rules! serde_as_string {
+    ($($amount:ty),*) => {
+        $(
+            impl Serialize for $amount {
+                fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $amount {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                    let text = Cow::<str>::deserialize(deserializer)?;
+                    text.parse().map_err(serde::de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+serde_as_string!(Drops, IssuedValue);
+
+impl Serialize for CurrencyAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CurrencyAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        CurrencyAmount::from_json(&Value::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+// Issued value of a payment, which has to be above zero
+pub(crate) fn positive_value(value: &IssuedValue) -> Result<IssuedValue> {
+    if !value.is_positive() {
+        return Err(RippleError::InvalidInput(format!("Amount must be positive, got {}", value)));
+    }
+    Ok(*value)
+}
+
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
@@ -0,0 +1,381 @@
+use crate::amount::CurrencyAmount;
+use crate::client::{self, RawRequest, with_timeout};
+use crate::error::{Result, RippleError};
+use crate::ledger_object::{ledger_object_id, ledger_object_id_bytes};
+use crate::payment_options::PaymentOptions;
+use crate::ripple_time::{from_ripple_time, to_ripple_time};
+use crate::secret::Secret;
+use crate::submission::{ValidatedTransaction, check_submit_result, wait_for_submit_result};
+use crate::transactions::submit_signed_transaction;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime};
+use tracing::{Span, debug, info, instrument};
+use xrpl::{
+    asynch::{clients::XRPLAsyncClient, transaction::autofill},
+    models::{
+        Amount,
+        results::submit::Submit as SubmitResult,
+        transactions::{check_cancel::CheckCancel, check_cash::CheckCash, check_create::CheckCreate},
+    },
+};
+
+// Check IDs hash this ledger space key with the owner and the CheckCreate's sequence
+const CHECK_SPACE_KEY: [u8; 2] = [0x00, 0x43];
+
+/// CheckCreate: let `destination` pull up to `send_max` from the sender later. Nothing moves
+/// until the destination cashes the check, and the sender's balance is not reserved for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckBuilder {
+    destination: String,
+    send_max: CurrencyAmount,
+    expiration: Option<SystemTime>,
+    options: PaymentOptions,
+}
+
+impl CheckBuilder {
+    /// `send_max` is XRP, or a token of the issuer named in it; for tokens it includes any transfer fee
+    pub fn new(destination: &str, send_max: CurrencyAmount) -> Self {
+        Self {
+            destination: destination.to_string(),
+            send_max,
+            expiration: None,
+            options: PaymentOptions::default(),
+        }
+    }
+
+    /// Time from which the check can no longer be cashed, only cancelled
+    pub fn expiration(mut self, time: SystemTime) -> Self {
+        self.expiration = Some(time);
+        self
+    }
+
+    /// Destination tag, source tag, InvoiceID and memos
+    pub fn with_options(mut self, options: PaymentOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    pub fn send_max(&self) -> &CurrencyAmount {
+        &self.send_max
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !self.send_max.is_positive() {
+            return Err(RippleError::InvalidInput("Check SendMax must be positive".to_string()));
+        }
+        self.options.validate()
+    }
+}
+
+/// Submitted CheckCreate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacedCheck {
+    pub tx_hash: String,
+    pub check_id: String,
+    /// Sequence of the CheckCreate, from which the check ID is derived
+    pub sequence: u32,
+}
+
+/// How much a CheckCash takes from the check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckCashAmount {
+    /// Exactly this much, or the cash fails
+    Exact(CurrencyAmount),
+    /// As much as the check and the sender's balance allow, failing below this
+    DeliverMin(CurrencyAmount),
+}
+
+impl CheckCashAmount {
+    pub fn amount(&self) -> &CurrencyAmount {
+        match self {
+            CheckCashAmount::Exact(amount) | CheckCashAmount::DeliverMin(amount) => amount,
+        }
+    }
+}
+
+/// Check in the ledger, as listed by `account_objects`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckEntry {
+    /// Account that wrote the check and pays when it is cashed
+    #[serde(rename = "Account")]
+    pub owner: String,
+    #[serde(rename = "Destination")]
+    pub destination: String,
+    #[serde(rename = "SendMax")]
+    pub send_max: CurrencyAmount,
+    /// Sequence of the CheckCreate
+    #[serde(rename = "Sequence")]
+    pub sequence: u32,
+    /// Seconds since the Ripple epoch
+    #[serde(rename = "Expiration", default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<u32>,
+    #[serde(rename = "DestinationTag", default, skip_serializing_if = "Option::is_none")]
+    pub destination_tag: Option<u32>,
+    #[serde(rename = "SourceTag", default, skip_serializing_if = "Option::is_none")]
+    pub source_tag: Option<u32>,
+    #[serde(rename = "InvoiceID", default, skip_serializing_if = "Option::is_none")]
+    pub invoice_id: Option<String>,
+    /// Ledger entry ID of the check, which CheckCash and CheckCancel name
+    #[serde(rename = "index")]
+    pub check_id: String,
+}
+
+impl CheckEntry {
+    pub fn expiration_time(&self) -> Option<SystemTime> {
+        self.expiration.map(from_ripple_time)
+    }
+
+    /// True when a ledger closing at `time` can no longer cash the check
+    pub fn is_expired_at(&self, time: SystemTime) -> bool {
+        self.expiration_time().is_some_and(|expiration| time >= expiration)
+    }
+}
+
+/// Unexpired checks `account` can cash, in the last validated ledger. Expiry is judged by the
+/// local clock, which can be a few seconds off the time of the next ledger close.
+#[instrument(skip(client, timeout))]
+pub async fn list_cashable_checks<C: RawRequest>(client: &C, account: &str, timeout: Duration) -> Result<Vec<CheckEntry>> {
+    let objects = client::account_objects(client, account, "check", timeout).await?;
+
+    let now = SystemTime::now();
+    let mut checks = Vec::with_capacity(objects.len());
+    for object in objects {
+        let check: CheckEntry = serde_json::from_value(object)
+            .map_err(|e| RippleError::client_source("Malformed check in account_objects response", e))?;
+        // account_objects also lists the checks `account` wrote
+        if check.destination == account && !check.is_expired_at(now) {
+            checks.push(check);
+        }
+    }
+
+    debug!(checks = checks.len(), "cashable checks listed");
+    Ok(checks)
+}
+
+/// ID of the check created by `owner`'s CheckCreate with sequence `sequence`
+pub fn check_id(owner: &str, sequence: u32) -> Result<String> {
+    ledger_object_id(CHECK_SPACE_KEY, &[owner], sequence)
+}
+
+/// Write a check to `check.destination()`
+#[instrument(skip(client, sender_secret, check), fields(account))]
+pub async fn create_check<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    check: &CheckBuilder,
+    timeout: Duration,
+) -> Result<PlacedCheck> {
+    let (result, sequence, check_id) = submit_check_create(client, sender_secret, check, timeout).await?;
+    let placed = PlacedCheck {
+        tx_hash: check_submit_result(&result)?,
+        check_id,
+        sequence,
+    };
+
+    info!(tx_hash = %placed.tx_hash, check_id = %placed.check_id, "check create submitted");
+    Ok(placed)
+}
+
+#[instrument(skip(client, sender_secret, check), fields(account))]
+pub async fn create_check_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    check: &CheckBuilder,
+    timeout: Duration,
+) -> Result<(PlacedCheck, ValidatedTransaction)> {
+    let (result, sequence, check_id) = submit_check_create(client, sender_secret, check, timeout).await?;
+    let validated = wait_for_submit_result(client, &result, timeout).await?;
+    let placed = PlacedCheck {
+        tx_hash: validated.tx_hash.clone(),
+        check_id,
+        sequence,
+    };
+    Ok((placed, validated))
+}
+
+async fn submit_check_create<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    check: &CheckBuilder,
+    timeout: Duration,
+) -> Result<(SubmitResult<'static>, u32, String)> {
+    check.validate()?;
+    let wallet = sender_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    // The options are set when signing, as for payments (see `transactions::sign_payment`)
+    let mut check_create = CheckCreate::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Cow::Owned(check.destination.clone()),
+        Amount::from(&check.send_max),
+        None,
+        check.expiration.map(to_ripple_time).transpose()?,
+        None,
+    );
+
+    debug!(
+        destination = %check.destination,
+        send_max = %check.send_max.value_text(),
+        currency = check.send_max.currency(),
+        "submitting check create"
+    );
+
+    with_timeout("autofill", timeout, async {
+        autofill(&mut check_create, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill check create", e))
+    })
+    .await?;
+    let sequence = check_create
+        .common_fields
+        .sequence
+        .ok_or_else(|| RippleError::client("Check was autofilled without a Sequence"))?;
+    let result = submit_signed_transaction(client, &wallet, &check_create, &check.options, timeout).await?;
+    let check_id = check_id(&wallet.classic_address, sequence)?;
+    Ok((result, sequence, check_id))
+}
+
+/// Cash a check written to the account of `casher_secret`
+#[instrument(skip(client, casher_secret), fields(account))]
+pub async fn cash_check<C: XRPLAsyncClient>(
+    client: &C,
+    casher_secret: &Secret,
+    check_id: &str,
+    amount: &CheckCashAmount,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_check_cash(client, casher_secret, check_id, amount, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "check cash submitted");
+    Ok(tx_hash)
+}
+
+#[instrument(skip(client, casher_secret), fields(account))]
+pub async fn cash_check_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    casher_secret: &Secret,
+    check_id: &str,
+    amount: &CheckCashAmount,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_check_cash(client, casher_secret, check_id, amount, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_check_cash<C: XRPLAsyncClient>(
+    client: &C,
+    casher_secret: &Secret,
+    check_id: &str,
+    amount: &CheckCashAmount,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    ledger_object_id_bytes(check_id, "check")?;
+    if !amount.amount().is_positive() {
+        return Err(RippleError::InvalidInput("Check cash amount must be positive".to_string()));
+    }
+    let wallet = casher_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let (exact, deliver_min) = match amount {
+        CheckCashAmount::Exact(amount) => (Some(Amount::from(amount)), None),
+        CheckCashAmount::DeliverMin(amount) => (None, Some(Amount::from(amount))),
+    };
+    let mut check_cash = CheckCash::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Cow::Owned(check_id.to_uppercase()),
+        exact,
+        deliver_min,
+    );
+
+    debug!(%check_id, ?amount, "submitting check cash");
+
+    with_timeout("autofill", timeout, async {
+        autofill(&mut check_cash, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill check cash", e))
+    })
+    .await?;
+    submit_signed_transaction(client, &wallet, &check_cash, &PaymentOptions::default(), timeout).await
+}
+
+/// Cancel a check. Its sender or destination can cancel it at any time; anyone can once it has expired.
+#[instrument(skip(client, canceller_secret), fields(account))]
+pub async fn cancel_check<C: XRPLAsyncClient>(
+    client: &C,
+    canceller_secret: &Secret,
+    check_id: &str,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_check_cancel(client, canceller_secret, check_id, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "check cancel submitted");
+    Ok(tx_hash)
+}
+
+#[instrument(skip(client, canceller_secret), fields(account))]
+pub async fn cancel_check_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    canceller_secret: &Secret,
+    check_id: &str,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_check_cancel(client, canceller_secret, check_id, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_check_cancel<C: XRPLAsyncClient>(
+    client: &C,
+    canceller_secret: &Secret,
+    check_id: &str,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    ledger_object_id_bytes(check_id, "check")?;
+    let wallet = canceller_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let mut check_cancel = CheckCancel::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Cow::Owned(check_id.to_uppercase()),
+    );
+
+    debug!(%check_id, "submitting check cancel");
+
+    with_timeout("autofill", timeout, async {
+        autofill(&mut check_cancel, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill check cancel", e))
+    })
+    .await?;
+    submit_signed_transaction(client, &wallet, &check_cancel, &PaymentOptions::default(), timeout).await
+}
@@ -1,21 +1,151 @@
-use anyhow::Result;
+use crate::amount::CurrencyAmount;
+use crate::error::{IntoSource, Result, RippleError};
+use crate::json_rpc::JsonRpcClient;
+use crate::network::{NetworkConfig, Transport};
+use crate::subscription::{self, StreamConnection, StreamMessage, Subscription};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
 use std::borrow::Cow;
+use std::time::Duration;
+use tracing::{debug, instrument, warn};
+use url::Url;
 use xrpl::{
-    asynch::clients::{AsyncWebSocketClient, WebSocketOpen, client::XRPLClient},
+    asynch::clients::{
+        AsyncWebSocketClient, SingleExecutorMutex, WebSocketOpen, XRPLAsyncClient, client::XRPLClient,
+        exceptions::XRPLClientResult,
+    },
     models::{
-        requests::{LedgerIndex, account_info::AccountInfo, tx::Tx},
-        results::{account_info::AccountInfoVersionMap, tx::TxVersionMap},
+        Currency,
+        requests::{LedgerIndex, XRPLRequest, account_info::AccountInfo, tx::Tx},
+        results::{XRPLResponse, account_info::AccountInfoVersionMap, tx::TxVersionMap},
     },
 };
 
-type XRPLClientType =
-    AsyncWebSocketClient<xrpl::asynch::clients::SingleExecutorMutex, WebSocketOpen>;
+/// Default deadline for a single request to the node
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An open WebSocket connection
+pub type WebSocketClient = AsyncWebSocketClient<SingleExecutorMutex, WebSocketOpen>;
+
+// rippled rejects path requests with more source currencies than this
+const MAX_SOURCE_CURRENCIES: usize = 18;
+// Largest pages account_offers and account_objects return
+const ACCOUNT_OFFERS_PAGE_LIMIT: u32 = 400;
+const ACCOUNT_OBJECTS_PAGE_LIMIT: u32 = 400;
+
+/// Client over whichever transport the NetworkConfig URL selects
+pub struct Client {
+    connection: Connection,
+    network: NetworkConfig,
+    // Raw JSON connection for the requests xrpl-rust has no model for, opened on first use
+    // (WebSocket only; JSON-RPC sends those over HTTP like any other request)
+    raw: tokio::sync::Mutex<Option<StreamConnection>>,
+}
+
+enum Connection {
+    WebSocket(WebSocketClient),
+    JsonRpc(JsonRpcClient),
+}
+
+impl Client {
+    pub async fn connect(network: &NetworkConfig) -> Result<Self> {
+        let connection = match network.transport() {
+            Transport::WebSocket => AsyncWebSocketClient::open(network.url.clone())
+                .await
+                .map(Connection::WebSocket)
+                .map_err(|e| RippleError::network(format!("Failed to connect to {}", network.url), e))?,
+            // HTTP is connectionless; failures surface on the first request
+            Transport::JsonRpc => Connection::JsonRpc(JsonRpcClient::new(network.url.clone())),
+        };
+        Ok(Self {
+            connection,
+            network: network.clone(),
+            raw: tokio::sync::Mutex::new(None),
+        })
+    }
+
+    pub fn transport(&self) -> Transport {
+        match self.connection {
+            Connection::WebSocket(_) => Transport::WebSocket,
+            Connection::JsonRpc(_) => Transport::JsonRpc,
+        }
+    }
+}
+
+impl XRPLClient for Client {
+    async fn request_impl<'a: 'b, 'b>(
+        &self,
+        request: XRPLRequest<'a>,
+    ) -> XRPLClientResult<XRPLResponse<'b>> {
+        match &self.connection {
+            Connection::WebSocket(client) => client.request_impl(request).await,
+            Connection::JsonRpc(client) => client.request_impl(request).await,
+        }
+    }
+
+    fn get_host(&self) -> Url {
+        match &self.connection {
+            Connection::WebSocket(client) => client.get_host(),
+            Connection::JsonRpc(client) => client.get_host(),
+        }
+    }
+}
+
+/// Client that can send a request as raw JSON. Used for the read-only queries whose request
+/// or result xrpl-rust's models cannot express: its path finding requests take amounts without
+/// a value, and its book_offers and account_offers results drop owner_funds, the funded amounts
+/// and offers that sell issued currencies.
+#[allow(async_fn_in_trait)]
+pub trait RawRequest {
+    /// Send `command` with `params` and return the `result` of its response
+    async fn request_json(&self, command: &str, params: Value, timeout: Duration) -> Result<Value>;
+}
+
+impl RawRequest for Client {
+    async fn request_json(&self, command: &str, params: Value, timeout: Duration) -> Result<Value> {
+        match &self.connection {
+            Connection::JsonRpc(client) => with_timeout(command, timeout, client.request_json(command, params)).await,
+            Connection::WebSocket(_) => {
+                // One request at a time, so the connection's responses are never interleaved
+                let mut raw = self.raw.lock().await;
+                let connection = match &mut *raw {
+                    Some(connection) => connection,
+                    None => raw.insert(StreamConnection::open(&self.network, timeout).await?),
+                };
+                let result = connection.request(command, params, timeout).await;
+                if let Err(RippleError::Network { .. }) = result {
+                    // Reopened by the next request
+                    *raw = None;
+                }
+                result
+            }
+        }
+    }
+}
+
+// Give up on `request` once `timeout` has passed. The request future is dropped at
+// its current await point, so nothing is left running against the node.
+pub(crate) async fn with_timeout<T>(
+    command: &str,
+    timeout: Duration,
+    request: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(timeout, request).await.unwrap_or_else(|_| {
+        warn!(command, ?timeout, "request timed out");
+        Err(RippleError::Timeout {
+            command: command.to_string(),
+            timeout,
+        })
+    })
+}
 
-pub async fn get_account_info(
-    client: &XRPLClientType,
+#[instrument(skip(client))]
+pub async fn get_account_info<C: XRPLAsyncClient>(
+    client: &C,
     account: &str,
+    timeout: Duration,
 ) -> Result<AccountInfoVersionMap<'static>> {
-    println!("Getting account info for: {}", account);
+    debug!("requesting account_info");
 
     let request = AccountInfo::new(
         None,
@@ -27,25 +157,37 @@ pub async fn get_account_info(
         None,
     );
 
-    let response = client.request_impl(request.into()).await?;
+    let response = with_timeout("account_info", timeout, async {
+        client
+            .request(request.into())
+            .await
+            .map_err(|e| RippleError::account(account, "account_info request failed", Some(e.into_source())))
+    })
+    .await?;
 
     match response.result {
         Some(xrpl::models::results::XRPLResult::AccountInfo(info)) => {
-            println!("Account info retrieved");
+            debug!("account info retrieved");
             Ok(info)
         }
         _ => {
-            println!("Unexpected response type");
-            Err(anyhow::anyhow!("Unexpected response type"))
+            let message = response
+                .error
+                .map(|error| error.to_string())
+                .unwrap_or_else(|| "Unexpected response type".to_string());
+            warn!(error = %message, "account_info request failed");
+            Err(RippleError::account(account, message, None))
         }
     }
 }
 
-pub async fn get_transaction(
-    client: &XRPLClientType,
+#[instrument(skip(client))]
+pub async fn get_transaction<C: XRPLAsyncClient>(
+    client: &C,
     tx_hash: &str,
+    timeout: Duration,
 ) -> Result<TxVersionMap<'static>> {
-    println!("Getting transaction: {}", tx_hash);
+    debug!("requesting transaction");
 
     let request = Tx::new(
         None,
@@ -55,16 +197,295 @@ pub async fn get_transaction(
         Some(Cow::Owned(tx_hash.to_string())),
     );
 
-    let response = client.request_impl(request.into()).await?;
+    let response = with_timeout("tx", timeout, async { Ok(client.request(request.into()).await?) }).await?;
 
     match response.result {
         Some(xrpl::models::results::XRPLResult::Tx(tx)) => {
-            println!("Transaction retrieved");
+            debug!("transaction retrieved");
             Ok(tx)
         }
         _ => {
-            println!("Unexpected response type for transaction");
-            Err(anyhow::anyhow!("Unexpected response type"))
+            let message = response
+                .error
+                .map(|error| error.to_string())
+                .unwrap_or_else(|| "Unexpected response type".to_string());
+            warn!(error = %message, "tx request failed");
+            Err(RippleError::Transaction {
+                message,
+                tx_hash: Some(tx_hash.to_string()),
+                source: None,
+            })
+        }
+    }
+}
+
+/// One hop of a payment path: rippling through `account`, or converting into
+/// `currency` issued by `issuer` through an order book ("XRP" has no issuer)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStep {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+}
+
+/// Steps the funds take from the sender to the destination
+pub type Path = Vec<PathStep>;
+
+/// One way to deliver a payment, as found by `ripple_path_find` or `path_find`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathAlternative {
+    /// What the sender would spend, at the rates of the ledger the paths were found in
+    pub source_amount: CurrencyAmount,
+    /// Empty when the default path (direct rippling or a single order book) is enough
+    #[serde(rename = "paths_computed", default)]
+    pub paths: Vec<Path>,
+}
+
+/// Alternatives from an open `path_find` request, resent as ledgers close.
+/// The first ones may come from a partial search; `full_reply` marks a complete one.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PathFindUpdate {
+    pub alternatives: Vec<PathAlternative>,
+    #[serde(default)]
+    pub full_reply: bool,
+}
+
+impl StreamMessage for PathFindUpdate {
+    const TYPE: &'static str = "path_find";
+}
+
+/// A path search: ways for `source_account` to deliver `destination_amount` to `destination_account`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRequest {
+    pub source_account: String,
+    pub destination_account: String,
+    pub destination_amount: CurrencyAmount,
+    /// Only paths spending at most this, in its currency
+    pub send_max: Option<CurrencyAmount>,
+    /// Only paths starting in these currencies; cannot be combined with `send_max`
+    pub source_currencies: Vec<Currency<'static>>,
+}
+
+impl PathRequest {
+    pub fn new(source_account: &str, destination_account: &str, destination_amount: CurrencyAmount) -> Self {
+        Self {
+            source_account: source_account.to_string(),
+            destination_account: destination_account.to_string(),
+            destination_amount,
+            send_max: None,
+            source_currencies: Vec::new(),
         }
     }
+
+    pub fn with_send_max(mut self, send_max: CurrencyAmount) -> Self {
+        self.send_max = Some(send_max);
+        self
+    }
+
+    pub fn with_source_currency(mut self, currency: Currency<'static>) -> Self {
+        self.source_currencies.push(currency);
+        self
+    }
+
+    fn to_json(&self) -> Result<Value> {
+        if self.send_max.is_some() && !self.source_currencies.is_empty() {
+            return Err(RippleError::InvalidInput(
+                "A path request takes either send_max or source currencies, not both".to_string(),
+            ));
+        }
+        if self.source_currencies.len() > MAX_SOURCE_CURRENCIES {
+            return Err(RippleError::InvalidInput(format!(
+                "At most {} source currencies are allowed, got {}",
+                MAX_SOURCE_CURRENCIES,
+                self.source_currencies.len()
+            )));
+        }
+
+        let mut request = json!({
+            "source_account": self.source_account,
+            "destination_account": self.destination_account,
+            "destination_amount": self.destination_amount,
+        });
+        if let Some(send_max) = &self.send_max {
+            request["send_max"] = json!(send_max);
+        }
+        if !self.source_currencies.is_empty() {
+            request["source_currencies"] = json!(self.source_currencies);
+        }
+        Ok(request)
+    }
+}
+
+/// Offer in an order book, as listed by `book_offers`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookOffer {
+    #[serde(rename = "Account")]
+    pub owner: String,
+    /// Sequence of the OfferCreate that placed the offer
+    #[serde(rename = "Sequence")]
+    pub sequence: u32,
+    #[serde(rename = "TakerGets")]
+    pub taker_gets: CurrencyAmount,
+    #[serde(rename = "TakerPays")]
+    pub taker_pays: CurrencyAmount,
+    /// Set when the owner cannot fund the whole offer: the part of TakerGets it can deliver
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_gets_funded: Option<CurrencyAmount>,
+    /// TakerPays for the funded part
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_pays_funded: Option<CurrencyAmount>,
+    /// Balance the owner holds to fund TakerGets (drops for XRP); only listed on the
+    /// owner's first offer in the book
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_funds: Option<String>,
+    /// TakerPays per unit of TakerGets, drops for XRP. The book lists the lowest first.
+    pub quality: String,
+    #[serde(rename = "Flags", default)]
+    pub flags: u32,
+    /// Seconds since the Ripple epoch after which the offer can no longer be taken
+    #[serde(rename = "Expiration", default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<u32>,
+    /// Ledger entry ID of the offer
+    #[serde(rename = "index")]
+    pub offer_id: String,
+}
+
+impl BookOffer {
+    /// What a taker can get from this offer, and what it pays for that
+    pub fn funded(&self) -> (&CurrencyAmount, &CurrencyAmount) {
+        match (&self.taker_gets_funded, &self.taker_pays_funded) {
+            (Some(gets), Some(pays)) => (gets, pays),
+            _ => (&self.taker_gets, &self.taker_pays),
+        }
+    }
+}
+
+/// Offer owned by an account, as listed by `account_offers`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountOffer {
+    /// Sequence of the OfferCreate that placed the offer, as `cancel_offer` takes it
+    #[serde(rename = "seq")]
+    pub sequence: u32,
+    pub taker_gets: CurrencyAmount,
+    pub taker_pays: CurrencyAmount,
+    /// TakerPays per unit of TakerGets, drops for XRP
+    pub quality: String,
+    #[serde(default)]
+    pub flags: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<u32>,
+}
+
+/// Best offers in the book where takers receive `taker_gets` and pay `taker_pays`,
+/// in the current open ledger, at most `limit` of them
+#[instrument(skip_all)]
+pub async fn book_offers<C: RawRequest>(
+    client: &C,
+    taker_gets: &Currency<'_>,
+    taker_pays: &Currency<'_>,
+    limit: u16,
+    timeout: Duration,
+) -> Result<Vec<BookOffer>> {
+    debug!("requesting book_offers");
+
+    let params = json!({ "taker_gets": taker_gets, "taker_pays": taker_pays, "limit": limit });
+    let mut result = client.request_json("book_offers", params, timeout).await?;
+    let offers: Vec<BookOffer> = serde_json::from_value(result["offers"].take())
+        .map_err(|e| RippleError::client_source("Malformed book_offers response", e))?;
+
+    debug!(offers = offers.len(), "order book retrieved");
+    Ok(offers)
+}
+
+/// Every offer `account` has on the books in the last validated ledger, following markers
+#[instrument(skip(client, timeout))]
+pub async fn account_offers<C: RawRequest>(client: &C, account: &str, timeout: Duration) -> Result<Vec<AccountOffer>> {
+    debug!("requesting account_offers");
+
+    let params = json!({ "account": account, "limit": ACCOUNT_OFFERS_PAGE_LIMIT });
+    let offers = request_pages(client, "account_offers", params, "offers", timeout).await?;
+
+    debug!(offers = offers.len(), "account offers retrieved");
+    Ok(offers)
+}
+
+/// Ledger objects of `object_type` ("escrow", "check", "payment_channel", ...) linked to `account`
+/// in the last validated ledger, following markers. Objects `account` is the destination of are
+/// listed as well as the ones it owns.
+#[instrument(skip(client, timeout))]
+pub async fn account_objects<C: RawRequest>(
+    client: &C,
+    account: &str,
+    object_type: &str,
+    timeout: Duration,
+) -> Result<Vec<Value>> {
+    debug!("requesting account_objects");
+
+    let params = json!({ "account": account, "type": object_type, "limit": ACCOUNT_OBJECTS_PAGE_LIMIT });
+    let objects = request_pages(client, "account_objects", params, "account_objects", timeout).await?;
+
+    debug!(objects = objects.len(), "account objects retrieved");
+    Ok(objects)
+}
+
+// Collect `field` from every page of a validated-ledger listing, sending each marker back until
+// the node stops returning one. Later pages are read from the ledger of the first, so the listing
+// stays consistent when a new ledger validates in between.
+pub(crate) async fn request_pages<C: RawRequest, T: DeserializeOwned>(
+    client: &C,
+    command: &str,
+    mut params: Value,
+    field: &str,
+    timeout: Duration,
+) -> Result<Vec<T>> {
+    params["ledger_index"] = json!("validated");
+    let mut items = Vec::new();
+    loop {
+        let mut page = client.request_json(command, params.clone(), timeout).await?;
+        let page_items: Vec<T> = serde_json::from_value(page[field].take())
+            .map_err(|e| RippleError::client_source(format!("Malformed {} response", command), e))?;
+        items.extend(page_items);
+
+        if let Some(ledger_index) = page.get("ledger_index").filter(|index| index.is_u64()) {
+            params["ledger_index"] = ledger_index.clone();
+        }
+        match page.get_mut("marker").map(Value::take) {
+            Some(next) if !next.is_null() => params["marker"] = next,
+            _ => break,
+        }
+    }
+    Ok(items)
+}
+
+/// Find ways to deliver a payment in the current open ledger. Works over either transport.
+#[instrument(skip_all, fields(source = %request.source_account, destination = %request.destination_account))]
+pub async fn ripple_path_find<C: RawRequest>(
+    client: &C,
+    request: &PathRequest,
+    timeout: Duration,
+) -> Result<Vec<PathAlternative>> {
+    debug!("requesting ripple_path_find");
+
+    let mut result = client.request_json("ripple_path_find", request.to_json()?, timeout).await?;
+    let alternatives: Vec<PathAlternative> = serde_json::from_value(result["alternatives"].take())
+        .map_err(|e| RippleError::client_source("Malformed ripple_path_find response", e))?;
+
+    debug!(alternatives = alternatives.len(), "paths found");
+    Ok(alternatives)
+}
+
+/// Keep a path search open; the node sends new alternatives as ledgers close.
+/// Needs a WebSocket endpoint. Dropping the stream closes the search.
+#[instrument(skip_all, fields(source = %request.source_account, destination = %request.destination_account))]
+pub async fn path_find(
+    network: &NetworkConfig,
+    request: &PathRequest,
+    timeout: Duration,
+) -> Result<Subscription<PathFindUpdate>> {
+    let mut params = request.to_json()?;
+    params["subcommand"] = json!("create");
+    subscription::open_stream(network, "path_find", params, timeout).await
 }
@@ -0,0 +1,156 @@
+use crate::error::{Result, RippleError};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+const CODE_BYTES: usize = 20;
+const HEX_LENGTH: usize = CODE_BYTES * 2;
+// Symbols rippled allows in standard codes besides letters and digits
+const STANDARD_SYMBOLS: &str = "?!@#$%^&*<>(){}[]|";
+// Where a standard code sits inside its 160-bit form
+const STANDARD_OFFSET: usize = 12;
+
+/// Currency of an issued token, kept in the form the ledger uses:
+/// a 3-character standard code ("USD") or 40 uppercase hex digits.
+///
+/// Names that are not standard codes ("MYTOKEN", up to 20 bytes of UTF-8) are
+/// hex-encoded, and 160-bit codes that only wrap a standard code are turned back
+/// into it, so the same currency always compares equal. `XRP` is not an issued
+/// currency and is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CurrencyCode(String);
+
+impl CurrencyCode {
+    pub fn new(code: &str) -> Result<Self> {
+        let invalid = |reason: &str| RippleError::InvalidInput(format!("Invalid currency code {:?}: {}", code, reason));
+
+        if code.len() == 3 && code.is_ascii() {
+            return Self::standard(code).ok_or_else(|| invalid("standard codes use letters, digits and ?!@#$%^&*<>(){}[]|"));
+        }
+        if code.len() == HEX_LENGTH && code.bytes().all(|b| b.is_ascii_hexdigit()) {
+            let mut bytes = [0u8; CODE_BYTES];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&code[i * 2..i * 2 + 2], 16).map_err(|_| invalid("not hex"))?;
+            }
+            return Self::from_bytes(bytes).ok_or_else(|| invalid("a 160-bit code starting with 0x00 must hold a standard code"));
+        }
+        if code.is_empty() || code.len() > CODE_BYTES {
+            return Err(invalid("names are 1 to 20 bytes of UTF-8"));
+        }
+        if code.contains('\0') {
+            return Err(invalid("names cannot contain NUL"));
+        }
+
+        let mut bytes = [0u8; CODE_BYTES];
+        bytes[..code.len()].copy_from_slice(code.as_bytes());
+        Ok(Self(hex(&bytes)))
+    }
+
+    /// The form used on ledger and in rippled's JSON
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_standard(&self) -> bool {
+        self.0.len() == 3
+    }
+
+    /// The 160-bit form, as 40 uppercase hex digits
+    pub fn to_hex(&self) -> String {
+        if !self.is_standard() {
+            return self.0.clone();
+        }
+        let mut bytes = [0u8; CODE_BYTES];
+        bytes[STANDARD_OFFSET..STANDARD_OFFSET + 3].copy_from_slice(self.0.as_bytes());
+        hex(&bytes)
+    }
+
+    /// Human-readable name: the standard code, or the UTF-8 text of a hex code
+    /// (trailing zero bytes removed). None for hex codes that are not text.
+    pub fn name(&self) -> Option<String> {
+        if self.is_standard() {
+            return Some(self.0.clone());
+        }
+        let bytes: Vec<u8> = (0..CODE_BYTES)
+            .map(|i| u8::from_str_radix(&self.0[i * 2..i * 2 + 2], 16).unwrap_or(0))
+            .collect();
+        let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |last| last + 1);
+        std::str::from_utf8(&bytes[..end])
+            .ok()
+            .filter(|name| !name.is_empty() && !name.contains('\0') && !name.chars().any(char::is_control))
+            .map(str::to_string)
+    }
+
+    fn standard(code: &str) -> Option<Self> {
+        let allowed = code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || STANDARD_SYMBOLS.contains(c));
+        (allowed && code != "XRP").then(|| Self(code.to_string()))
+    }
+
+    fn from_bytes(bytes: [u8; CODE_BYTES]) -> Option<Self> {
+        if bytes[0] != 0 {
+            return Some(Self(hex(&bytes)));
+        }
+        // Only the standard layout may start with a zero byte
+        let code = &bytes[STANDARD_OFFSET..STANDARD_OFFSET + 3];
+        let padding_is_zero = bytes[..STANDARD_OFFSET]
+            .iter()
+            .chain(&bytes[STANDARD_OFFSET + 3..])
+            .all(|&b| b == 0);
+        if !padding_is_zero {
+            return None;
+        }
+        Self::standard(std::str::from_utf8(code).ok()?)
+    }
+}
+
+impl FromStr for CurrencyCode {
+    type Err = RippleError;
+
+    fn from_str(code: &str) -> Result<Self> {
+        Self::new(code)
+    }
+}
+
+impl fmt::Display for CurrencyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for CurrencyCode {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for CurrencyCode {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for CurrencyCode {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Serialize for CurrencyCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for CurrencyCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let code = Cow::<str>::deserialize(deserializer)?;
+        Self::new(&code).map_err(serde::de::Error::custom)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
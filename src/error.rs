@@ -1,44 +1,308 @@
+use crate::submission::EngineResult;
+use std::error::Error;
 use std::fmt;
+use std::time::Duration;
+
+/// Underlying error carried by a RippleError and exposed through `source()`
+pub type BoxError = Box<dyn Error + Send + Sync + 'static>;
+
+pub type Result<T, E = RippleError> = std::result::Result<T, E>;
+
+/// xrpl-rust exception carried in the source chain. Most of its exception
+/// types implement Display but not std::error::Error, so only the message is kept.
+#[derive(Debug)]
+pub struct XRPLException(String);
+
+impl fmt::Display for XRPLException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for XRPLException {}
+
+/// Conversion of the error types this crate wraps into a boxed source
+pub(crate) trait IntoSource {
+    fn into_source(self) -> BoxError;
+}
+
+macro_rules! display_source {
+    ($($exception:ty),* $(,)?) => {
+        $(impl IntoSource for $exception {
+            fn into_source(self) -> BoxError {
+                Box::new(XRPLException(self.to_string()))
+            }
+        })*
+    };
+}
+
+macro_rules! error_source {
+    ($($error:ty),* $(,)?) => {
+        $(impl IntoSource for $error {
+            fn into_source(self) -> BoxError {
+                Box::new(self)
+            }
+        })*
+    };
+}
+
+display_source!(
+    xrpl::asynch::exceptions::XRPLHelperException,
+    xrpl::wallet::exceptions::XRPLWalletException,
+);
+
+error_source!(
+    xrpl::asynch::clients::exceptions::XRPLClientException,
+    xrpl::core::exceptions::XRPLCoreException,
+    serde_json::Error,
+    tokio_tungstenite::tungstenite::Error,
+    reqwest::Error,
+    hex::FromHexError,
+    std::io::Error,
+);
 
 #[derive(Debug)]
 pub enum RippleError {
-    XRPLClient(String),
-    Transaction(String),
-    Verification(String),
-    Wallet(String),
-    Network(String),
+    /// Request to the XRPL node failed or returned something unexpected
+    XRPLClient {
+        message: String,
+        source: Option<BoxError>,
+    },
+    /// Ledger lookup for a specific account failed
+    Account {
+        account: String,
+        message: String,
+        source: Option<BoxError>,
+    },
+    /// Building, signing or submitting a transaction failed
+    Transaction {
+        message: String,
+        tx_hash: Option<String>,
+        source: Option<BoxError>,
+    },
+    /// Node did not apply the transaction, but resubmitting it later may succeed (ter/tel)
+    Retryable {
+        engine_result: EngineResult,
+        message: String,
+        tx_hash: Option<String>,
+    },
+    /// Transaction failed permanently (tec/tef/tem)
+    Rejected {
+        engine_result: EngineResult,
+        message: String,
+        tx_hash: Option<String>,
+    },
+    /// Transaction is past its LastLedgerSequence and can never be included
+    Expired {
+        current_ledger: u32,
+        last_ledger_sequence: u32,
+        tx_hash: Option<String>,
+    },
+    /// Node did not answer `command` within the request timeout
+    Timeout {
+        command: String,
+        timeout: Duration,
+    },
+    /// Transaction could not be checked against the expected transfer
+    Verification {
+        message: String,
+        tx_hash: String,
+        source: Option<BoxError>,
+    },
+    Wallet {
+        message: String,
+        source: Option<BoxError>,
+    },
+    /// Connection to the endpoint could not be established
+    Network {
+        message: String,
+        source: Option<BoxError>,
+    },
+    /// Loading or saving persisted state (e.g. a watcher's ledger cursor) failed
+    Storage {
+        message: String,
+        source: Option<BoxError>,
+    },
     InvalidInput(String),
 }
 
+impl RippleError {
+    pub(crate) fn client(message: impl Into<String>) -> Self {
+        RippleError::XRPLClient {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub(crate) fn client_source(message: impl Into<String>, source: impl IntoSource) -> Self {
+        RippleError::XRPLClient {
+            message: message.into(),
+            source: Some(source.into_source()),
+        }
+    }
+
+    pub(crate) fn account(account: &str, message: impl Into<String>, source: Option<BoxError>) -> Self {
+        RippleError::Account {
+            account: account.to_string(),
+            message: message.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn transaction(message: impl Into<String>, source: impl IntoSource) -> Self {
+        RippleError::Transaction {
+            message: message.into(),
+            tx_hash: None,
+            source: Some(source.into_source()),
+        }
+    }
+
+    pub(crate) fn wallet(source: impl IntoSource) -> Self {
+        RippleError::Wallet {
+            message: "Failed to derive wallet from secret".to_string(),
+            source: Some(source.into_source()),
+        }
+    }
+
+    pub(crate) fn network(message: impl Into<String>, source: impl IntoSource) -> Self {
+        RippleError::Network {
+            message: message.into(),
+            source: Some(source.into_source()),
+        }
+    }
+
+    pub(crate) fn storage(message: impl Into<String>, source: impl IntoSource) -> Self {
+        RippleError::Storage {
+            message: message.into(),
+            source: Some(source.into_source()),
+        }
+    }
+
+    /// Hash of the transaction the error relates to, when known
+    pub fn tx_hash(&self) -> Option<&str> {
+        match self {
+            RippleError::Transaction { tx_hash, .. }
+            | RippleError::Retryable { tx_hash, .. }
+            | RippleError::Rejected { tx_hash, .. }
+            | RippleError::Expired { tx_hash, .. } => tx_hash.as_deref(),
+            RippleError::Verification { tx_hash, .. } => Some(tx_hash),
+            _ => None,
+        }
+    }
+
+    /// Engine result reported by the node, e.g. `tecUNFUNDED_PAYMENT`
+    pub fn engine_result(&self) -> Option<&EngineResult> {
+        match self {
+            RippleError::Retryable { engine_result, .. }
+            | RippleError::Rejected { engine_result, .. } => Some(engine_result),
+            _ => None,
+        }
+    }
+
+    /// Whether submitting the same transaction again may still succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, RippleError::Retryable { .. })
+    }
+
+    /// Whether the node stopped answering, as opposed to answering with an error
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, RippleError::Timeout { .. })
+    }
+
+    pub fn account_address(&self) -> Option<&str> {
+        match self {
+            RippleError::Account { account, .. } => Some(account),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for RippleError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            RippleError::XRPLClient(msg) => write!(f, "XRPL Client Error: {}", msg),
-            RippleError::Transaction(msg) => write!(f, "Transaction Error: {}", msg),
-            RippleError::Verification(msg) => write!(f, "Verification Error: {}", msg),
-            RippleError::Wallet(msg) => write!(f, "Wallet Error: {}", msg),
-            RippleError::Network(msg) => write!(f, "Network Error: {}", msg),
+            RippleError::XRPLClient { message, .. } => write!(f, "XRPL Client Error: {}", message),
+            RippleError::Account {
+                account, message, ..
+            } => write!(f, "Account Error ({}): {}", account, message),
+            RippleError::Transaction {
+                message, tx_hash, ..
+            } => {
+                write!(f, "Transaction Error: {}", message)?;
+                if let Some(tx_hash) = tx_hash {
+                    write!(f, " (tx {})", tx_hash)?;
+                }
+                Ok(())
+            }
+            RippleError::Retryable {
+                engine_result,
+                message,
+                ..
+            } => write!(f, "Transaction Not Applied (retryable): {}: {}", engine_result, message),
+            RippleError::Rejected {
+                engine_result,
+                message,
+                ..
+            } => write!(f, "Transaction Rejected: {}: {}", engine_result, message),
+            RippleError::Expired {
+                current_ledger,
+                last_ledger_sequence,
+                ..
+            } => write!(
+                f,
+                "Transaction Expired: current ledger {} >= expiration {}",
+                current_ledger, last_ledger_sequence
+            ),
+            RippleError::Timeout { command, timeout } => write!(
+                f,
+                "Request Timed Out: no response to {} within {:?}",
+                command, timeout
+            ),
+            RippleError::Verification {
+                message, tx_hash, ..
+            } => write!(f, "Verification Error (tx {}): {}", tx_hash, message),
+            RippleError::Wallet { message, .. } => write!(f, "Wallet Error: {}", message),
+            RippleError::Network { message, .. } => write!(f, "Network Error: {}", message),
+            RippleError::Storage { message, .. } => write!(f, "Storage Error: {}", message),
             RippleError::InvalidInput(msg) => write!(f, "Invalid Input: {}", msg),
         }
     }
 }
 
-impl std::error::Error for RippleError {}
+impl Error for RippleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RippleError::XRPLClient { source, .. }
+            | RippleError::Account { source, .. }
+            | RippleError::Transaction { source, .. }
+            | RippleError::Verification { source, .. }
+            | RippleError::Wallet { source, .. }
+            | RippleError::Network { source, .. }
+            | RippleError::Storage { source, .. } => source
+                .as_deref()
+                .map(|source| source as &(dyn Error + 'static)),
+            RippleError::Retryable { .. }
+            | RippleError::Rejected { .. }
+            | RippleError::Expired { .. }
+            | RippleError::Timeout { .. }
+            | RippleError::InvalidInput(_) => None,
+        }
+    }
+}
 
 impl From<xrpl::asynch::exceptions::XRPLHelperException> for RippleError {
     fn from(err: xrpl::asynch::exceptions::XRPLHelperException) -> Self {
-        RippleError::XRPLClient(format!("{:?}", err))
+        RippleError::client_source("XRPL helper request failed", err)
     }
 }
 
 impl From<xrpl::wallet::exceptions::XRPLWalletException> for RippleError {
     fn from(err: xrpl::wallet::exceptions::XRPLWalletException) -> Self {
-        RippleError::Wallet(format!("{:?}", err))
+        RippleError::wallet(err)
     }
 }
 
 impl From<xrpl::asynch::clients::exceptions::XRPLClientException> for RippleError {
     fn from(err: xrpl::asynch::clients::exceptions::XRPLClientException) -> Self {
-        RippleError::XRPLClient(format!("{:?}", err))
+        RippleError::client_source("XRPL request failed", err)
     }
 }
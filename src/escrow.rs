@@ -0,0 +1,634 @@
+use crate::amount::Drops;
+use crate::client::{self, RawRequest, with_timeout};
+use crate::error::{Result, RippleError};
+use crate::offline_signing::{OfflineTransactionParams, sign_offline_json};
+use crate::ripple_time::{from_ripple_time, to_ripple_time};
+use crate::secret::Secret;
+use crate::submission::{ValidatedTransaction, check_submit_result, wait_for_submit_result};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime};
+use tracing::{Span, debug, info, instrument, warn};
+use zeroize::Zeroizing;
+use xrpl::{
+    asynch::{
+        clients::XRPLAsyncClient,
+        ledger::get_fee,
+        transaction::{autofill, sign_and_submit},
+    },
+    models::{
+        XRPAmount,
+        results::submit::Submit as SubmitResult,
+        transactions::{escrow_cancel::EscrowCancel, escrow_create::EscrowCreate, escrow_finish::EscrowFinish},
+    },
+};
+
+// PREIMAGE-SHA-256 in DER: the fulfillment is [A0 len [80 n preimage]], the condition is
+// [A0 25 [80 20 sha256(preimage)] [81 01 n]], n (the preimage length) being its cost
+const CONDITION_PREFIX: [u8; 4] = [0xA0, 0x25, 0x80, 0x20];
+const CONDITION_COST_TAG: [u8; 2] = [0x81, 0x01];
+const CONDITION_LEN: usize = 39;
+const FULFILLMENT_TAG: u8 = 0xA0;
+const PREIMAGE_TAG: u8 = 0x80;
+// Keeps every length within a single DER length byte
+const MAX_PREIMAGE_LEN: usize = 64;
+const GENERATED_PREIMAGE_LEN: usize = 32;
+
+/// PREIMAGE-SHA-256 crypto-condition. The condition goes on the EscrowCreate; the fulfillment
+/// reveals the preimage, so keep it secret until the escrow is to be finished.
+#[derive(Debug, Clone)]
+pub struct PreimageCondition {
+    condition: String,
+    fulfillment: Secret,
+}
+
+impl PreimageCondition {
+    /// Condition over a random 32-byte preimage
+    pub fn generate() -> Self {
+        let mut preimage = Zeroizing::new([0u8; GENERATED_PREIMAGE_LEN]);
+        OsRng.fill_bytes(preimage.as_mut());
+        Self::encode(preimage.as_ref())
+    }
+
+    /// Condition over a preimage of 1 to 64 bytes
+    pub fn from_preimage(preimage: &[u8]) -> Result<Self> {
+        if preimage.is_empty() || preimage.len() > MAX_PREIMAGE_LEN {
+            return Err(RippleError::InvalidInput(format!(
+                "Preimage must be 1 to {} bytes, got {}",
+                MAX_PREIMAGE_LEN,
+                preimage.len()
+            )));
+        }
+        Ok(Self::encode(preimage))
+    }
+
+    /// Read back a hex fulfillment, e.g. one handed over by the escrow's creator
+    pub fn from_fulfillment(fulfillment: &str) -> Result<Self> {
+        let invalid = || RippleError::InvalidInput("Not a PREIMAGE-SHA-256 fulfillment".to_string());
+        let bytes = Zeroizing::new(hex::decode(fulfillment).map_err(|_| invalid())?);
+        match bytes.as_slice() {
+            [FULFILLMENT_TAG, len, PREIMAGE_TAG, n, preimage @ ..]
+                if *len as usize == preimage.len() + 2 && *n as usize == preimage.len() =>
+            {
+                Self::from_preimage(preimage)
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Hex condition, for `EscrowBuilder::with_condition`
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
+
+    /// Hex fulfillment, for `finish_escrow`
+    pub fn fulfillment(&self) -> &Secret {
+        &self.fulfillment
+    }
+
+    // Length of the fulfillment in bytes, which sets the EscrowFinish fee
+    fn fulfillment_len(&self) -> usize {
+        self.fulfillment.expose_secret().len() / 2
+    }
+
+    fn encode(preimage: &[u8]) -> Self {
+        let n = preimage.len() as u8;
+        let mut condition = CONDITION_PREFIX.to_vec();
+        condition.extend_from_slice(&Sha256::digest(preimage));
+        condition.extend_from_slice(&CONDITION_COST_TAG);
+        condition.push(n);
+
+        let mut fulfillment = Zeroizing::new(vec![FULFILLMENT_TAG, n + 2, PREIMAGE_TAG, n]);
+        fulfillment.extend_from_slice(preimage);
+        Self {
+            condition: hex::encode_upper(condition),
+            fulfillment: Secret::new(hex::encode_upper(fulfillment.as_slice())),
+        }
+    }
+}
+
+/// EscrowCreate: hold `amount` for `destination` until FinishAfter has passed and/or the
+/// condition is fulfilled. After CancelAfter, anyone can return it to the owner instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowBuilder {
+    destination: String,
+    amount: Drops,
+    finish_after: Option<SystemTime>,
+    cancel_after: Option<SystemTime>,
+    condition: Option<String>,
+    destination_tag: Option<u32>,
+}
+
+impl EscrowBuilder {
+    pub fn new(destination: &str, amount: Drops) -> Self {
+        Self {
+            destination: destination.to_string(),
+            amount,
+            finish_after: None,
+            cancel_after: None,
+            condition: None,
+            destination_tag: None,
+        }
+    }
+
+    /// The escrow cannot be finished until a ledger closes after `time`
+    pub fn finish_after(mut self, time: SystemTime) -> Self {
+        self.finish_after = Some(time);
+        self
+    }
+
+    /// The escrow expires once a ledger closes after `time`, and can then only be cancelled
+    pub fn cancel_after(mut self, time: SystemTime) -> Self {
+        self.cancel_after = Some(time);
+        self
+    }
+
+    /// Hex PREIMAGE-SHA-256 condition the finishing transaction must fulfill
+    pub fn with_condition(mut self, condition: &str) -> Self {
+        self.condition = Some(condition.to_uppercase());
+        self
+    }
+
+    pub fn with_destination_tag(mut self, destination_tag: u32) -> Self {
+        self.destination_tag = Some(destination_tag);
+        self
+    }
+
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    pub fn amount(&self) -> Drops {
+        self.amount
+    }
+
+    pub fn condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+
+    // rippled rejects escrows that could be finished by anyone at any time, and ones that
+    // expire before they can be finished
+    fn validate(&self) -> Result<()> {
+        if self.amount.is_zero() {
+            return Err(RippleError::InvalidInput("Escrow amount must be positive".to_string()));
+        }
+        if self.finish_after.is_none() && self.condition.is_none() {
+            return Err(RippleError::InvalidInput(
+                "Escrow needs a finish_after time or a condition".to_string(),
+            ));
+        }
+        if let (Some(finish), Some(cancel)) = (self.finish_after, self.cancel_after)
+            && to_ripple_time(cancel)? <= to_ripple_time(finish)?
+        {
+            return Err(RippleError::InvalidInput(
+                "Escrow cancel_after must be later than finish_after".to_string(),
+            ));
+        }
+        if let Some(condition) = &self.condition {
+            validate_condition(condition)?;
+        }
+        Ok(())
+    }
+
+    fn to_transaction(&self, owner: &str) -> Result<EscrowCreate<'static>> {
+        self.validate()?;
+        Ok(EscrowCreate::new(
+            Cow::Owned(owner.to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            XRPAmount::from(self.amount.to_string()),
+            Cow::Owned(self.destination.clone()),
+            self.cancel_after.map(to_ripple_time).transpose()?,
+            self.condition.clone().map(Cow::Owned),
+            self.destination_tag,
+            self.finish_after.map(to_ripple_time).transpose()?,
+        ))
+    }
+}
+
+/// Submitted EscrowCreate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacedEscrow {
+    pub tx_hash: String,
+    pub owner: String,
+    /// Sequence of the EscrowCreate, which identifies the escrow for `finish_escrow` and `cancel_escrow`
+    pub sequence: u32,
+}
+
+/// Escrow held in the ledger, as listed by `list_escrows`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Escrow {
+    #[serde(rename = "Account")]
+    pub owner: String,
+    #[serde(rename = "Destination")]
+    pub destination: String,
+    #[serde(rename = "Amount")]
+    pub amount: Drops,
+    #[serde(rename = "Condition", default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// Seconds since the Ripple epoch
+    #[serde(rename = "FinishAfter", default, skip_serializing_if = "Option::is_none")]
+    pub finish_after: Option<u32>,
+    #[serde(rename = "CancelAfter", default, skip_serializing_if = "Option::is_none")]
+    pub cancel_after: Option<u32>,
+    #[serde(rename = "DestinationTag", default, skip_serializing_if = "Option::is_none")]
+    pub destination_tag: Option<u32>,
+    /// Escrows are never modified, so this is the EscrowCreate
+    #[serde(rename = "PreviousTxnID")]
+    pub create_tx_hash: String,
+    /// Ledger entry ID of the escrow
+    #[serde(rename = "index")]
+    pub escrow_id: String,
+    /// Sequence (or ticket) of the EscrowCreate, read from that transaction. None when the
+    /// node's history no longer holds it; look it up on a full-history server instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u32>,
+}
+
+impl Escrow {
+    pub fn finish_after_time(&self) -> Option<SystemTime> {
+        self.finish_after.map(from_ripple_time)
+    }
+
+    pub fn cancel_after_time(&self) -> Option<SystemTime> {
+        self.cancel_after.map(from_ripple_time)
+    }
+
+    /// True when a ledger closing at `time` can finish the escrow (given the fulfillment,
+    /// for a conditional one)
+    pub fn can_finish_at(&self, time: SystemTime) -> bool {
+        self.finish_after_time().is_none_or(|finish| time > finish)
+            && self.cancel_after_time().is_none_or(|cancel| time <= cancel)
+    }
+
+    /// True when a ledger closing at `time` can cancel the escrow
+    pub fn can_cancel_at(&self, time: SystemTime) -> bool {
+        self.cancel_after_time().is_some_and(|cancel| time > cancel)
+    }
+}
+
+/// Escrows `account` owns or is the destination of, in the last validated ledger.
+/// Each escrow's EscrowCreate is looked up for the sequence that finishes or cancels it.
+#[instrument(skip(client, timeout))]
+pub async fn list_escrows<C: RawRequest>(client: &C, account: &str, timeout: Duration) -> Result<Vec<Escrow>> {
+    let objects = client::account_objects(client, account, "escrow", timeout).await?;
+
+    let mut escrows = Vec::with_capacity(objects.len());
+    for object in objects {
+        let mut escrow: Escrow = serde_json::from_value(object)
+            .map_err(|e| RippleError::client_source("Malformed escrow in account_objects response", e))?;
+        let lookup = json!({ "transaction": escrow.create_tx_hash });
+        escrow.sequence = match client.request_json("tx", lookup, timeout).await {
+            Ok(create) => create_sequence(&create),
+            Err(e @ (RippleError::Network { .. } | RippleError::Timeout { .. })) => return Err(e),
+            // Older than the node's history; the rest of the listing is still valid
+            Err(e) => {
+                warn!(create_tx_hash = %escrow.create_tx_hash, error = %e, "EscrowCreate not available");
+                None
+            }
+        };
+        escrows.push(escrow);
+    }
+
+    debug!(escrows = escrows.len(), "escrows listed");
+    Ok(escrows)
+}
+
+fn create_sequence(create: &Value) -> Option<u32> {
+    // API v2 nests the transaction under tx_json
+    let create = create.get("tx_json").unwrap_or(create);
+    ["Sequence", "TicketSequence"]
+        .iter()
+        .filter_map(|field| create.get(*field).and_then(Value::as_u64))
+        .find(|&sequence| sequence != 0)
+        .and_then(|sequence| u32::try_from(sequence).ok())
+}
+
+/// Lock funds in an escrow
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn create_escrow<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    escrow: &EscrowBuilder,
+    timeout: Duration,
+) -> Result<PlacedEscrow> {
+    let (result, sequence) = submit_escrow_create(client, owner_secret, escrow, timeout).await?;
+    let placed = PlacedEscrow {
+        tx_hash: check_submit_result(&result)?,
+        owner: owner_secret.wallet()?.classic_address.clone(),
+        sequence,
+    };
+
+    info!(tx_hash = %placed.tx_hash, sequence = placed.sequence, "escrow create submitted");
+    Ok(placed)
+}
+
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn create_escrow_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    escrow: &EscrowBuilder,
+    timeout: Duration,
+) -> Result<(PlacedEscrow, ValidatedTransaction)> {
+    let (result, sequence) = submit_escrow_create(client, owner_secret, escrow, timeout).await?;
+    let validated = wait_for_submit_result(client, &result, timeout).await?;
+    let placed = PlacedEscrow {
+        tx_hash: validated.tx_hash.clone(),
+        owner: owner_secret.wallet()?.classic_address.clone(),
+        sequence,
+    };
+    Ok((placed, validated))
+}
+
+async fn submit_escrow_create<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    escrow: &EscrowBuilder,
+    timeout: Duration,
+) -> Result<(SubmitResult<'static>, u32)> {
+    let wallet = owner_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+    let mut escrow_create = escrow.to_transaction(&wallet.classic_address)?;
+
+    debug!(
+        destination = %escrow.destination,
+        drops = %escrow.amount,
+        finish_after = ?escrow_create.finish_after,
+        cancel_after = ?escrow_create.cancel_after,
+        conditional = escrow.condition.is_some(),
+        "submitting escrow create"
+    );
+
+    let result = with_timeout("submit", timeout, async {
+        sign_and_submit(&mut escrow_create, client, &wallet, true, false)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to submit escrow create", e))
+    })
+    .await?;
+
+    debug!(engine_result = %result.engine_result, "submit response received");
+    // Autofilled before signing
+    let sequence = escrow_create
+        .common_fields
+        .sequence
+        .ok_or_else(|| RippleError::client("Escrow was submitted without a Sequence"))?;
+    Ok((result, sequence))
+}
+
+/// Deliver an escrow to its destination. Anyone can finish a time-based escrow; a conditional
+/// one needs `fulfillment`, and costs more the longer the fulfillment is.
+#[instrument(skip(client, finisher_secret, fulfillment), fields(account))]
+pub async fn finish_escrow<C: XRPLAsyncClient>(
+    client: &C,
+    finisher_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    fulfillment: Option<&PreimageCondition>,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_escrow_finish(client, finisher_secret, owner, sequence, fulfillment, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "escrow finish submitted");
+    Ok(tx_hash)
+}
+
+#[instrument(skip(client, finisher_secret, fulfillment), fields(account))]
+pub async fn finish_escrow_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    finisher_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    fulfillment: Option<&PreimageCondition>,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_escrow_finish(client, finisher_secret, owner, sequence, fulfillment, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_escrow_finish<C: XRPLAsyncClient>(
+    client: &C,
+    finisher_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    fulfillment: Option<&PreimageCondition>,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let wallet = finisher_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+    let mut escrow_finish = escrow_finish(&wallet.classic_address, owner, sequence, fulfillment);
+
+    // xrpl-rust's autofill does not see the fulfillment, so the fee is set here
+    if let Some(fulfillment) = fulfillment {
+        let base_fee = with_timeout("fee", timeout, async {
+            get_fee(client, None, None)
+                .await
+                .map_err(|e| RippleError::client_source("Failed to get the network fee", e))
+        })
+        .await?;
+        let base_fee: Drops = base_fee.0.parse()?;
+        escrow_finish.common_fields.fee = Some(XRPAmount::from(finish_fee(base_fee, fulfillment)?.to_string()));
+    }
+    with_timeout("autofill", timeout, async {
+        autofill(&mut escrow_finish, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill escrow finish", e))
+    })
+    .await?;
+
+    debug!(fee = ?escrow_finish.common_fields.fee, conditional = fulfillment.is_some(), "submitting escrow finish");
+
+    let result = with_timeout("submit", timeout, async {
+        sign_and_submit(&mut escrow_finish, client, &wallet, false, false)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to submit escrow finish", e))
+    })
+    .await?;
+
+    debug!(engine_result = %result.engine_result, "submit response received");
+    Ok(result)
+}
+
+/// Return an expired escrow to its owner. Anyone can cancel once CancelAfter has passed.
+#[instrument(skip(client, canceller_secret), fields(account))]
+pub async fn cancel_escrow<C: XRPLAsyncClient>(
+    client: &C,
+    canceller_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_escrow_cancel(client, canceller_secret, owner, sequence, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "escrow cancel submitted");
+    Ok(tx_hash)
+}
+
+#[instrument(skip(client, canceller_secret), fields(account))]
+pub async fn cancel_escrow_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    canceller_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_escrow_cancel(client, canceller_secret, owner, sequence, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_escrow_cancel<C: XRPLAsyncClient>(
+    client: &C,
+    canceller_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let wallet = canceller_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+    let mut escrow_cancel = escrow_cancel(&wallet.classic_address, owner, sequence);
+
+    debug!("submitting escrow cancel");
+
+    let result = with_timeout("submit", timeout, async {
+        sign_and_submit(&mut escrow_cancel, client, &wallet, true, false)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to submit escrow cancel", e))
+    })
+    .await?;
+
+    debug!(engine_result = %result.engine_result, "submit response received");
+    Ok(result)
+}
+
+/// Sign an EscrowCreate offline with parameters from `gather_transaction_params`
+#[instrument(skip(owner_secret, escrow, params), fields(account))]
+pub fn offline_sign_escrow_create(
+    owner_secret: &Secret,
+    escrow: &EscrowBuilder,
+    params: &OfflineTransactionParams,
+) -> Result<String> {
+    let wallet = owner_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+    let escrow_create = escrow.to_transaction(&wallet.classic_address)?;
+    sign_offline(&escrow_create, &wallet, params)
+}
+
+/// Sign an EscrowFinish offline. The fee for a fulfillment is scaled up from `params.fee`.
+#[instrument(skip(finisher_secret, fulfillment, params), fields(account))]
+pub fn offline_sign_escrow_finish(
+    finisher_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    fulfillment: Option<&PreimageCondition>,
+    params: &OfflineTransactionParams,
+) -> Result<String> {
+    let wallet = finisher_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+    let mut escrow_finish = escrow_finish(&wallet.classic_address, owner, sequence, fulfillment);
+    if let Some(fulfillment) = fulfillment {
+        let base_fee: Drops = params.fee.parse()?;
+        escrow_finish.common_fields.fee = Some(XRPAmount::from(finish_fee(base_fee, fulfillment)?.to_string()));
+    }
+    sign_offline(&escrow_finish, &wallet, params)
+}
+
+/// Sign an EscrowCancel offline
+#[instrument(skip(canceller_secret, params), fields(account))]
+pub fn offline_sign_escrow_cancel(
+    canceller_secret: &Secret,
+    owner: &str,
+    sequence: u32,
+    params: &OfflineTransactionParams,
+) -> Result<String> {
+    let wallet = canceller_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+    sign_offline(&escrow_cancel(&wallet.classic_address, owner, sequence), &wallet, params)
+}
+
+fn sign_offline(
+    transaction: &impl Serialize,
+    wallet: &xrpl::wallet::Wallet,
+    params: &OfflineTransactionParams,
+) -> Result<String> {
+    let json = serde_json::to_value(transaction).map_err(|e| RippleError::transaction("Failed to encode escrow", e))?;
+    let signed_blob = sign_offline_json(json, wallet, params)?;
+
+    info!(
+        blob_len = signed_blob.len(),
+        last_ledger_sequence = params.last_ledger_sequence,
+        "escrow transaction signed offline"
+    );
+    Ok(signed_blob)
+}
+
+fn escrow_finish(
+    account: &str,
+    owner: &str,
+    sequence: u32,
+    fulfillment: Option<&PreimageCondition>,
+) -> EscrowFinish<'static> {
+    EscrowFinish::new(
+        Cow::Owned(account.to_string()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Cow::Owned(owner.to_string()),
+        sequence,
+        fulfillment.map(|f| Cow::Owned(f.condition.clone())),
+        fulfillment.map(|f| Cow::Owned(f.fulfillment.expose_secret().to_string())),
+    )
+}
+
+fn escrow_cancel(account: &str, owner: &str, sequence: u32) -> EscrowCancel<'static> {
+    EscrowCancel::new(
+        Cow::Owned(account.to_string()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Cow::Owned(owner.to_string()),
+        sequence,
+    )
+}
+
+// rippled charges 33 base fees plus one more per 16 bytes of fulfillment
+fn finish_fee(base_fee: Drops, fulfillment: &PreimageCondition) -> Result<Drops> {
+    let multiplier = 33 + fulfillment.fulfillment_len() as u64 / 16;
+    base_fee
+        .as_u64()
+        .checked_mul(multiplier)
+        .ok_or_else(|| RippleError::InvalidInput(format!("Fee of {} drops is too high", base_fee)))
+        .and_then(Drops::new)
+}
+
+fn validate_condition(condition: &str) -> Result<()> {
+    let bytes = hex::decode(condition).unwrap_or_default();
+    let valid = bytes.len() == CONDITION_LEN
+        && bytes.starts_with(&CONDITION_PREFIX)
+        && bytes[36..38] == CONDITION_COST_TAG
+        && (1..=0x7F).contains(&bytes[38]);
+    if !valid {
+        return Err(RippleError::InvalidInput(format!("{} is not a PREIMAGE-SHA-256 condition", condition)));
+    }
+    Ok(())
+}
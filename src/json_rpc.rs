@@ -0,0 +1,114 @@
+use crate::error::{Result, RippleError};
+use serde_json::{Map, Value, json};
+use tracing::warn;
+use url::Url;
+use xrpl::{
+    asynch::clients::{XRPLJsonRpcException, client::XRPLClient, exceptions::XRPLClientResult},
+    models::{requests::XRPLRequest, results::XRPLResponse},
+};
+
+// Fields rippled nests inside `result` over JSON-RPC but sends at the top level over WebSocket
+const ENVELOPE_FIELDS: [&str; 6] = ["status", "error", "error_code", "error_message", "request", "warnings"];
+
+/// rippled JSON-RPC client for environments that only allow outbound HTTP(S).
+/// Responses are reshaped into the WebSocket envelope, so `error` codes such as
+/// `txnNotFound` surface exactly as they do over the default transport.
+pub struct JsonRpcClient {
+    url: Url,
+    http: reqwest::Client,
+}
+
+impl JsonRpcClient {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    // Send `command` as raw JSON and return the `result` of its response, for requests
+    // whose xrpl-rust models cannot express them (path finding)
+    pub(crate) async fn request_json(&self, command: &str, mut params: Value) -> Result<Value> {
+        params["command"] = json!(command);
+        let body = to_json_rpc(params).map_err(RippleError::client)?;
+        let response = self
+            .http
+            .post(self.url.as_str())
+            .json(&body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| RippleError::network(format!("{} request to {} failed", command, self.url), e))?;
+        let response: Value = response
+            .json()
+            .await
+            .map_err(|e| RippleError::network(format!("Malformed {} response", command), e))?;
+
+        let mut envelope = from_json_rpc(response);
+        if envelope["status"] == "error" {
+            let error = envelope["error"].as_str().unwrap_or("Unexpected response type");
+            warn!(command, error, "request failed");
+            return Err(RippleError::client(format!("{} failed: {}", command, error)));
+        }
+        Ok(envelope["result"].take())
+    }
+}
+
+impl XRPLClient for JsonRpcClient {
+    async fn request_impl<'a: 'b, 'b>(
+        &self,
+        request: XRPLRequest<'a>,
+    ) -> XRPLClientResult<XRPLResponse<'b>> {
+        let body = to_json_rpc(serde_json::to_value(&request)?)
+            .map_err(|message| XRPLJsonRpcException::RequestError(message.to_string()))?;
+        let response = self
+            .http
+            .post(self.url.as_str())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        let response: Value = response.json().await?;
+
+        Ok(serde_json::from_value(from_json_rpc(response))?)
+    }
+
+    fn get_host(&self) -> Url {
+        self.url.clone()
+    }
+}
+
+// {"command": "tx", ...} -> {"method": "tx", "params": [{...}]}
+fn to_json_rpc(request: Value) -> Result<Value, &'static str> {
+    let Value::Object(mut params) = request else {
+        return Err("Request is not a JSON object");
+    };
+    let method = params.remove("command").ok_or("Request has no command")?;
+    params.remove("id");
+
+    Ok(json!({ "method": method, "params": [params] }))
+}
+
+// {"result": {"status": "error", "error": ..., ...}} -> {"status": "error", "error": ..., "type": "response"}
+fn from_json_rpc(response: Value) -> Value {
+    let mut result = match response {
+        Value::Object(mut response) => match response.remove("result") {
+            Some(Value::Object(result)) => result,
+            _ => Map::new(),
+        },
+        _ => Map::new(),
+    };
+
+    let mut envelope = Map::new();
+    envelope.insert("type".to_string(), json!("response"));
+    for field in ENVELOPE_FIELDS {
+        if let Some(value) = result.remove(field) {
+            envelope.insert(field.to_string(), value);
+        }
+    }
+    if envelope.get("status").and_then(Value::as_str) != Some("error") {
+        envelope.insert("result".to_string(), Value::Object(result));
+    }
+
+    Value::Object(envelope)
+}
@@ -0,0 +1,26 @@
+use crate::error::{Result, RippleError};
+use sha2::{Digest, Sha512};
+use xrpl::core::addresscodec::decode_classic_address;
+
+// Ledger object IDs are the first half of SHA-512 over the object type's space key and its fields
+const OBJECT_ID_LEN: usize = 32;
+
+/// ID of the object in ledger space `space` created by `accounts`' transaction with sequence `sequence`
+pub(crate) fn ledger_object_id(space: [u8; 2], accounts: &[&str], sequence: u32) -> Result<String> {
+    let mut key = space.to_vec();
+    for account in accounts {
+        let account_id = decode_classic_address(account)
+            .map_err(|_| RippleError::InvalidInput(format!("{} is not a classic address", account)))?;
+        key.extend_from_slice(&account_id);
+    }
+    key.extend_from_slice(&sequence.to_be_bytes());
+    Ok(hex::encode_upper(&Sha512::digest(&key)[..OBJECT_ID_LEN]))
+}
+
+/// Bytes of a ledger object ID given in hex; `kind` names the object in the error
+pub(crate) fn ledger_object_id_bytes(id: &str, kind: &str) -> Result<Vec<u8>> {
+    hex::decode(id)
+        .ok()
+        .filter(|bytes| bytes.len() == OBJECT_ID_LEN)
+        .ok_or_else(|| RippleError::InvalidInput(format!("{} is not a {} ID", id, kind)))
+}
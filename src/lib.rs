@@ -1,51 +1,217 @@
+pub mod amount;
+pub mod checks;
 pub mod client;
+pub mod currency;
 pub mod error;
+pub mod escrow;
+pub mod json_rpc;
+mod ledger_object;
+pub mod network;
+pub mod offers;
 pub mod offline_signing;
+pub mod payment_channels;
+pub mod payment_options;
+pub mod ripple_time;
+pub mod secret;
+pub mod submission;
+pub mod subscription;
+pub mod supervisor;
 pub mod transactions;
 pub mod verification;
+pub mod watcher;
 
-use anyhow::Result;
-use xrpl::{
-    asynch::clients::{AsyncWebSocketClient, WebSocketOpen},
-    wallet::Wallet,
-};
+use std::time::Duration;
+use tracing::{debug, info};
+use xrpl::wallet::Wallet;
 
-pub use error::RippleError;
+pub use amount::{CurrencyAmount, Drops, IssuedValue};
+pub use checks::{CheckBuilder, CheckCashAmount, CheckEntry, PlacedCheck};
+pub use client::{
+    AccountOffer, BookOffer, Client, DEFAULT_REQUEST_TIMEOUT, Path, PathAlternative, PathFindUpdate, PathRequest, PathStep,
+    RawRequest, WebSocketClient,
+};
+pub use currency::CurrencyCode;
+pub use json_rpc::JsonRpcClient;
+pub use error::{Result, RippleError};
+pub use escrow::{Escrow, EscrowBuilder, PlacedEscrow, PreimageCondition};
+pub use network::{Network, NetworkConfig, Transport};
+pub use offers::{FillQuote, OfferBuilder, OfferFill, PlacedOffer};
+pub use payment_channels::{
+    ChannelBuilder, ChannelClaimBuilder, ChannelTracker, PaymentChannel, PlacedChannel, SignedClaim,
+};
+pub use payment_options::{Memo, PaymentOptions};
+pub use ripple_time::{from_ripple_time, to_ripple_time};
+pub use secret::Secret;
+pub use submission::{EngineResult, EngineResultClass, ValidatedTransaction};
+pub use subscription::{LedgerClosed, Subscription, TransactionEvent};
+pub use supervisor::{ReconnectPolicy, SupervisedClient};
+pub use transactions::PaymentBuilder;
+pub use verification::{Check, CheckKind, VerificationReport};
+pub use watcher::{CursorPosition, FileCursor, IncomingPayment, LedgerCursor, MemoryCursor, PaymentWatcher};
 
 pub use xrpl::{
-    asynch::clients::client::XRPLClient, models::transactions::CommonTransactionBuilder,
+    asynch::clients::{XRPLAsyncClient, client::XRPLClient},
+    models::transactions::CommonTransactionBuilder,
 };
 
-pub struct XRPLManager {
-    client: AsyncWebSocketClient<xrpl::asynch::clients::SingleExecutorMutex, WebSocketOpen>,
+/// High-level XRPL operations over any `XRPLClient` transport
+/// (WebSocket or JSON-RPC over HTTP by default, or a test double)
+pub struct XRPLManager<C = Client> {
+    client: C,
+    network: NetworkConfig,
+    // Deadline for each lookup the manager sends to the node
+    request_timeout: Duration,
 }
 
 impl XRPLManager {
+    pub async fn connect(network: NetworkConfig) -> Result<Self> {
+        let transport = network.transport();
+        debug!(network = ?network.network, url = %network.url, ?transport, "connecting to XRPL");
+        let client = Client::connect(&network).await?;
+        info!(network = ?network.network, url = %network.url, ?transport, "connected to XRPL");
+
+        Ok(Self::with_client(client, network))
+    }
+
     pub async fn new_testnet() -> Result<Self> {
-        println!("Connecting to XRPL Testnet...");
-        let url = url::Url::parse("wss://s.altnet.rippletest.net:51233")?;
-        let client = AsyncWebSocketClient::open(url).await?;
-        println!("Connected to XRPL Testnet");
+        Self::connect(NetworkConfig::testnet()).await
+    }
+
+    pub async fn new_mainnet() -> Result<Self> {
+        Self::connect(NetworkConfig::mainnet()).await
+    }
+
+    pub async fn new_devnet() -> Result<Self> {
+        Self::connect(NetworkConfig::devnet()).await
+    }
+
+    pub async fn new_standalone() -> Result<Self> {
+        Self::connect(NetworkConfig::standalone()).await
+    }
+
+    // Utility to create a second connection, optionally against a different endpoint
+    pub async fn create_second_connection(&self, network: Option<NetworkConfig>) -> Result<XRPLManager> {
+        let connection = Self::connect(network.unwrap_or_else(|| self.network.clone())).await?;
+        Ok(connection.with_request_timeout(self.request_timeout))
+    }
+
+    // Signing never touches the client, so it is only defined once
+    pub fn offline_sign_transaction(
+        user_secret: &Secret,
+        to_address: &str,
+        amount: xrpl::models::Amount<'static>,
+        params: offline_signing::OfflineTransactionParams,
+        options: &PaymentOptions,
+    ) -> Result<String> {
+        offline_signing::offline_sign_transaction(user_secret, to_address, amount, params, options)
+    }
+
+    pub fn offline_sign_escrow_create(
+        owner_secret: &Secret,
+        escrow: &EscrowBuilder,
+        params: &offline_signing::OfflineTransactionParams,
+    ) -> Result<String> {
+        escrow::offline_sign_escrow_create(owner_secret, escrow, params)
+    }
+
+    pub fn offline_sign_escrow_finish(
+        finisher_secret: &Secret,
+        owner: &str,
+        sequence: u32,
+        fulfillment: Option<&PreimageCondition>,
+        params: &offline_signing::OfflineTransactionParams,
+    ) -> Result<String> {
+        escrow::offline_sign_escrow_finish(finisher_secret, owner, sequence, fulfillment, params)
+    }
+
+    pub fn offline_sign_escrow_cancel(
+        canceller_secret: &Secret,
+        owner: &str,
+        sequence: u32,
+        params: &offline_signing::OfflineTransactionParams,
+    ) -> Result<String> {
+        escrow::offline_sign_escrow_cancel(canceller_secret, owner, sequence, params)
+    }
+
+    /// Sign a payment channel claim for `amount` drops in total, without a node
+    pub fn sign_channel_claim(secret: &Secret, channel_id: &str, amount: Drops) -> Result<SignedClaim> {
+        payment_channels::sign_claim(secret, channel_id, amount)
+    }
+
+    pub fn verify_channel_claim(channel_id: &str, amount: Drops, signature: &str, public_key: &str) -> bool {
+        payment_channels::verify_claim(channel_id, amount, signature, public_key)
+    }
+}
+
+impl XRPLManager<SupervisedClient> {
+    /// Long-lived manager that reconnects with backoff and fails over between `endpoints`.
+    /// All endpoints must belong to the same network; the first one is tried first.
+    pub async fn connect_supervised(endpoints: Vec<NetworkConfig>, policy: ReconnectPolicy) -> Result<Self> {
+        let network = endpoints
+            .first()
+            .cloned()
+            .ok_or_else(|| RippleError::InvalidInput("At least one endpoint is required".to_string()))?;
+        let client = SupervisedClient::connect(endpoints, policy).await?;
+        info!(url = %client.current_endpoint().url, "supervised XRPL connection established");
+        Ok(Self::with_client(client, network))
+    }
+}
+
+impl<C: XRPLAsyncClient> XRPLManager<C> {
+    /// Build a manager over an already connected client
+    pub fn with_client(client: C, network: NetworkConfig) -> Self {
+        Self {
+            client,
+            network,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
 
-        Ok(Self { client })
+    /// Change how long each lookup may wait for the node before failing with
+    /// `RippleError::Timeout` (default `DEFAULT_REQUEST_TIMEOUT`)
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    pub fn network(&self) -> &NetworkConfig {
+        &self.network
     }
 
     // Part 1 functionality
     pub async fn send_xrp(
         &self,
-        user1_secret: &str,
+        user1_secret: &Secret,
         user2_address: &str,
         amount_drops: u64,
+        options: &PaymentOptions,
     ) -> Result<String> {
-        transactions::send_xrp(&self.client, user1_secret, user2_address, amount_drops).await
+        transactions::send_xrp(
+            &self.client,
+            user1_secret,
+            user2_address,
+            amount_drops,
+            options,
+            self.request_timeout,
+        )
+        .await
     }
 
     pub async fn send_issued_token(
         &self,
-        user1_secret: &str,
+        user1_secret: &Secret,
         user2_address: &str,
-        currency_code: &str,
-        amount: &str,
+        currency_code: &CurrencyCode,
+        amount: &IssuedValue,
+        options: &PaymentOptions,
     ) -> Result<String> {
         transactions::send_issued_token(
             &self.client,
@@ -53,15 +219,43 @@ impl XRPLManager {
             user2_address,
             currency_code,
             amount,
+            options,
+            self.request_timeout,
         )
         .await
     }
 
+    /// Pass on tokens of any issuer; see `transactions::send_token` for how SendMax is chosen
+    pub async fn send_token(
+        &self,
+        sender_secret: &Secret,
+        destination: &str,
+        amount: &CurrencyAmount,
+        send_max: Option<&CurrencyAmount>,
+        options: &PaymentOptions,
+    ) -> Result<String> {
+        transactions::send_token(
+            &self.client,
+            sender_secret,
+            destination,
+            amount,
+            send_max,
+            options,
+            self.request_timeout,
+        )
+        .await
+    }
+
+    /// Submit a payment built with `PaymentBuilder` (paths, SendMax, DeliverMin)
+    pub async fn send_payment(&self, sender_secret: &Secret, payment: &PaymentBuilder) -> Result<String> {
+        transactions::send_payment(&self.client, sender_secret, payment, self.request_timeout).await
+    }
+
     pub async fn setup_trustline(
         &self,
-        user_secret: &str,
+        user_secret: &Secret,
         issuer_address: &str,
-        currency_code: &str,
+        currency_code: &CurrencyCode,
         limit: &str,
     ) -> Result<String> {
         transactions::setup_trustline(
@@ -70,86 +264,437 @@ impl XRPLManager {
             issuer_address,
             currency_code,
             limit,
+            self.request_timeout,
         )
         .await
     }
 
+    // Same operations, returning only once the transaction is in a validated ledger
+    pub async fn send_xrp_and_wait(
+        &self,
+        user1_secret: &Secret,
+        user2_address: &str,
+        amount_drops: u64,
+        options: &PaymentOptions,
+    ) -> Result<ValidatedTransaction> {
+        transactions::send_xrp_and_wait(
+            &self.client,
+            user1_secret,
+            user2_address,
+            amount_drops,
+            options,
+            self.request_timeout,
+        )
+        .await
+    }
+
+    pub async fn send_issued_token_and_wait(
+        &self,
+        user1_secret: &Secret,
+        user2_address: &str,
+        currency_code: &CurrencyCode,
+        amount: &IssuedValue,
+        options: &PaymentOptions,
+    ) -> Result<ValidatedTransaction> {
+        transactions::send_issued_token_and_wait(
+            &self.client,
+            user1_secret,
+            user2_address,
+            currency_code,
+            amount,
+            options,
+            self.request_timeout,
+        )
+        .await
+    }
+
+    pub async fn send_token_and_wait(
+        &self,
+        sender_secret: &Secret,
+        destination: &str,
+        amount: &CurrencyAmount,
+        send_max: Option<&CurrencyAmount>,
+        options: &PaymentOptions,
+    ) -> Result<ValidatedTransaction> {
+        transactions::send_token_and_wait(
+            &self.client,
+            sender_secret,
+            destination,
+            amount,
+            send_max,
+            options,
+            self.request_timeout,
+        )
+        .await
+    }
+
+    pub async fn send_payment_and_wait(
+        &self,
+        sender_secret: &Secret,
+        payment: &PaymentBuilder,
+    ) -> Result<ValidatedTransaction> {
+        transactions::send_payment_and_wait(&self.client, sender_secret, payment, self.request_timeout).await
+    }
+
+    pub async fn setup_trustline_and_wait(
+        &self,
+        user_secret: &Secret,
+        issuer_address: &str,
+        currency_code: &CurrencyCode,
+        limit: &str,
+    ) -> Result<ValidatedTransaction> {
+        transactions::setup_trustline_and_wait(
+            &self.client,
+            user_secret,
+            issuer_address,
+            currency_code,
+            limit,
+            self.request_timeout,
+        )
+        .await
+    }
+
+    /// Wait for an already submitted transaction to reach a validated ledger
+    pub async fn wait_for_validation(
+        &self,
+        tx_hash: &str,
+        last_ledger_sequence: u32,
+    ) -> Result<ValidatedTransaction> {
+        submission::wait_for_validation(&self.client, tx_hash, last_ledger_sequence, self.request_timeout).await
+    }
+
+    /// Check a payment on ledger; `expected` names the token's issuer, which may differ from `expected_from`.
+    /// Fields set in `expected_options` (destination tag, memos, ...) are checked too.
     pub async fn verify_transfer(
         &self,
         tx_hash: &str,
         expected_from: &str,
         expected_to: &str,
-        expected_amount: &str,
-        currency_code: Option<&str>,
-    ) -> Result<bool> {
+        expected: &CurrencyAmount,
+        expected_options: &PaymentOptions,
+    ) -> Result<VerificationReport> {
         verification::verify_transfer(
             &self.client,
             tx_hash,
             expected_from,
             expected_to,
-            expected_amount,
-            currency_code,
+            expected,
+            expected_options,
+            self.request_timeout,
         )
         .await
     }
 
-    // Part 2 functionality - True offline signing
-    pub async fn gather_transaction_params(&self, account_address: &str) -> Result<offline_signing::OfflineTransactionParams> {
-        offline_signing::gather_transaction_params(&self.client, account_address).await
+    // DEX offers
+    pub async fn place_offer(&self, owner_secret: &Secret, offer: &OfferBuilder) -> Result<PlacedOffer> {
+        offers::place_offer(&self.client, owner_secret, offer, self.request_timeout).await
     }
 
-    pub fn offline_sign_transaction(
-        user_secret: &str,
-        to_address: &str,
-        amount: xrpl::models::Amount<'static>,
-        params: offline_signing::OfflineTransactionParams,
+    /// Place an offer and report what it traded once validated
+    pub async fn place_offer_and_wait(&self, owner_secret: &Secret, offer: &OfferBuilder) -> Result<OfferFill> {
+        offers::place_offer_and_wait(&self.client, owner_secret, offer, self.request_timeout).await
+    }
+
+    pub async fn cancel_offer(&self, owner_secret: &Secret, offer_sequence: u32) -> Result<String> {
+        offers::cancel_offer(&self.client, owner_secret, offer_sequence, self.request_timeout).await
+    }
+
+    pub async fn cancel_offer_and_wait(&self, owner_secret: &Secret, offer_sequence: u32) -> Result<ValidatedTransaction> {
+        offers::cancel_offer_and_wait(&self.client, owner_secret, offer_sequence, self.request_timeout).await
+    }
+
+    /// What an OfferCreate traded immediately and what it left on the order book
+    pub async fn verify_offer(&self, tx_hash: &str) -> Result<OfferFill> {
+        offers::verify_offer(&self.client, tx_hash, self.request_timeout).await
+    }
+
+    // Escrows
+    pub async fn create_escrow(&self, owner_secret: &Secret, escrow: &EscrowBuilder) -> Result<PlacedEscrow> {
+        escrow::create_escrow(&self.client, owner_secret, escrow, self.request_timeout).await
+    }
+
+    pub async fn create_escrow_and_wait(
+        &self,
+        owner_secret: &Secret,
+        escrow: &EscrowBuilder,
+    ) -> Result<(PlacedEscrow, ValidatedTransaction)> {
+        escrow::create_escrow_and_wait(&self.client, owner_secret, escrow, self.request_timeout).await
+    }
+
+    pub async fn finish_escrow(
+        &self,
+        finisher_secret: &Secret,
+        owner: &str,
+        sequence: u32,
+        fulfillment: Option<&PreimageCondition>,
+    ) -> Result<String> {
+        escrow::finish_escrow(&self.client, finisher_secret, owner, sequence, fulfillment, self.request_timeout).await
+    }
+
+    pub async fn finish_escrow_and_wait(
+        &self,
+        finisher_secret: &Secret,
+        owner: &str,
+        sequence: u32,
+        fulfillment: Option<&PreimageCondition>,
+    ) -> Result<ValidatedTransaction> {
+        escrow::finish_escrow_and_wait(&self.client, finisher_secret, owner, sequence, fulfillment, self.request_timeout)
+            .await
+    }
+
+    pub async fn cancel_escrow(&self, canceller_secret: &Secret, owner: &str, sequence: u32) -> Result<String> {
+        escrow::cancel_escrow(&self.client, canceller_secret, owner, sequence, self.request_timeout).await
+    }
+
+    pub async fn cancel_escrow_and_wait(
+        &self,
+        canceller_secret: &Secret,
+        owner: &str,
+        sequence: u32,
+    ) -> Result<ValidatedTransaction> {
+        escrow::cancel_escrow_and_wait(&self.client, canceller_secret, owner, sequence, self.request_timeout).await
+    }
+
+    // Payment channels
+    pub async fn create_channel(&self, owner_secret: &Secret, channel: &ChannelBuilder) -> Result<PlacedChannel> {
+        payment_channels::create_channel(&self.client, owner_secret, channel, self.request_timeout).await
+    }
+
+    pub async fn create_channel_and_wait(
+        &self,
+        owner_secret: &Secret,
+        channel: &ChannelBuilder,
+    ) -> Result<(PlacedChannel, ValidatedTransaction)> {
+        payment_channels::create_channel_and_wait(&self.client, owner_secret, channel, self.request_timeout).await
+    }
+
+    pub async fn fund_channel(
+        &self,
+        owner_secret: &Secret,
+        channel_id: &str,
+        amount: Drops,
+        expiration: Option<std::time::SystemTime>,
     ) -> Result<String> {
-        offline_signing::offline_sign_transaction(user_secret, to_address, amount, params)
+        payment_channels::fund_channel(
+            &self.client,
+            owner_secret,
+            channel_id,
+            amount,
+            expiration,
+            self.request_timeout,
+        )
+        .await
+    }
+
+    pub async fn fund_channel_and_wait(
+        &self,
+        owner_secret: &Secret,
+        channel_id: &str,
+        amount: Drops,
+        expiration: Option<std::time::SystemTime>,
+    ) -> Result<ValidatedTransaction> {
+        payment_channels::fund_channel_and_wait(&self.client, owner_secret, channel_id, amount, expiration, self.request_timeout)
+            .await
+    }
+
+    pub async fn claim_channel(&self, claimer_secret: &Secret, claim: &ChannelClaimBuilder) -> Result<String> {
+        payment_channels::claim_channel(&self.client, claimer_secret, claim, self.request_timeout).await
+    }
+
+    pub async fn claim_channel_and_wait(
+        &self,
+        claimer_secret: &Secret,
+        claim: &ChannelClaimBuilder,
+    ) -> Result<ValidatedTransaction> {
+        payment_channels::claim_channel_and_wait(&self.client, claimer_secret, claim, self.request_timeout).await
+    }
+
+    // Checks
+    pub async fn create_check(&self, sender_secret: &Secret, check: &CheckBuilder) -> Result<PlacedCheck> {
+        checks::create_check(&self.client, sender_secret, check, self.request_timeout).await
+    }
+
+    pub async fn create_check_and_wait(
+        &self,
+        sender_secret: &Secret,
+        check: &CheckBuilder,
+    ) -> Result<(PlacedCheck, ValidatedTransaction)> {
+        checks::create_check_and_wait(&self.client, sender_secret, check, self.request_timeout).await
+    }
+
+    pub async fn cash_check(&self, casher_secret: &Secret, check_id: &str, amount: &CheckCashAmount) -> Result<String> {
+        checks::cash_check(&self.client, casher_secret, check_id, amount, self.request_timeout).await
+    }
+
+    pub async fn cash_check_and_wait(
+        &self,
+        casher_secret: &Secret,
+        check_id: &str,
+        amount: &CheckCashAmount,
+    ) -> Result<ValidatedTransaction> {
+        checks::cash_check_and_wait(&self.client, casher_secret, check_id, amount, self.request_timeout).await
+    }
+
+    pub async fn cancel_check(&self, canceller_secret: &Secret, check_id: &str) -> Result<String> {
+        checks::cancel_check(&self.client, canceller_secret, check_id, self.request_timeout).await
+    }
+
+    pub async fn cancel_check_and_wait(&self, canceller_secret: &Secret, check_id: &str) -> Result<ValidatedTransaction> {
+        checks::cancel_check_and_wait(&self.client, canceller_secret, check_id, self.request_timeout).await
+    }
+
+    /// Check that `tx_hash` cashed check `check_id` for exactly `expected`
+    pub async fn verify_check_cash(
+        &self,
+        tx_hash: &str,
+        check_id: &str,
+        expected_casher: &str,
+        expected: &CurrencyAmount,
+    ) -> Result<VerificationReport> {
+        verification::verify_check_cash(&self.client, tx_hash, check_id, expected_casher, expected, self.request_timeout)
+            .await
+    }
+
+    // Part 2 functionality - True offline signing
+    pub async fn gather_transaction_params(&self, account_address: &str) -> Result<offline_signing::OfflineTransactionParams> {
+        let mut params = offline_signing::gather_transaction_params(&self.client, account_address, self.request_timeout).await?;
+        params.network_id = self.network.required_network_id();
+        Ok(params)
     }
 
     pub async fn submit_signed_blob(&self, signed_blob: &str) -> Result<String> {
-        offline_signing::submit_signed_blob(&self.client, signed_blob).await
+        offline_signing::submit_signed_blob(&self.client, signed_blob, self.request_timeout).await
+    }
+
+    pub async fn submit_signed_blob_and_wait(&self, signed_blob: &str) -> Result<ValidatedTransaction> {
+        offline_signing::submit_signed_blob_and_wait(&self.client, signed_blob, self.request_timeout).await
+    }
+
+    // Streams, each over its own WebSocket connection to this manager's endpoint
+    /// Open path search with updates as ledgers close
+    pub async fn path_find(&self, request: &PathRequest) -> Result<Subscription<PathFindUpdate>> {
+        client::path_find(&self.network, request, self.request_timeout).await
+    }
+
+    pub async fn subscribe_ledgers(&self) -> Result<Subscription<LedgerClosed>> {
+        subscription::subscribe_ledgers(&self.network, self.request_timeout).await
+    }
+
+    pub async fn subscribe_transactions(&self) -> Result<Subscription<TransactionEvent>> {
+        subscription::subscribe_transactions(&self.network, self.request_timeout).await
+    }
+
+    pub async fn subscribe_accounts(&self, accounts: &[&str]) -> Result<Subscription<TransactionEvent>> {
+        subscription::subscribe_accounts(&self.network, accounts, self.request_timeout).await
+    }
+
+    pub async fn subscribe_order_book(
+        &self,
+        taker_gets: &xrpl::models::Currency<'_>,
+        taker_pays: &xrpl::models::Currency<'_>,
+    ) -> Result<Subscription<TransactionEvent>> {
+        subscription::subscribe_order_book(&self.network, taker_gets, taker_pays, self.request_timeout).await
+    }
+
+    /// Report validated payments into `accounts`, resuming after the position stored in `cursor`
+    pub async fn watch_payments<S: LedgerCursor>(&self, accounts: &[&str], cursor: S) -> Result<PaymentWatcher<S>> {
+        PaymentWatcher::start(&self.network, accounts, cursor, self.request_timeout).await
     }
 
     // High-level workflows
-    pub async fn offline_xrp_workflow(
+    pub async fn offline_xrp_workflow<D: XRPLAsyncClient>(
         &self,
-        offline_client: &XRPLManager,
-        user_secret: &str,
+        offline_client: &XRPLManager<D>,
+        user_secret: &Secret,
         to_address: &str,
         amount_drops: u64,
+        options: &PaymentOptions,
     ) -> Result<String> {
-        offline_signing::offline_xrp_workflow(&self.client, &offline_client.client, user_secret, to_address, amount_drops).await
+        offline_signing::offline_xrp_workflow(&self.client, &offline_client.client, self.network.required_network_id(), self.request_timeout, user_secret, to_address, amount_drops, options).await
     }
 
-    pub async fn offline_token_workflow(
+    pub async fn offline_token_workflow<D: XRPLAsyncClient>(
         &self,
-        offline_client: &XRPLManager,
-        user_secret: &str,
+        offline_client: &XRPLManager<D>,
+        user_secret: &Secret,
         to_address: &str,
-        currency_code: &str,
-        amount: &str,
+        currency_code: &CurrencyCode,
+        amount: &IssuedValue,
+        options: &PaymentOptions,
     ) -> Result<String> {
-        offline_signing::offline_token_workflow(&self.client, &offline_client.client, user_secret, to_address, currency_code, amount).await
+        offline_signing::offline_token_workflow(&self.client, &offline_client.client, self.network.required_network_id(), self.request_timeout, user_secret, to_address, currency_code, amount, options).await
+    }
+}
+
+// Queries sent as raw JSON, because xrpl-rust has no model for their request or result
+impl<C: XRPLAsyncClient + RawRequest> XRPLManager<C> {
+    /// Escrows `account` owns or is the destination of
+    pub async fn list_escrows(&self, account: &str) -> Result<Vec<Escrow>> {
+        escrow::list_escrows(&self.client, account, self.request_timeout).await
+    }
+
+    /// Channels `account` owns, optionally only the ones to `destination`
+    pub async fn list_channels(&self, account: &str, destination: Option<&str>) -> Result<Vec<PaymentChannel>> {
+        payment_channels::list_channels(&self.client, account, destination, self.request_timeout).await
     }
 
-    // Utility to create a second connection
-    pub async fn create_second_connection() -> Result<XRPLManager> {
-        Self::new_testnet().await
+    /// Unexpired checks written to `account`
+    pub async fn list_cashable_checks(&self, account: &str) -> Result<Vec<CheckEntry>> {
+        checks::list_cashable_checks(&self.client, account, self.request_timeout).await
+    }
+
+    // Path finding
+    pub async fn ripple_path_find(&self, request: &PathRequest) -> Result<Vec<PathAlternative>> {
+        client::ripple_path_find(&self.client, request, self.request_timeout).await
+    }
+
+    /// What `source_account` would spend, in `pay_with`, for `amount` to arrive at `destination`
+    pub async fn quote_payment(
+        &self,
+        source_account: &str,
+        destination: &str,
+        amount: &CurrencyAmount,
+        pay_with: &xrpl::models::Currency<'static>,
+    ) -> Result<PathAlternative> {
+        transactions::quote_payment(&self.client, source_account, destination, amount, pay_with, self.request_timeout).await
+    }
+
+    // Order book queries
+    pub async fn book_offers(
+        &self,
+        taker_gets: &xrpl::models::Currency<'_>,
+        taker_pays: &xrpl::models::Currency<'_>,
+        limit: u16,
+    ) -> Result<Vec<BookOffer>> {
+        client::book_offers(&self.client, taker_gets, taker_pays, limit, self.request_timeout).await
+    }
+
+    pub async fn account_offers(&self, account: &str) -> Result<Vec<AccountOffer>> {
+        client::account_offers(&self.client, account, self.request_timeout).await
+    }
+
+    /// Price taking `size` of `taker_gets` from the best `limit` offers of the book
+    pub async fn quote_fill(
+        &self,
+        taker_gets: &xrpl::models::Currency<'_>,
+        taker_pays: &xrpl::models::Currency<'_>,
+        size: &CurrencyAmount,
+        limit: u16,
+    ) -> Result<FillQuote> {
+        let offers = self.book_offers(taker_gets, taker_pays, limit).await?;
+        offers::quote_fill(&offers, size)
     }
 }
 
+// The new seed is only reachable through `wallet.seed`; it is never logged
 pub fn create_test_wallet() -> Result<Wallet> {
-    let wallet =
-        Wallet::create(None).map_err(|e| anyhow::anyhow!("Wallet creation error: {:?}", e))?;
-    println!("Created new wallet: {}", wallet.classic_address);
-    println!("   Seed: {}", wallet.seed);
+    let wallet = Wallet::create(None).map_err(RippleError::wallet)?;
+    info!(account = %wallet.classic_address, "created new wallet");
     Ok(wallet)
 }
 
-pub fn wallet_from_seed(seed: &str) -> Result<Wallet> {
-    let wallet =
-        Wallet::new(seed, 0).map_err(|e| anyhow::anyhow!("Wallet creation error: {:?}", e))?;
-    println!("Loaded wallet: {}", wallet.classic_address);
+pub fn wallet_from_seed(seed: &Secret) -> Result<Wallet> {
+    let wallet = seed.wallet()?;
+    debug!(account = %wallet.classic_address, "loaded wallet");
     Ok(wallet)
 }
@@ -1,17 +1,21 @@
 use anyhow::Result;
-use dotenvy::dotenv;
-use ripple_task::{XRPLManager, wallet_from_seed};
-use std::env;
-use std::time::Duration;
-use tokio::time::sleep;
+use ripple_task::{CurrencyAmount, CurrencyCode, IssuedValue, Memo, PaymentOptions, Secret, VerificationReport, XRPLManager, wallet_from_seed};
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // The library only emits tracing events; print them for the demo (override with RUST_LOG)
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("ripple_task=info")),
+        )
+        .init();
+
     println!("Starting XRPL Rust Library Demo");
     println!("=====================================");
 
     // Load environment variables
-    if let Err(_) = dotenvy::dotenv() {
+    if dotenvy::dotenv().is_err() {
         eprintln!("Error: .env file not found in current directory");
         eprintln!("Please create a .env file with the following format:");
         eprintln!("USER1_SEED=your_first_testnet_seed_here");
@@ -20,8 +24,10 @@ async fn main() -> Result<()> {
     }
 
     let user1_seed = std::env::var("USER1_SEED")
+        .map(Secret::from)
         .map_err(|_| anyhow::anyhow!("USER1_SEED not found in .env file"))?;
     let user2_seed = std::env::var("USER2_SEED")
+        .map(Secret::from)
         .map_err(|_| anyhow::anyhow!("USER2_SEED not found in .env file"))?;
 
     // Create XRPL manager
@@ -35,9 +41,6 @@ async fn main() -> Result<()> {
 
     demo_xrp_transfer(&xrpl, &user1_seed, &user2_seed).await?;
 
-    println!("\nWaiting 10 seconds for transaction to settle...");
-    sleep(Duration::from_secs(10)).await;
-
     println!("=====================================");
 
     // Demo 2: Issued Token Transfer
@@ -46,9 +49,6 @@ async fn main() -> Result<()> {
 
     demo_issued_token_transfer(&xrpl, &user1_seed, &user2_seed).await?;
 
-    println!("\nWaiting 10 seconds for transaction to settle...");
-    sleep(Duration::from_secs(10)).await;
-
     println!("=====================================");
 
     // Demo 3: Offline Signing
@@ -61,7 +61,7 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn demo_xrp_transfer(xrpl: &XRPLManager, user1_seed: &str, user2_seed: &str) -> Result<()> {
+async fn demo_xrp_transfer(xrpl: &XRPLManager, user1_seed: &Secret, user2_seed: &Secret) -> Result<()> {
     let user1_wallet = wallet_from_seed(user1_seed)?;
     let user2_wallet = wallet_from_seed(user2_seed)?;
 
@@ -72,28 +72,28 @@ async fn demo_xrp_transfer(xrpl: &XRPLManager, user1_seed: &str, user2_seed: &st
     println!("\nSending {} drops from User1 to User2...", amount_drops);
 
     match xrpl
-        .send_xrp(user1_seed, &user2_wallet.classic_address, amount_drops)
+        .send_xrp_and_wait(user1_seed, &user2_wallet.classic_address, amount_drops, &PaymentOptions::default())
         .await
     {
-        Ok(tx_hash) => {
-            println!("XRP transfer successful!");
-
-            println!("Waiting 5 seconds before verification...");
-            sleep(Duration::from_secs(5)).await;
+        Ok(validated) => {
+            println!(
+                "XRP transfer validated in ledger {} ({})",
+                validated.ledger_index, validated.engine_result
+            );
 
             println!("\nVerifying XRP transfer...");
             match xrpl
                 .verify_transfer(
-                    &tx_hash,
+                    &validated.tx_hash,
                     &user1_wallet.classic_address,
                     &user2_wallet.classic_address,
-                    &amount_drops.to_string(),
-                    None,
+                    &CurrencyAmount::drops(amount_drops)?,
+                    &PaymentOptions::default(),
                 )
                 .await
             {
-                Ok(true) => println!("XRP transfer verification successful!"),
-                Ok(false) => println!("XRP transfer verification failed!"),
+                Ok(report) if report.is_verified() => println!("XRP transfer verification successful!"),
+                Ok(report) => print_failed_checks("XRP transfer verification failed!", &report),
                 Err(e) => println!("Error during verification: {}", e),
             }
         }
@@ -105,8 +105,8 @@ async fn demo_xrp_transfer(xrpl: &XRPLManager, user1_seed: &str, user2_seed: &st
 
 async fn demo_issued_token_transfer(
     xrpl: &XRPLManager,
-    user1_seed: &str,
-    user2_seed: &str,
+    user1_seed: &Secret,
+    user2_seed: &Secret,
 ) -> Result<()> {
     let user1_wallet = wallet_from_seed(user1_seed)?;
     let user2_wallet = wallet_from_seed(user2_seed)?;
@@ -114,7 +114,7 @@ async fn demo_issued_token_transfer(
     println!("User1 (Token Issuer): {}", user1_wallet.classic_address);
     println!("User2 (Token Receiver): {}", user2_wallet.classic_address);
 
-    let currency_code = "TST";
+    let currency_code = CurrencyCode::new("TST")?;
     let trust_limit = "1000";
 
     println!(
@@ -122,54 +122,59 @@ async fn demo_issued_token_transfer(
         currency_code
     );
     match xrpl
-        .setup_trustline(
+        .setup_trustline_and_wait(
             user2_seed,
             &user1_wallet.classic_address,
-            currency_code,
+            &currency_code,
             trust_limit,
         )
         .await
     {
-        Ok(trustline_tx_hash) => {
-            println!("Trustline setup successful! Hash: {}", trustline_tx_hash);
-
-            println!("Waiting 10 seconds for trustline to be processed...");
-            sleep(Duration::from_secs(10)).await;
+        Ok(trustline) => {
+            println!(
+                "Trustline validated in ledger {} ({}). Hash: {}",
+                trustline.ledger_index, trustline.engine_result, trustline.tx_hash
+            );
 
-            let token_amount = "100";
+            let token_amount: IssuedValue = "100".parse()?;
             println!(
                 "\nUser1 issuing {} {} tokens to User2...",
                 token_amount, currency_code
             );
 
             match xrpl
-                .send_issued_token(
+                .send_issued_token_and_wait(
                     user1_seed,
                     &user2_wallet.classic_address,
-                    currency_code,
-                    token_amount,
+                    &currency_code,
+                    &token_amount,
+                    &PaymentOptions::default(),
                 )
                 .await
             {
-                Ok(token_tx_hash) => {
-                    println!("Token issuance successful! Hash: {}", token_tx_hash);
-
-                    println!("Waiting 5 seconds before verification...");
-                    sleep(Duration::from_secs(5)).await;
+                Ok(token) => {
+                    println!(
+                        "Token issuance validated in ledger {} ({}). Hash: {}",
+                        token.ledger_index, token.engine_result, token.tx_hash
+                    );
 
                     println!("\nVerifying token transfer...");
                     match xrpl
                         .verify_transfer(
-                            &token_tx_hash,
+                            &token.tx_hash,
                             &user1_wallet.classic_address,
                             &user2_wallet.classic_address,
-                            token_amount,
-                            Some(currency_code),
+                            &CurrencyAmount::Issued {
+                                currency: currency_code.clone(),
+                                issuer: user1_wallet.classic_address.clone(),
+                                value: token_amount,
+                            },
+                            &PaymentOptions::default(),
                         )
                         .await
                     {
-                        Ok(true) => println!("Token transfer verification successful!"),
-                        Ok(false) => println!("Token transfer verification failed!"),
+                        Ok(report) if report.is_verified() => println!("Token transfer verification successful!"),
+                        Ok(report) => print_failed_checks("Token transfer verification failed!", &report),
                         Err(e) => println!("Error during verification: {}", e),
                     }
                 }
@@ -182,7 +187,7 @@ async fn demo_issued_token_transfer(
     Ok(())
 }
 
-async fn demo_offline_signing(xrpl: &XRPLManager, user1_seed: &str, user2_seed: &str) -> Result<()> {
+async fn demo_offline_signing(xrpl: &XRPLManager, user1_seed: &Secret, user2_seed: &Secret) -> Result<()> {
     let user1_wallet = wallet_from_seed(user1_seed)?;
     let user2_wallet = wallet_from_seed(user2_seed)?;
 
@@ -202,7 +207,7 @@ async fn demo_offline_signing(xrpl: &XRPLManager, user1_seed: &str, user2_seed:
         }
         Err(e) => {
             println!("Failed to gather parameters: {}", e);
-            return Err(e);
+            return Err(e.into());
         }
     };
 
@@ -210,11 +215,13 @@ async fn demo_offline_signing(xrpl: &XRPLManager, user1_seed: &str, user2_seed:
     println!("---------------------------------------------------------");
     println!("Simulating air-gapped environment...");
     
+    let options = PaymentOptions::default().with_memo(Memo::text("signed offline"));
     let signed_blob = match XRPLManager::offline_sign_transaction(
         user1_seed,
         &user2_wallet.classic_address,
         xrpl::models::Amount::XRPAmount(xrpl::models::XRPAmount(std::borrow::Cow::Owned(amount_drops.to_string()))),
         params,
+        &options,
     ) {
         Ok(blob) => {
             println!("Transaction signed successfully in OFFLINE environment!");
@@ -223,44 +230,56 @@ async fn demo_offline_signing(xrpl: &XRPLManager, user1_seed: &str, user2_seed:
         }
         Err(e) => {
             println!("Offline signing failed: {}", e);
-            return Err(e);
+            return Err(e.into());
         }
     };
 
     println!("\n3: Submit signed blob (Connection B - Different connection)");
     println!("----------------------------------------------------------------");
-    let xrpl2 = XRPLManager::create_second_connection().await?;
+    let xrpl2 = xrpl.create_second_connection(None).await?;
     println!("Created separate Connection B for submission");
 
-    match xrpl2.submit_signed_blob(&signed_blob).await {
-        Ok(tx_hash) => {
-            println!("Signed blob submitted successfully via Connection B!");
-            
-            println!("Waiting 5 seconds before verification...");
-            sleep(Duration::from_secs(5)).await;
+    match xrpl2.submit_signed_blob_and_wait(&signed_blob).await {
+        Ok(validated) => {
+            println!(
+                "Signed blob submitted via Connection B and validated in ledger {} ({})",
+                validated.ledger_index, validated.engine_result
+            );
 
             println!("\n4: Verify transaction on ledger");
             println!("------------------------------------");
             match xrpl2
                 .verify_transfer(
-                    &tx_hash,
+                    &validated.tx_hash,
                     &user1_wallet.classic_address,
                     &user2_wallet.classic_address,
-                    &amount_drops.to_string(),
-                    None,
+                    &CurrencyAmount::drops(amount_drops)?,
+                    &options,
                 )
                 .await
             {
-                Ok(true) => println!("Offline signed transaction verified successfully!"),
-                Ok(false) => println!("Offline signed transaction verification failed!"),
+                Ok(report) if report.is_verified() => println!("Offline signed transaction verified successfully!"),
+                Ok(report) => print_failed_checks("Offline signed transaction verification failed!", &report),
                 Err(e) => println!("Error during verification: {}", e),
             }
         }
         Err(e) => {
             println!("Blob submission failed: {}", e);
-            return Err(e);
+            return Err(e.into());
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+fn print_failed_checks(heading: &str, report: &VerificationReport) {
+    println!("{}", heading);
+    for check in report.failures() {
+        println!(
+            "  {:?}: expected {}, got {}",
+            check.kind,
+            check.expected.as_deref().unwrap_or("nothing"),
+            check.actual.as_deref().unwrap_or("nothing")
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::error::{Result, RippleError};
+use url::Url;
+
+/// Public endpoints for the well-known XRPL networks
+pub const MAINNET_URL: &str = "wss://xrplcluster.com";
+pub const TESTNET_URL: &str = "wss://s.altnet.rippletest.net:51233";
+pub const DEVNET_URL: &str = "wss://s.devnet.rippletest.net:51233";
+pub const STANDALONE_URL: &str = "ws://127.0.0.1:6006";
+
+/// JSON-RPC (HTTP) endpoints of the same networks
+pub const MAINNET_JSON_RPC_URL: &str = "https://xrplcluster.com";
+pub const TESTNET_JSON_RPC_URL: &str = "https://s.altnet.rippletest.net:51234";
+pub const DEVNET_JSON_RPC_URL: &str = "https://s.devnet.rippletest.net:51234";
+pub const STANDALONE_JSON_RPC_URL: &str = "http://127.0.0.1:5005";
+
+/// Networks with an ID above this value require the NetworkID field on every transaction
+const RESTRICTED_NETWORK_ID: u32 = 1024;
+
+/// Named XRPL networks the library knows how to reach
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+    /// Local `rippled` running in standalone mode
+    Standalone,
+    /// Any other endpoint (private network, mock server, ...)
+    Custom,
+}
+
+/// Protocol used to talk to the endpoint, derived from the URL scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// `ws://` / `wss://`
+    WebSocket,
+    /// `http://` / `https://`
+    JsonRpc,
+}
+
+/// Endpoint configuration used to open an XRPLManager connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkConfig {
+    pub network: Network,
+    pub url: Url,
+    pub network_id: Option<u32>,
+}
+
+impl NetworkConfig {
+    pub fn mainnet() -> Self {
+        Self::preset(Network::Mainnet, MAINNET_URL, Some(0))
+    }
+
+    pub fn testnet() -> Self {
+        Self::preset(Network::Testnet, TESTNET_URL, Some(1))
+    }
+
+    pub fn devnet() -> Self {
+        Self::preset(Network::Devnet, DEVNET_URL, Some(2))
+    }
+
+    pub fn standalone() -> Self {
+        Self::preset(Network::Standalone, STANDALONE_URL, None)
+    }
+
+    // Arbitrary endpoint, e.g. a private rippled node or a mock server
+    pub fn custom(url: &str, network_id: Option<u32>) -> Result<Self> {
+        let url = parse_url(url)?;
+        Ok(Self {
+            network: Network::Custom,
+            url,
+            network_id,
+        })
+    }
+
+    // Keep the network preset but talk to a different node (e.g. a second testnet server)
+    pub fn with_url(mut self, url: &str) -> Result<Self> {
+        self.url = parse_url(url)?;
+        Ok(self)
+    }
+
+    /// Reach a preset network through its public JSON-RPC endpoint instead of WebSocket.
+    /// Custom configs are left unchanged; give them an http(s) URL instead.
+    pub fn over_json_rpc(mut self) -> Self {
+        let url = match self.network {
+            Network::Mainnet => MAINNET_JSON_RPC_URL,
+            Network::Testnet => TESTNET_JSON_RPC_URL,
+            Network::Devnet => DEVNET_JSON_RPC_URL,
+            Network::Standalone => STANDALONE_JSON_RPC_URL,
+            Network::Custom => return self,
+        };
+        self.url = Url::parse(url).expect("preset endpoint URLs are valid");
+        self
+    }
+
+    pub fn transport(&self) -> Transport {
+        match self.url.scheme() {
+            "http" | "https" => Transport::JsonRpc,
+            _ => Transport::WebSocket,
+        }
+    }
+
+    pub fn with_network_id(mut self, network_id: u32) -> Self {
+        self.network_id = Some(network_id);
+        self
+    }
+
+    /// NetworkID value that must be included in transactions, if any
+    pub fn required_network_id(&self) -> Option<u32> {
+        self.network_id.filter(|id| *id > RESTRICTED_NETWORK_ID)
+    }
+
+    fn preset(network: Network, url: &str, network_id: Option<u32>) -> Self {
+        Self {
+            network,
+            url: Url::parse(url).expect("preset endpoint URLs are valid"),
+            network_id,
+        }
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    let parsed = Url::parse(url).map_err(|e| RippleError::InvalidInput(format!("Invalid endpoint URL {}: {}", url, e)))?;
+    match parsed.scheme() {
+        "ws" | "wss" | "http" | "https" => Ok(parsed),
+        scheme => Err(RippleError::InvalidInput(format!(
+            "Unsupported endpoint scheme {} in {}; expected ws, wss, http or https",
+            scheme, url
+        ))),
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self::testnet()
+    }
+}
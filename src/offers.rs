@@ -0,0 +1,540 @@
+use crate::amount::{CurrencyAmount, Drops, IssuedValue};
+use crate::client::{self, BookOffer, with_timeout};
+use crate::error::{Result, RippleError};
+use crate::secret::Secret;
+use crate::submission::{ValidatedTransaction, check_submit_result, wait_for_submit_result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::time::Duration;
+use tracing::{Span, debug, info, instrument};
+use xrpl::{
+    asynch::{clients::XRPLAsyncClient, transaction::sign_and_submit},
+    models::{
+        Amount, FlagCollection,
+        results::{
+            metadata::{LedgerNode, TransactionMetadata},
+            submit::Submit as SubmitResult,
+            tx::TxVersionMap,
+        },
+        transactions::{
+            offer_cancel::OfferCancel,
+            offer_create::{OfferCreate, OfferCreateFlag},
+        },
+    },
+};
+
+/// OfferCreate: the owner gives `taker_gets` in exchange for `taker_pays`.
+/// Without flags, whatever does not trade immediately stays on the order book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfferBuilder {
+    taker_gets: CurrencyAmount,
+    taker_pays: CurrencyAmount,
+    flags: Vec<OfferCreateFlag>,
+}
+
+impl OfferBuilder {
+    pub fn new(taker_gets: CurrencyAmount, taker_pays: CurrencyAmount) -> Self {
+        Self {
+            taker_gets,
+            taker_pays,
+            flags: Vec::new(),
+        }
+    }
+
+    /// tfPassive: do not consume offers at exactly the same rate, only better ones
+    pub fn passive(self) -> Self {
+        self.with_flag(OfferCreateFlag::TfPassive)
+    }
+
+    /// tfImmediateOrCancel: trade what can be traded now, never rest on the book
+    pub fn immediate_or_cancel(self) -> Self {
+        self.with_flag(OfferCreateFlag::TfImmediateOrCancel)
+    }
+
+    /// tfFillOrKill: trade the full amount now or nothing (tecKILLED)
+    pub fn fill_or_kill(self) -> Self {
+        self.with_flag(OfferCreateFlag::TfFillOrKill)
+    }
+
+    /// tfSell: spend all of `taker_gets`, even if that buys more than `taker_pays`
+    pub fn sell(self) -> Self {
+        self.with_flag(OfferCreateFlag::TfSell)
+    }
+
+    fn with_flag(mut self, flag: OfferCreateFlag) -> Self {
+        if !self.flags.contains(&flag) {
+            self.flags.push(flag);
+        }
+        self
+    }
+
+    pub fn taker_gets(&self) -> &CurrencyAmount {
+        &self.taker_gets
+    }
+
+    pub fn taker_pays(&self) -> &CurrencyAmount {
+        &self.taker_pays
+    }
+
+    pub fn flags(&self) -> &[OfferCreateFlag] {
+        &self.flags
+    }
+
+    // What rippled would reject as temBAD_OFFER, temREDUNDANT or temINVALID_FLAG
+    fn validate(&self) -> Result<()> {
+        for (name, amount) in [("TakerGets", &self.taker_gets), ("TakerPays", &self.taker_pays)] {
+            if !amount.is_positive() {
+                return Err(RippleError::InvalidInput(format!("{} must be positive", name)));
+            }
+        }
+        if let (CurrencyAmount::Xrp(_), CurrencyAmount::Xrp(_)) = (&self.taker_gets, &self.taker_pays) {
+            return Err(RippleError::InvalidInput("An offer cannot trade XRP for XRP".to_string()));
+        }
+        if self.taker_gets.currency() == self.taker_pays.currency() && self.taker_gets.issuer() == self.taker_pays.issuer() {
+            return Err(RippleError::InvalidInput(format!(
+                "An offer cannot trade {} for itself",
+                self.taker_gets.currency()
+            )));
+        }
+        if self.flags.contains(&OfferCreateFlag::TfImmediateOrCancel) && self.flags.contains(&OfferCreateFlag::TfFillOrKill) {
+            return Err(RippleError::InvalidInput(
+                "tfImmediateOrCancel and tfFillOrKill cannot be combined".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Submitted OfferCreate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacedOffer {
+    pub tx_hash: String,
+    /// Sequence of the OfferCreate, which identifies the offer for `cancel_offer`
+    pub offer_sequence: u32,
+}
+
+/// What an OfferCreate traded when it was executed, read from its metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfferFill {
+    pub tx_hash: String,
+    pub offer_sequence: u32,
+    pub ledger_index: Option<u32>,
+    pub validated: bool,
+    /// `TransactionResult`, e.g. tecKILLED for a fill-or-kill offer that could not be filled
+    pub result: Option<String>,
+    /// How much of TakerGets the owner gave up
+    pub taker_gets_filled: CurrencyAmount,
+    /// How much of TakerPays the owner received
+    pub taker_pays_filled: CurrencyAmount,
+    /// What is left on the order book; None when nothing was placed
+    pub remaining_taker_gets: Option<CurrencyAmount>,
+    pub remaining_taker_pays: Option<CurrencyAmount>,
+}
+
+impl OfferFill {
+    /// True when part of the offer is resting on the order book
+    pub fn is_on_book(&self) -> bool {
+        self.remaining_taker_gets.is_some()
+    }
+
+    /// True when the offer traded anything immediately
+    pub fn has_traded(&self) -> bool {
+        !self.taker_pays_filled.is_zero()
+    }
+
+    // The fill is what the crossed offers of other accounts lost: their TakerGets went to the
+    // owner and their TakerPays came from the owner. Transfer fees the owner paid on top, and
+    // trades with AMM pools, are not included.
+    fn from_metadata(
+        tx_hash: &str,
+        owner: &str,
+        offer_sequence: u32,
+        taker_gets: &CurrencyAmount,
+        taker_pays: &CurrencyAmount,
+        meta: &TransactionMetadata<'_>,
+    ) -> Self {
+        let mut taker_gets_filled = taker_gets.zero();
+        let mut taker_pays_filled = taker_pays.zero();
+        let (mut remaining_taker_gets, mut remaining_taker_pays) = (None, None);
+
+        for node in meta.affected_nodes.iter() {
+            if let Some(created) = &node.created_node
+                && created.ledger_entry_type == "Offer"
+                && let Some(fields) = &created.new_fields
+                && fields.get("Account").and_then(Value::as_str) == Some(owner)
+            {
+                remaining_taker_gets = amount_field(fields, "TakerGets");
+                remaining_taker_pays = amount_field(fields, "TakerPays");
+            }
+            for crossed in [&node.modified_node, &node.deleted_node].into_iter().flatten() {
+                let Some((gave, received)) = crossed_offer(crossed, owner) else {
+                    continue;
+                };
+                if let Some(total) = taker_pays_filled.checked_add(&gave) {
+                    taker_pays_filled = total;
+                }
+                if let Some(total) = taker_gets_filled.checked_add(&received) {
+                    taker_gets_filled = total;
+                }
+            }
+        }
+
+        OfferFill {
+            tx_hash: tx_hash.to_string(),
+            offer_sequence,
+            ledger_index: None,
+            validated: false,
+            result: Some(meta.transaction_result.to_string()),
+            taker_gets_filled,
+            taker_pays_filled,
+            remaining_taker_gets,
+            remaining_taker_pays,
+        }
+    }
+}
+
+// How much an offer of another account gave (its TakerGets) and received (its TakerPays)
+fn crossed_offer(node: &LedgerNode<'_>, owner: &str) -> Option<(CurrencyAmount, CurrencyAmount)> {
+    if node.ledger_entry_type != "Offer" {
+        return None;
+    }
+    let final_fields = node.final_fields.as_ref()?;
+    let previous = node.previous_fields.as_ref()?;
+    if final_fields.get("Account").and_then(Value::as_str) == Some(owner) {
+        return None;
+    }
+    let change = |name: &str| amount_field(previous, name)?.checked_sub(&amount_field(final_fields, name)?);
+    Some((change("TakerGets")?, change("TakerPays")?))
+}
+
+fn amount_field(fields: &Value, name: &str) -> Option<CurrencyAmount> {
+    fields.get(name).and_then(|amount| CurrencyAmount::from_json(amount).ok())
+}
+
+/// Place an offer on the DEX
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn place_offer<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    offer: &OfferBuilder,
+    timeout: Duration,
+) -> Result<PlacedOffer> {
+    let (result, offer_sequence) = submit_offer(client, owner_secret, offer, timeout).await?;
+    let placed = PlacedOffer {
+        tx_hash: check_submit_result(&result)?,
+        offer_sequence,
+    };
+
+    info!(tx_hash = %placed.tx_hash, offer_sequence = placed.offer_sequence, "offer submitted");
+    Ok(placed)
+}
+
+/// Place an offer and report what it traded once it is in a validated ledger
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn place_offer_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    offer: &OfferBuilder,
+    timeout: Duration,
+) -> Result<OfferFill> {
+    let (result, offer_sequence) = submit_offer(client, owner_secret, offer, timeout).await?;
+    let validated = wait_for_submit_result(client, &result, timeout).await?;
+
+    let owner = owner_secret.wallet()?.classic_address.clone();
+    let mut fill = OfferFill::from_metadata(
+        &validated.tx_hash,
+        &owner,
+        offer_sequence,
+        &offer.taker_gets,
+        &offer.taker_pays,
+        &validated.meta,
+    );
+    fill.ledger_index = Some(validated.ledger_index);
+    fill.validated = true;
+    info!(
+        tx_hash = %fill.tx_hash,
+        filled = %fill.taker_pays_filled.value_text(),
+        on_book = fill.is_on_book(),
+        "offer validated"
+    );
+    Ok(fill)
+}
+
+async fn submit_offer<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    offer: &OfferBuilder,
+    timeout: Duration,
+) -> Result<(SubmitResult<'static>, u32)> {
+    offer.validate()?;
+    let wallet = owner_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let flags = (!offer.flags.is_empty()).then(|| FlagCollection::from(offer.flags.clone()));
+    let mut offer_create = OfferCreate::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        flags,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Amount::from(&offer.taker_gets),
+        Amount::from(&offer.taker_pays),
+        None,
+        None,
+    );
+
+    debug!(
+        taker_gets = %offer.taker_gets.value_text(),
+        taker_pays = %offer.taker_pays.value_text(),
+        flags = ?offer.flags,
+        "submitting offer"
+    );
+
+    let result = with_timeout("submit", timeout, async {
+        sign_and_submit(&mut offer_create, client, &wallet, true, false)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to submit offer", e))
+    })
+    .await?;
+
+    debug!(engine_result = %result.engine_result, "submit response received");
+    // Autofilled before signing
+    let offer_sequence = offer_create
+        .common_fields
+        .sequence
+        .ok_or_else(|| RippleError::client("Offer was submitted without a Sequence"))?;
+    Ok((result, offer_sequence))
+}
+
+/// Remove the offer created by the OfferCreate with sequence `offer_sequence`
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn cancel_offer<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    offer_sequence: u32,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_offer_cancel(client, owner_secret, offer_sequence, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "offer cancel submitted");
+    Ok(tx_hash)
+}
+
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn cancel_offer_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    offer_sequence: u32,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_offer_cancel(client, owner_secret, offer_sequence, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_offer_cancel<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    offer_sequence: u32,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let wallet = owner_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let mut offer_cancel = OfferCancel::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        offer_sequence,
+    );
+
+    debug!("submitting offer cancel");
+
+    let result = with_timeout("submit", timeout, async {
+        sign_and_submit(&mut offer_cancel, client, &wallet, true, false)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to submit offer cancel", e))
+    })
+    .await?;
+
+    debug!(engine_result = %result.engine_result, "submit response received");
+    Ok(result)
+}
+
+/// Look up an OfferCreate and report what it traded when it was executed.
+/// A cancel of an offer that was already filled or cancelled still succeeds, so check
+/// `is_on_book` here rather than the result of `cancel_offer`.
+#[instrument(skip(client))]
+pub async fn verify_offer<C: XRPLAsyncClient>(client: &C, tx_hash: &str, timeout: Duration) -> Result<OfferFill> {
+    let tx_result = client::get_transaction(client, tx_hash, timeout).await?;
+    let (base, tx_json, meta) = match &tx_result {
+        TxVersionMap::Default(tx) => (&tx.base, &tx.tx_json, tx.meta.as_ref()),
+        TxVersionMap::V1(tx_v1) => (&tx_v1.base, &tx_v1.tx_json, tx_v1.meta.as_ref()),
+    };
+
+    if tx_json.get("TransactionType").and_then(Value::as_str) != Some("OfferCreate") {
+        return Err(RippleError::InvalidInput(format!("{} is not an OfferCreate", tx_hash)));
+    }
+    let invalid = |name: &str| RippleError::client(format!("OfferCreate {} has no valid {}", tx_hash, name));
+    let owner = tx_json.get("Account").and_then(Value::as_str).ok_or_else(|| invalid("Account"))?;
+    let offer_sequence = tx_json
+        .get("Sequence")
+        .and_then(Value::as_u64)
+        .and_then(|sequence| u32::try_from(sequence).ok())
+        .ok_or_else(|| invalid("Sequence"))?;
+    let taker_gets = amount_field(tx_json, "TakerGets").ok_or_else(|| invalid("TakerGets"))?;
+    let taker_pays = amount_field(tx_json, "TakerPays").ok_or_else(|| invalid("TakerPays"))?;
+
+    let mut fill = match meta {
+        Some(meta) => OfferFill::from_metadata(tx_hash, owner, offer_sequence, &taker_gets, &taker_pays, meta),
+        None => OfferFill {
+            tx_hash: tx_hash.to_string(),
+            offer_sequence,
+            ledger_index: None,
+            validated: false,
+            result: None,
+            taker_gets_filled: taker_gets.zero(),
+            taker_pays_filled: taker_pays.zero(),
+            remaining_taker_gets: None,
+            remaining_taker_pays: None,
+        },
+    };
+    fill.ledger_index = base.ledger_index;
+    fill.validated = base.validated.unwrap_or(false);
+    debug!(
+        validated = fill.validated,
+        filled = %fill.taker_pays_filled.value_text(),
+        on_book = fill.is_on_book(),
+        "offer looked up"
+    );
+    Ok(fill)
+}
+
+/// What it would cost to take a given size from an order book
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FillQuote {
+    pub requested: CurrencyAmount,
+    /// How much of the requested size the offers can deliver
+    pub filled: CurrencyAmount,
+    /// What the taker pays for `filled`, rounded up to whole drops for XRP
+    pub cost: CurrencyAmount,
+    /// `cost` per unit of `filled`, in the units of an offer's quality (drops for XRP);
+    /// None when nothing can be filled
+    pub effective_price: Option<IssuedValue>,
+    /// Price of the last and worst offer the fill reaches
+    pub worst_price: Option<IssuedValue>,
+    pub offers_consumed: usize,
+}
+
+impl FillQuote {
+    /// True when the book is deep enough for the whole requested size
+    pub fn is_complete(&self) -> bool {
+        self.filled == self.requested
+    }
+}
+
+/// Walk `offers`, best first as `book_offers` returns them, to price taking `size` of their
+/// TakerGets. Only the funded part of each offer counts. Transfer fees and AMM pools are not
+/// included, and the book may move before an order reaches it, so treat this as an estimate.
+/// XRP amounts with more than 16 significant digits of drops are refused, not rounded.
+pub fn quote_fill(offers: &[BookOffer], size: &CurrencyAmount) -> Result<FillQuote> {
+    if !size.is_positive() {
+        return Err(RippleError::InvalidInput("Size to fill must be positive".to_string()));
+    }
+    let first = offers
+        .first()
+        .ok_or_else(|| RippleError::InvalidInput("No offers to fill from".to_string()))?;
+    let overflow = || RippleError::InvalidInput("Fill quote overflows".to_string());
+
+    let (gets_currency, pays_currency) = (size.zero(), first.taker_pays.zero());
+    let mut remaining = size.clone();
+    let mut filled = gets_currency.clone();
+    let mut cost = pays_currency.clone();
+    let mut worst_price = None;
+    let mut offers_consumed = 0;
+
+    for offer in offers {
+        if remaining.is_zero() {
+            break;
+        }
+        let (gets, pays) = offer.funded();
+        if gets.zero() != gets_currency || pays.zero() != pays_currency {
+            return Err(RippleError::InvalidInput(format!(
+                "Offer {} is not in the {}/{} book",
+                offer.offer_id,
+                gets_currency.currency(),
+                pays_currency.currency()
+            )));
+        }
+        if gets.is_zero() {
+            continue;
+        }
+
+        let (pays_value, gets_value) = (numeric(pays)?, numeric(gets)?);
+        let (taken, paid) = if gets <= &remaining {
+            (gets.clone(), pays.clone())
+        } else {
+            // Part of this offer, at its own rate
+            let paid = pays_value
+                .checked_mul(&numeric(&remaining)?)
+                .and_then(|value| value.checked_div(&gets_value))
+                .and_then(|value| with_value(pays, value))
+                .ok_or_else(overflow)?;
+            (remaining.clone(), paid)
+        };
+        filled = filled.checked_add(&taken).ok_or_else(overflow)?;
+        cost = cost.checked_add(&paid).ok_or_else(overflow)?;
+        remaining = remaining.checked_sub(&taken).ok_or_else(overflow)?;
+        worst_price = pays_value.checked_div(&gets_value);
+        offers_consumed += 1;
+    }
+
+    let quote = FillQuote {
+        requested: size.clone(),
+        effective_price: numeric(&cost)?.checked_div(&numeric(&filled)?),
+        filled,
+        cost,
+        worst_price,
+        offers_consumed,
+    };
+    debug!(
+        filled = %quote.filled.value_text(),
+        cost = %quote.cost.value_text(),
+        offers = quote.offers_consumed,
+        "fill quoted"
+    );
+    Ok(quote)
+}
+
+// Drops for XRP, the value for issued currencies. Fails rather than round XRP amounts with
+// more than 16 significant digits of drops.
+fn numeric(amount: &CurrencyAmount) -> Result<IssuedValue> {
+    match amount {
+        CurrencyAmount::Xrp(drops) => IssuedValue::try_from(*drops),
+        CurrencyAmount::Issued { value, .. } => Ok(*value),
+    }
+}
+
+// `value` in the currency of `template`, XRP rounded up to whole drops
+fn with_value(template: &CurrencyAmount, value: IssuedValue) -> Option<CurrencyAmount> {
+    match template {
+        CurrencyAmount::Xrp(_) => Drops::from_value_rounded_up(&value).map(CurrencyAmount::Xrp),
+        CurrencyAmount::Issued { currency, issuer, .. } => Some(CurrencyAmount::Issued {
+            currency: currency.clone(),
+            issuer: issuer.clone(),
+            value,
+        }),
+    }
+}
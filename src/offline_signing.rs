@@ -1,26 +1,33 @@
-use anyhow::{Context, Result};
+use crate::amount::{self, CurrencyAmount, Drops, IssuedValue};
+use crate::currency::CurrencyCode;
+use crate::error::{Result, RippleError};
+use crate::payment_options::PaymentOptions;
+use crate::secret::Secret;
+use crate::transactions;
+use serde_json::{Value, json};
 use std::borrow::Cow;
+use std::time::Duration;
+use tracing::{Span, debug, info, instrument, warn};
 use xrpl::{
     asynch::{
-        clients::{AsyncWebSocketClient, WebSocketOpen, client::XRPLClient},
+        clients::XRPLAsyncClient,
         ledger::get_latest_validated_ledger_sequence,
-        transaction::sign,
     },
-    core::binarycodec::encode,
     models::{
-        Amount, IssuedCurrencyAmount, XRPAmount,
+        Amount, XRPAmount,
         requests::submit::Submit as SubmitRequest,
+        results::submit::Submit as SubmitResult,
         transactions::payment::Payment,
     },
     wallet::Wallet,
 };
-use crate::client::get_account_info;
+use crate::client::{get_account_info, with_timeout};
+use crate::submission::{ValidatedTransaction, check_submit_result, wait_for_submit_result};
 
-type XRPLClientType = AsyncWebSocketClient<xrpl::asynch::clients::SingleExecutorMutex, WebSocketOpen>;
 
 /// Security configuration for offline transactions
 const TRANSACTION_EXPIRY_LEDGERS: u32 = 10; // Transaction expires after 10 ledgers (~50 seconds)
-const MINIMUM_FEE_DROPS: u32 = 12; // Minimum fee for testnet
+const MINIMUM_FEE_DROPS: u64 = 12; // Minimum fee for testnet
 
 // Parameters required for secure offline transaction construction
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -33,6 +40,8 @@ pub struct OfflineTransactionParams {
     pub last_ledger_sequence: u32,
     /// Current validated ledger when parameters were gathered
     pub current_ledger_index: u32,
+    // NetworkID to include in the transaction (only required on networks with ID > 1024)
+    pub network_id: Option<u32>,
 }
 
 impl OfflineTransactionParams {
@@ -40,25 +49,30 @@ impl OfflineTransactionParams {
     pub fn validate_security(&self, current_ledger: Option<u32>) -> Result<()> {
         // Ensure expiration is set
         if self.last_ledger_sequence == 0 {
-            anyhow::bail!("Transaction must have expiration (last_ledger_sequence) for security");
+            return Err(RippleError::InvalidInput(
+                "Transaction must have expiration (last_ledger_sequence) for security".to_string(),
+            ));
         }
         
         // Check if transaction has expired (if current ledger provided)
-        if let Some(current) = current_ledger {
-            if current >= self.last_ledger_sequence {
-                anyhow::bail!(
-                    "Transaction expired: current ledger {} >= expiration {}", 
-                    current, self.last_ledger_sequence
-                );
-            }
+        if let Some(current) = current_ledger
+            && current >= self.last_ledger_sequence
+        {
+            return Err(RippleError::Expired {
+                current_ledger: current,
+                last_ledger_sequence: self.last_ledger_sequence,
+                tx_hash: None,
+            });
         }
         
         // Validate fee is reasonable
-        let fee_drops: u32 = self.fee.parse()
-            .context("Fee must be valid numeric string")?;
+        let fee_drops: Drops = self.fee.parse()
+            .map_err(|_| RippleError::InvalidInput(format!("Fee must be valid numeric string, got {:?}", self.fee)))?;
             
-        if fee_drops < MINIMUM_FEE_DROPS {
-            anyhow::bail!("Fee {} drops is below minimum {}", fee_drops, MINIMUM_FEE_DROPS);
+        if fee_drops.as_u64() < MINIMUM_FEE_DROPS {
+            return Err(RippleError::InvalidInput(format!(
+                "Fee {} drops is below minimum {}", fee_drops, MINIMUM_FEE_DROPS
+            )));
         }
         
         Ok(())
@@ -71,23 +85,24 @@ impl OfflineTransactionParams {
 }
 
 // Gather transaction parameters online (to be passed to offline environment)
-pub async fn gather_transaction_params(
-    client: &XRPLClientType,
+#[instrument(skip(client, account_address), fields(account = account_address))]
+pub async fn gather_transaction_params<C: XRPLAsyncClient>(
+    client: &C,
     account_address: &str,
+    timeout: Duration,
 ) -> Result<OfflineTransactionParams> {
-    println!("Gathering transaction parameters for offline signing...");
-    println!("Account: {}", account_address);
-    
     // Get current validated ledger index for expiration calculation
-    let current_ledger_index = get_latest_validated_ledger_sequence(client)
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to get current ledger: {:?}", e))?;
+    let current_ledger_index = with_timeout("ledger", timeout, async {
+        get_latest_validated_ledger_sequence(client)
+            .await
+            .map_err(|e| RippleError::client_source("Failed to get current ledger", e))
+    })
+    .await?;
     
-    println!("Current validated ledger: {}", current_ledger_index);
+    debug!(ledger_index = current_ledger_index, "current validated ledger");
     
     // Get account info to determine next sequence number
-    let account_info = get_account_info(client, account_address).await
-        .context("Failed to retrieve account information")?;
+    let account_info = get_account_info(client, account_address, timeout).await?;
     
     let account_root = account_info.get_account_root();
     let sequence = account_root.sequence;
@@ -103,46 +118,37 @@ pub async fn gather_transaction_params(
         fee,
         last_ledger_sequence,
         current_ledger_index,
+        network_id: None,
     };
     
     // Validate security parameters before returning
-    params.validate_security(Some(current_ledger_index))
-        .context("Security validation failed")?;
+    params.validate_security(Some(current_ledger_index))?;
     
-    println!("Transaction parameters gathered:");
-    println!("  Sequence: {}", params.sequence);
-    println!("  Fee: {} drops", params.fee);
-    println!("  Current Ledger: {}", params.current_ledger_index);
-    println!("  Expires at Ledger: {}", params.last_ledger_sequence);
-    println!("  Valid for {} more ledgers (~{} seconds)", 
-             TRANSACTION_EXPIRY_LEDGERS, 
-             TRANSACTION_EXPIRY_LEDGERS * 5); // ~5 seconds per ledger
+    info!(
+        sequence = params.sequence,
+        fee = %params.fee,
+        ledger_index = params.current_ledger_index,
+        last_ledger_sequence = params.last_ledger_sequence,
+        "offline transaction parameters gathered"
+    );
     
     Ok(params)
 }
 
 // Sign transaction completely offline (no network calls)
+#[instrument(skip(user_secret, amount), fields(account))]
 pub fn offline_sign_transaction(
-    user_secret: &str,
+    user_secret: &Secret,
     to_address: &str,
     amount: Amount<'static>,
     params: OfflineTransactionParams,
+    options: &PaymentOptions,
 ) -> Result<String> {
-    println!("Signing transaction OFFLINE (no network calls)...");
-    
     // Validate parameters are secure before signing
-    params.validate_security(None)
-        .context("Transaction parameters failed security validation")?;
+    params.validate_security(None)?;
     
-    let wallet = Wallet::new(user_secret, 0)
-        .map_err(|e| anyhow::anyhow!("Wallet error: {:?}", e))?;
-    
-    println!("From address: {}", wallet.classic_address);
-    println!("To address: {}", to_address);
-    println!("Using offline parameters:");
-    println!("  Sequence: {}", params.sequence);
-    println!("  Fee: {}", params.fee);
-    println!("  Expires at ledger: {}", params.last_ledger_sequence);
+    let wallet = user_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
     
     // Create payment with manually set parameters (no network calls)
     let mut payment = Payment::new(
@@ -164,145 +170,185 @@ pub fn offline_sign_transaction(
         None, 
         None,
     );
+    payment.common_fields.network_id = params.network_id;
 
-    println!("Signing transaction offline...");
-    
-    // Sign the transaction
-    sign(&mut payment, &wallet, false)
-        .map_err(|e| anyhow::anyhow!("Sign error: {:?}", e))?;
+    // Sign and encode to hex blob, with the destination tag, memos etc. from `options`
+    let signed_blob = transactions::sign_payment(&payment, &wallet, options, &[])?;
 
-    println!("Encoding to signed blob...");
-    
-    // Encode to hex blob
-    let signed_blob = encode(&payment)
-        .map_err(|e| anyhow::anyhow!("Encode error: {:?}", e))?;
-
-    println!("Transaction signed offline successfully!");
-    println!("Signed blob length: {} characters", signed_blob.len());
-    println!("Blob preview: {}...", &signed_blob[..std::cmp::min(64, signed_blob.len())]);
-    println!("Security: Transaction expires at ledger {}", params.last_ledger_sequence);
+    info!(
+        blob_len = signed_blob.len(),
+        last_ledger_sequence = params.last_ledger_sequence,
+        "transaction signed offline"
+    );
     
     Ok(signed_blob)
 }
 
+// Sign a transaction model, in its JSON form, with the parameters gathered online. A Fee already
+// set on `transaction` is kept, for transactions that cost more than the base fee.
+pub(crate) fn sign_offline_json(
+    mut transaction: Value,
+    wallet: &Wallet,
+    params: &OfflineTransactionParams,
+) -> Result<String> {
+    params.validate_security(None)?;
+
+    if transaction.get("Fee").is_none_or(Value::is_null) {
+        transaction["Fee"] = json!(params.fee);
+    }
+    transaction["Sequence"] = json!(params.sequence);
+    transaction["LastLedgerSequence"] = json!(params.last_ledger_sequence);
+    if let Some(network_id) = params.network_id {
+        transaction["NetworkID"] = json!(network_id);
+    }
+    transactions::sign_with_options(transaction, wallet, &PaymentOptions::default(), &[])
+}
+
 // Submit pre-signed transaction blob using different connection
-pub async fn submit_signed_blob(
-    client: &XRPLClientType,
+#[instrument(skip_all)]
+pub async fn submit_signed_blob<C: XRPLAsyncClient>(
+    client: &C,
     signed_blob: &str,
+    timeout: Duration,
 ) -> Result<String> {
-    println!("Submitting pre-signed blob via different connection...");
-    println!("Blob length: {} characters", signed_blob.len());
-    println!("Blob preview: {}...", &signed_blob[..std::cmp::min(64, signed_blob.len())]);
-    
-    // Get current ledger to check if transaction has expired
-    let current_ledger = get_latest_validated_ledger_sequence(client)
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to get current ledger before submission: {:?}", e))?;
-    
-    println!("Current ledger at submission: {}", current_ledger);
-    
+    let submit_result = submit_blob(client, signed_blob, timeout).await?;
+
+    // Expired blobs come back as tefMAX_LEDGER and are rejected permanently
+    let tx_hash = check_submit_result(&submit_result)?;
+    info!(%tx_hash, "signed blob submitted");
+    Ok(tx_hash)
+}
+
+// Submit a pre-signed blob and wait until it is validated or its LastLedgerSequence has passed
+#[instrument(skip_all)]
+pub async fn submit_signed_blob_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    signed_blob: &str,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let submit_result = submit_blob(client, signed_blob, timeout).await?;
+    wait_for_submit_result(client, &submit_result, timeout).await
+}
+
+pub(crate) async fn submit_blob<C: XRPLAsyncClient>(
+    client: &C,
+    signed_blob: &str,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    debug!(blob_len = signed_blob.len(), "submitting signed blob");
+
     // Create submit request with the signed blob
     let submit_request = SubmitRequest::new(
         None, // id
-        Cow::Borrowed(signed_blob), // tx_blob
+        Cow::Owned(signed_blob.to_string()), // tx_blob
         None, // fail_hard
     );
 
-    let response = client.request_impl(submit_request.into()).await
-        .context("Failed to submit transaction blob")?;
-    
+    let response = with_timeout("submit", timeout, async {
+        client
+            .request(submit_request.into())
+            .await
+            .map_err(|e| RippleError::transaction("Failed to submit transaction blob", e))
+    })
+    .await?;
+
+    // A malformed or unparseable blob is refused with an error instead of an engine result
+    if let Some(error) = response.error {
+        let message = match response.error_message {
+            Some(detail) if detail != error => format!("{}: {}", error, detail),
+            _ => error.to_string(),
+        };
+        warn!(error = %message, "submit request failed");
+        return Err(RippleError::Transaction {
+            message,
+            tx_hash: None,
+            source: None,
+        });
+    }
+
     match response.result {
         Some(xrpl::models::results::XRPLResult::Submit(submit_result)) => {
-            println!("Transaction submitted successfully via different connection!");
-            println!("Engine result: {}", submit_result.engine_result);
-            
-            // Check for common expiration errors
-            if submit_result.engine_result.contains("EXPIRED") || 
-               submit_result.engine_result.contains("LATE") {
-                anyhow::bail!("Transaction expired: {}", submit_result.engine_result);
-            }
-            
-            let tx_hash = submit_result.tx_json
-                .get("hash")
-                .and_then(|h| h.as_str())
-                .ok_or_else(|| anyhow::anyhow!("No transaction hash in response"))?
-                .to_string();
-                
-            println!("Transaction hash: {}", tx_hash);
-            Ok(tx_hash)
-        }
-        _ => {
-            anyhow::bail!("Unexpected response type for submit request")
+            debug!(engine_result = %submit_result.engine_result, "submit response received");
+            Ok(submit_result)
         }
+        _ => Err(RippleError::client("Unexpected response type for submit request")),
     }
 }
 
 // High-level workflow: Complete offline signing process for XRP with security validation
-pub async fn offline_xrp_workflow(
-    online_client: &XRPLClientType,
-    offline_client: &XRPLClientType,
-    user_secret: &str,
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(online_client, offline_client, user_secret))]
+pub async fn offline_xrp_workflow<C: XRPLAsyncClient, D: XRPLAsyncClient>(
+    online_client: &C,
+    offline_client: &D,
+    network_id: Option<u32>,
+    timeout: Duration,
+    user_secret: &Secret,
     to_address: &str,
     amount_drops: u64,
+    options: &PaymentOptions,
 ) -> Result<String> {
     // Phase 1: Gather parameters online with security validation
-    let wallet = Wallet::new(user_secret, 0)
-        .map_err(|e| anyhow::anyhow!("Wallet error: {:?}", e))?;
+    let wallet = user_secret.wallet()?;
     
-    let params = gather_transaction_params(online_client, &wallet.classic_address).await
-        .context("Failed to gather secure transaction parameters")?;
+    let mut params = gather_transaction_params(online_client, &wallet.classic_address, timeout).await?;
+    params.network_id = network_id;
     
     // Phase 2: Sign completely offline with expiration bounds
-    let xrp_amount = XRPAmount(Cow::Owned(amount_drops.to_string()));
+    let xrp_amount = CurrencyAmount::Xrp(Drops::new(amount_drops)?);
     let signed_blob = offline_sign_transaction(
         user_secret,
         to_address,
-        Amount::XRPAmount(xrp_amount),
+        Amount::from(&xrp_amount),
         params.clone(),
-    ).context("Failed to sign transaction offline")?;
+        options,
+    )?;
     
     // Phase 3: Submit via different connection with expiration checking
-    let tx_hash = submit_signed_blob(offline_client, &signed_blob).await
-        .context("Failed to submit signed blob")?;
+    let tx_hash = submit_signed_blob(offline_client, &signed_blob, timeout).await?;
     
-    println!("Secure offline workflow completed successfully!");
+    info!(%tx_hash, "offline XRP workflow completed");
     Ok(tx_hash)
 }
 
 // High-level workflow: Complete offline signing process for tokens with security validation
-pub async fn offline_token_workflow(
-    online_client: &XRPLClientType,
-    offline_client: &XRPLClientType,
-    user_secret: &str,
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(online_client, offline_client, user_secret))]
+pub async fn offline_token_workflow<C: XRPLAsyncClient, D: XRPLAsyncClient>(
+    online_client: &C,
+    offline_client: &D,
+    network_id: Option<u32>,
+    timeout: Duration,
+    user_secret: &Secret,
     to_address: &str,
-    currency_code: &str,
-    amount: &str,
+    currency_code: &CurrencyCode,
+    amount: &IssuedValue,
+    options: &PaymentOptions,
 ) -> Result<String> {
     // Phase 1: Gather parameters online with security validation
-    let wallet = Wallet::new(user_secret, 0)
-        .map_err(|e| anyhow::anyhow!("Wallet error: {:?}", e))?;
+    let wallet = user_secret.wallet()?;
     
-    let params = gather_transaction_params(online_client, &wallet.classic_address).await
-        .context("Failed to gather secure transaction parameters")?;
+    let mut params = gather_transaction_params(online_client, &wallet.classic_address, timeout).await?;
+    params.network_id = network_id;
     
     // Phase 2: Sign completely offline with expiration bounds
-    let issued_amount = IssuedCurrencyAmount::new(
-        Cow::Owned(currency_code.to_string()),
-        Cow::Owned(wallet.classic_address.clone()),
-        Cow::Owned(amount.to_string()),
-    );
+    let issued_amount = CurrencyAmount::Issued {
+        currency: currency_code.clone(),
+        issuer: wallet.classic_address.clone(),
+        value: amount::positive_value(amount)?,
+    };
     
     let signed_blob = offline_sign_transaction(
         user_secret,
         to_address,
-        Amount::IssuedCurrencyAmount(issued_amount),
+        Amount::from(&issued_amount),
         params.clone(),
-    ).context("Failed to sign transaction offline")?;
+        options,
+    )?;
     
     // Phase 3: Submit via different connection with expiration checking
-    let tx_hash = submit_signed_blob(offline_client, &signed_blob).await
-        .context("Failed to submit signed blob")?;
+    let tx_hash = submit_signed_blob(offline_client, &signed_blob, timeout).await?;
     
-    println!("Secure offline token workflow completed successfully!");
+    info!(%tx_hash, "offline token workflow completed");
     Ok(tx_hash)
 }
\ No newline at end of file
@@ -0,0 +1,653 @@
+use crate::amount::Drops;
+use crate::client::{self, RawRequest, with_timeout};
+use crate::error::{Result, RippleError};
+use crate::ledger_object::{ledger_object_id, ledger_object_id_bytes};
+use crate::payment_options::PaymentOptions;
+use crate::ripple_time::{from_ripple_time, to_ripple_time};
+use crate::secret::Secret;
+use crate::submission::{ValidatedTransaction, check_submit_result, wait_for_submit_result};
+use crate::transactions::submit_signed_transaction;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime};
+use tracing::{Span, debug, info, instrument};
+use xrpl::{
+    asynch::{clients::XRPLAsyncClient, transaction::autofill},
+    core::keypairs,
+    models::{
+        FlagCollection, XRPAmount,
+        results::submit::Submit as SubmitResult,
+        transactions::{
+            payment_channel_claim::{PaymentChannelClaim, PaymentChannelClaimFlag},
+            payment_channel_create::PaymentChannelCreate,
+            payment_channel_fund::PaymentChannelFund,
+        },
+    },
+    wallet::Wallet,
+};
+
+// Signed claims are "CLM\0", the channel ID and the amount in drops as a big-endian u64
+const CLAIM_PREFIX: [u8; 4] = *b"CLM\0";
+// Channel IDs hash this ledger space key with the owner, the destination and the sequence
+const CHANNEL_SPACE_KEY: [u8; 2] = [0x00, 0x78];
+const ACCOUNT_CHANNELS_PAGE_LIMIT: u32 = 400;
+
+/// PaymentChannelCreate: set `amount` aside for off-ledger claims to `destination`.
+/// `settle_delay` is how long the owner must wait to close a channel that still holds XRP,
+/// so the destination has time to redeem its last claim.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelBuilder {
+    destination: String,
+    amount: Drops,
+    settle_delay: Duration,
+    cancel_after: Option<SystemTime>,
+    destination_tag: Option<u32>,
+    public_key: Option<String>,
+}
+
+impl ChannelBuilder {
+    pub fn new(destination: &str, amount: Drops, settle_delay: Duration) -> Self {
+        Self {
+            destination: destination.to_string(),
+            amount,
+            settle_delay,
+            cancel_after: None,
+            destination_tag: None,
+            public_key: None,
+        }
+    }
+
+    /// Fixed time after which the channel closes, whatever it holds
+    pub fn cancel_after(mut self, time: SystemTime) -> Self {
+        self.cancel_after = Some(time);
+        self
+    }
+
+    pub fn with_destination_tag(mut self, destination_tag: u32) -> Self {
+        self.destination_tag = Some(destination_tag);
+        self
+    }
+
+    /// Hex public key that signs claims (default: the owner's own key)
+    pub fn with_public_key(mut self, public_key: &str) -> Self {
+        self.public_key = Some(public_key.to_uppercase());
+        self
+    }
+
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    pub fn amount(&self) -> Drops {
+        self.amount
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.amount.is_zero() {
+            return Err(RippleError::InvalidInput("Channel amount must be positive".to_string()));
+        }
+        if u32::try_from(self.settle_delay.as_secs()).is_err() {
+            return Err(RippleError::InvalidInput(format!(
+                "Settle delay of {}s does not fit the ledger",
+                self.settle_delay.as_secs()
+            )));
+        }
+        if let Some(public_key) = &self.public_key
+            && hex::decode(public_key).map(|key| key.len()) != Ok(33)
+        {
+            return Err(RippleError::InvalidInput(format!("{} is not a public key", public_key)));
+        }
+        Ok(())
+    }
+}
+
+/// Submitted PaymentChannelCreate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacedChannel {
+    pub tx_hash: String,
+    pub channel_id: String,
+    /// Sequence of the PaymentChannelCreate, from which the channel ID is derived
+    pub sequence: u32,
+}
+
+/// Off-ledger claim: the channel's key authorizes paying out `amount` in total
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedClaim {
+    pub channel_id: String,
+    /// Everything the destination may have been paid from the channel so far, not an increment
+    pub amount: Drops,
+    pub signature: String,
+    pub public_key: String,
+}
+
+impl SignedClaim {
+    pub fn verify(&self) -> bool {
+        verify_claim(&self.channel_id, self.amount, &self.signature, &self.public_key)
+    }
+}
+
+/// Sign a claim offline (`channel_authorize` without a node). Uses the key pair of `secret`,
+/// which must be the channel's public key for the claim to be redeemable.
+#[instrument(skip(secret))]
+pub fn sign_claim(secret: &Secret, channel_id: &str, amount: Drops) -> Result<SignedClaim> {
+    sign_claim_with(&secret.wallet()?, channel_id, amount)
+}
+
+fn sign_claim_with(wallet: &Wallet, channel_id: &str, amount: Drops) -> Result<SignedClaim> {
+    let message = claim_message(channel_id, amount)?;
+    let signature =
+        keypairs::sign(&message, &wallet.private_key).map_err(|e| RippleError::transaction("Failed to sign claim", e))?;
+
+    debug!(drops = %amount, "claim signed");
+    Ok(SignedClaim {
+        channel_id: channel_id.to_uppercase(),
+        amount,
+        signature,
+        public_key: wallet.public_key.clone(),
+    })
+}
+
+/// Check a claim offline (`channel_verify` without a node). Malformed input is never valid.
+pub fn verify_claim(channel_id: &str, amount: Drops, signature: &str, public_key: &str) -> bool {
+    let Ok(message) = claim_message(channel_id, amount) else {
+        return false;
+    };
+    let well_formed = hex::decode(signature).is_ok_and(|bytes| !bytes.is_empty())
+        && hex::decode(public_key).is_ok_and(|bytes| bytes.len() == 33);
+    well_formed && keypairs::is_valid_message(&message, signature, public_key)
+}
+
+fn claim_message(channel_id: &str, amount: Drops) -> Result<Vec<u8>> {
+    let mut message = CLAIM_PREFIX.to_vec();
+    message.extend_from_slice(&ledger_object_id_bytes(channel_id, "channel")?);
+    message.extend_from_slice(&amount.as_u64().to_be_bytes());
+    Ok(message)
+}
+
+/// ID of the channel created by `owner`'s PaymentChannelCreate with sequence `sequence`
+pub fn channel_id(owner: &str, destination: &str, sequence: u32) -> Result<String> {
+    ledger_object_id(CHANNEL_SPACE_KEY, &[owner, destination], sequence)
+}
+
+/// PaymentChannelClaim. The destination redeems a signed claim; the owner can pay out
+/// without one, and either side can ask to close the channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelClaimBuilder {
+    channel_id: String,
+    balance: Option<Drops>,
+    claim: Option<SignedClaim>,
+    flags: Vec<PaymentChannelClaimFlag>,
+}
+
+impl ChannelClaimBuilder {
+    pub fn new(channel_id: &str) -> Self {
+        Self {
+            channel_id: channel_id.to_uppercase(),
+            balance: None,
+            claim: None,
+            flags: Vec::new(),
+        }
+    }
+
+    /// Redeem `claim` in full
+    pub fn redeem(mut self, claim: &SignedClaim) -> Self {
+        self.balance = Some(claim.amount);
+        self.claim = Some(claim.clone());
+        self
+    }
+
+    /// Total the destination has been paid once this claim is processed
+    pub fn with_balance(mut self, balance: Drops) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// tfClose: close now if the channel is empty or the sender is the destination, otherwise
+    /// once the settle delay has passed
+    pub fn close(mut self) -> Self {
+        self.flags.push(PaymentChannelClaimFlag::TfClose);
+        self
+    }
+
+    /// tfRenew: clear the channel's expiration (owner only)
+    pub fn renew(mut self) -> Self {
+        self.flags.push(PaymentChannelClaimFlag::TfRenew);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        ledger_object_id_bytes(&self.channel_id, "channel")?;
+        if let Some(claim) = &self.claim {
+            if !claim.channel_id.eq_ignore_ascii_case(&self.channel_id) {
+                return Err(RippleError::InvalidInput(format!(
+                    "Claim is for channel {}, not {}",
+                    claim.channel_id, self.channel_id
+                )));
+            }
+            if self.balance.is_some_and(|balance| balance > claim.amount) {
+                return Err(RippleError::InvalidInput("Balance cannot exceed the signed claim".to_string()));
+            }
+            if !claim.verify() {
+                return Err(RippleError::InvalidInput("Claim signature is invalid".to_string()));
+            }
+        }
+        if self.flags.contains(&PaymentChannelClaimFlag::TfClose) && self.flags.contains(&PaymentChannelClaimFlag::TfRenew)
+        {
+            return Err(RippleError::InvalidInput("tfClose and tfRenew cannot be combined".to_string()));
+        }
+        Ok(())
+    }
+
+    fn to_transaction(&self, account: &str) -> Result<PaymentChannelClaim<'static>> {
+        self.validate()?;
+        let flags = (!self.flags.is_empty()).then(|| FlagCollection::from(self.flags.clone()));
+        Ok(PaymentChannelClaim::new(
+            Cow::Owned(account.to_string()),
+            None,
+            None,
+            flags,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Cow::Owned(self.channel_id.clone()),
+            self.claim.as_ref().map(|claim| Cow::Owned(claim.amount.to_string())),
+            self.balance.map(|balance| Cow::Owned(balance.to_string())),
+            self.claim.as_ref().map(|claim| Cow::Owned(claim.public_key.clone())),
+            self.claim.as_ref().map(|claim| Cow::Owned(claim.signature.clone())),
+        ))
+    }
+}
+
+/// Channel in the ledger, as listed by `account_channels`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentChannel {
+    pub channel_id: String,
+    #[serde(rename = "account")]
+    pub owner: String,
+    #[serde(rename = "destination_account")]
+    pub destination: String,
+    /// Total XRP set aside, including what was already paid out
+    pub amount: Drops,
+    /// XRP already paid out to the destination
+    pub balance: Drops,
+    /// Hex key that signs claims
+    #[serde(rename = "public_key_hex", default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Seconds
+    pub settle_delay: u32,
+    /// Seconds since the Ripple epoch; set once the owner asks to close a funded channel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_after: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_tag: Option<u32>,
+}
+
+impl PaymentChannel {
+    pub fn expiration_time(&self) -> Option<SystemTime> {
+        self.expiration.map(from_ripple_time)
+    }
+
+    pub fn cancel_after_time(&self) -> Option<SystemTime> {
+        self.cancel_after.map(from_ripple_time)
+    }
+}
+
+/// Channels owned by `account` in the last validated ledger, optionally only the ones to `destination`
+#[instrument(skip(client, timeout))]
+pub async fn list_channels<C: RawRequest>(
+    client: &C,
+    account: &str,
+    destination: Option<&str>,
+    timeout: Duration,
+) -> Result<Vec<PaymentChannel>> {
+    let mut params = json!({ "account": account, "limit": ACCOUNT_CHANNELS_PAGE_LIMIT });
+    if let Some(destination) = destination {
+        params["destination_account"] = json!(destination);
+    }
+    let channels: Vec<PaymentChannel> = client::request_pages(client, "account_channels", params, "channels", timeout).await?;
+
+    debug!(channels = channels.len(), "payment channels listed");
+    Ok(channels)
+}
+
+/// Off-ledger view of one channel: what it holds, what was paid out on ledger, and the highest
+/// claim issued so far. The payer issues claims with `authorize`, the payee checks incoming ones
+/// with `accept`; both call `sync` with the ledger's view after funding or redeeming.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelTracker {
+    channel_id: String,
+    public_key: String,
+    capacity: Drops,
+    balance: Drops,
+    highest_claim: Option<SignedClaim>,
+}
+
+impl ChannelTracker {
+    pub fn new(channel_id: &str, public_key: &str, capacity: Drops) -> Result<Self> {
+        ledger_object_id_bytes(channel_id, "channel")?;
+        Ok(Self {
+            channel_id: channel_id.to_uppercase(),
+            public_key: public_key.to_uppercase(),
+            capacity,
+            balance: Drops::default(),
+            highest_claim: None,
+        })
+    }
+
+    pub fn from_channel(channel: &PaymentChannel) -> Result<Self> {
+        let public_key = channel.public_key.as_deref().ok_or_else(|| {
+            RippleError::InvalidInput(format!("Channel {} has no public key", channel.channel_id))
+        })?;
+        let mut tracker = Self::new(&channel.channel_id, public_key, channel.amount)?;
+        tracker.balance = channel.balance;
+        Ok(tracker)
+    }
+
+    /// Sign a claim that pays `amount` more than the highest claim so far
+    pub fn authorize(&mut self, payer_secret: &Secret, amount: Drops) -> Result<SignedClaim> {
+        let total = self
+            .highest_amount()
+            .checked_add(amount)
+            .filter(|&total| total <= self.capacity)
+            .ok_or_else(|| {
+                RippleError::InvalidInput(format!(
+                    "Paying {} more drops exceeds channel capacity of {} drops",
+                    amount, self.capacity
+                ))
+            })?;
+        let wallet = payer_secret.wallet()?;
+        if !wallet.public_key.eq_ignore_ascii_case(&self.public_key) {
+            return Err(RippleError::InvalidInput(format!(
+                "Secret does not hold the key of channel {}",
+                self.channel_id
+            )));
+        }
+        let claim = sign_claim_with(&wallet, &self.channel_id, total)?;
+        self.highest_claim = Some(claim.clone());
+        Ok(claim)
+    }
+
+    /// Check a claim received from the payer and keep it if it is the new highest.
+    /// Returns how much more it pays than the previous highest claim.
+    pub fn accept(&mut self, claim: &SignedClaim) -> Result<Drops> {
+        if !claim.channel_id.eq_ignore_ascii_case(&self.channel_id) || !claim.public_key.eq_ignore_ascii_case(&self.public_key)
+        {
+            return Err(RippleError::InvalidInput(format!("Claim is not for channel {}", self.channel_id)));
+        }
+        if !claim.verify() {
+            return Err(RippleError::InvalidInput("Claim signature is invalid".to_string()));
+        }
+        if claim.amount > self.capacity {
+            return Err(RippleError::InvalidInput(format!(
+                "Claim of {} drops exceeds channel capacity of {} drops",
+                claim.amount, self.capacity
+            )));
+        }
+        let increase = claim
+            .amount
+            .checked_sub(self.highest_amount())
+            .filter(|increase| !increase.is_zero())
+            .ok_or_else(|| {
+                RippleError::InvalidInput(format!(
+                    "Claim of {} drops does not exceed the highest claim of {} drops",
+                    claim.amount,
+                    self.highest_amount()
+                ))
+            })?;
+        self.highest_claim = Some(claim.clone());
+        Ok(increase)
+    }
+
+    /// Take capacity and paid-out balance from the ledger
+    pub fn sync(&mut self, channel: &PaymentChannel) -> Result<()> {
+        if !channel.channel_id.eq_ignore_ascii_case(&self.channel_id) {
+            return Err(RippleError::InvalidInput(format!("{} is not channel {}", channel.channel_id, self.channel_id)));
+        }
+        self.capacity = channel.amount;
+        self.balance = channel.balance;
+        Ok(())
+    }
+
+    pub fn channel_id(&self) -> &str {
+        &self.channel_id
+    }
+
+    pub fn capacity(&self) -> Drops {
+        self.capacity
+    }
+
+    /// Paid out on ledger
+    pub fn balance(&self) -> Drops {
+        self.balance
+    }
+
+    pub fn highest_claim(&self) -> Option<&SignedClaim> {
+        self.highest_claim.as_ref()
+    }
+
+    pub fn highest_amount(&self) -> Drops {
+        self.highest_claim.as_ref().map(|claim| claim.amount).unwrap_or(self.balance).max(self.balance)
+    }
+
+    /// What further claims can still pay
+    pub fn available(&self) -> Drops {
+        self.capacity.checked_sub(self.highest_amount()).unwrap_or_default()
+    }
+
+    /// Claimed off ledger but not yet redeemed
+    pub fn unredeemed(&self) -> Drops {
+        self.highest_amount().checked_sub(self.balance).unwrap_or_default()
+    }
+}
+
+/// Open a channel to `channel.destination()`
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn create_channel<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    channel: &ChannelBuilder,
+    timeout: Duration,
+) -> Result<PlacedChannel> {
+    let (result, sequence, channel_id) = submit_channel_create(client, owner_secret, channel, timeout).await?;
+    let placed = PlacedChannel {
+        tx_hash: check_submit_result(&result)?,
+        channel_id,
+        sequence,
+    };
+
+    info!(tx_hash = %placed.tx_hash, channel_id = %placed.channel_id, "channel create submitted");
+    Ok(placed)
+}
+
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn create_channel_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    channel: &ChannelBuilder,
+    timeout: Duration,
+) -> Result<(PlacedChannel, ValidatedTransaction)> {
+    let (result, sequence, channel_id) = submit_channel_create(client, owner_secret, channel, timeout).await?;
+    let validated = wait_for_submit_result(client, &result, timeout).await?;
+    let placed = PlacedChannel {
+        tx_hash: validated.tx_hash.clone(),
+        channel_id,
+        sequence,
+    };
+    Ok((placed, validated))
+}
+
+async fn submit_channel_create<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    channel: &ChannelBuilder,
+    timeout: Duration,
+) -> Result<(SubmitResult<'static>, u32, String)> {
+    channel.validate()?;
+    let wallet = owner_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let mut channel_create = PaymentChannelCreate::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        XRPAmount::from(channel.amount.to_string()),
+        Cow::Owned(channel.destination.clone()),
+        Cow::Owned(channel.public_key.clone().unwrap_or_else(|| wallet.public_key.clone())),
+        channel.settle_delay.as_secs() as u32,
+        channel.cancel_after.map(to_ripple_time).transpose()?,
+        channel.destination_tag,
+    );
+
+    debug!(destination = %channel.destination, drops = %channel.amount, "submitting channel create");
+
+    with_timeout("autofill", timeout, async {
+        autofill(&mut channel_create, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill channel create", e))
+    })
+    .await?;
+    let result = submit_signed_transaction(client, &wallet, &channel_create, &PaymentOptions::default(), timeout).await?;
+    let sequence = channel_create
+        .common_fields
+        .sequence
+        .ok_or_else(|| RippleError::client("Channel was autofilled without a Sequence"))?;
+    let channel_id = channel_id(&wallet.classic_address, &channel.destination, sequence)?;
+    Ok((result, sequence, channel_id))
+}
+
+/// Add `amount` to a channel (owner only). `expiration`, if set, replaces the channel's own.
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn fund_channel<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    channel_id: &str,
+    amount: Drops,
+    expiration: Option<SystemTime>,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_channel_fund(client, owner_secret, channel_id, amount, expiration, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "channel fund submitted");
+    Ok(tx_hash)
+}
+
+#[instrument(skip(client, owner_secret), fields(account))]
+pub async fn fund_channel_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    channel_id: &str,
+    amount: Drops,
+    expiration: Option<SystemTime>,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_channel_fund(client, owner_secret, channel_id, amount, expiration, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_channel_fund<C: XRPLAsyncClient>(
+    client: &C,
+    owner_secret: &Secret,
+    channel_id: &str,
+    amount: Drops,
+    expiration: Option<SystemTime>,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    ledger_object_id_bytes(channel_id, "channel")?;
+    if amount.is_zero() {
+        return Err(RippleError::InvalidInput("Channel fund amount must be positive".to_string()));
+    }
+    let wallet = owner_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let mut channel_fund = PaymentChannelFund::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        XRPAmount::from(amount.to_string()),
+        Cow::Owned(channel_id.to_uppercase()),
+        expiration.map(to_ripple_time).transpose()?,
+    );
+
+    debug!(drops = %amount, "submitting channel fund");
+
+    with_timeout("autofill", timeout, async {
+        autofill(&mut channel_fund, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill channel fund", e))
+    })
+    .await?;
+    let result = submit_signed_transaction(client, &wallet, &channel_fund, &PaymentOptions::default(), timeout).await?;
+    Ok(result)
+}
+
+/// Redeem a claim, pay out, or close a channel, as `claim` describes
+#[instrument(skip(client, claimer_secret, claim), fields(account))]
+pub async fn claim_channel<C: XRPLAsyncClient>(
+    client: &C,
+    claimer_secret: &Secret,
+    claim: &ChannelClaimBuilder,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_channel_claim(client, claimer_secret, claim, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "channel claim submitted");
+    Ok(tx_hash)
+}
+
+#[instrument(skip(client, claimer_secret, claim), fields(account))]
+pub async fn claim_channel_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    claimer_secret: &Secret,
+    claim: &ChannelClaimBuilder,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_channel_claim(client, claimer_secret, claim, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_channel_claim<C: XRPLAsyncClient>(
+    client: &C,
+    claimer_secret: &Secret,
+    claim: &ChannelClaimBuilder,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let wallet = claimer_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+    let mut channel_claim = claim.to_transaction(&wallet.classic_address)?;
+
+    debug!(
+        channel_id = %claim.channel_id,
+        balance = ?claim.balance.map(|balance| balance.to_string()),
+        flags = ?claim.flags,
+        "submitting channel claim"
+    );
+
+    with_timeout("autofill", timeout, async {
+        autofill(&mut channel_claim, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill channel claim", e))
+    })
+    .await?;
+    let result = submit_signed_transaction(client, &wallet, &channel_claim, &PaymentOptions::default(), timeout).await?;
+    Ok(result)
+}
@@ -0,0 +1,213 @@
+use crate::error::{Result, RippleError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+// rippled rejects transactions whose serialized Memos field is larger than this
+const MAX_MEMOS_BYTES: usize = 1024;
+// The Memos array's own field header and end marker
+const MEMOS_ARRAY_OVERHEAD: usize = 2;
+const INVOICE_ID_HEX_LENGTH: usize = 64;
+// Besides letters and digits, MemoType and MemoFormat may only use characters allowed in URLs
+const MEMO_URL_SYMBOLS: &str = "-._~:/?#[]@!$&'()*+,;=%";
+
+/// Memo attached to a payment, hex-encoded on ledger. `memo_data` holds any bytes (a hash, a
+/// protobuf, ...); `memo_type` (e.g. "invoice") and `memo_format` (a MIME type such as
+/// "text/plain") are text that may only use characters allowed in URLs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Memo {
+    pub memo_type: Option<String>,
+    pub memo_format: Option<String>,
+    #[serde(default, with = "hex_bytes")]
+    pub memo_data: Option<Vec<u8>>,
+}
+
+impl Memo {
+    pub fn new(memo_type: &str, memo_format: &str, memo_data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            memo_type: Some(memo_type.to_string()),
+            memo_format: Some(memo_format.to_string()),
+            memo_data: Some(memo_data.into()),
+        }
+    }
+
+    /// Memo with only text data, formatted as "text/plain"
+    pub fn text(memo_data: &str) -> Self {
+        Self {
+            memo_type: None,
+            memo_format: Some("text/plain".to_string()),
+            memo_data: Some(memo_data.as_bytes().to_vec()),
+        }
+    }
+
+    /// `memo_data` as text, if it is UTF-8
+    pub fn data_text(&self) -> Option<&str> {
+        self.memo_data.as_deref().and_then(|data| std::str::from_utf8(data).ok())
+    }
+
+    /// Read the ledger form, `{"Memo": {"MemoType": hex, ...}}`. MemoType and MemoFormat must
+    /// be UTF-8 text.
+    pub fn from_json(memo: &Value) -> Result<Self> {
+        let fields = memo
+            .get("Memo")
+            .and_then(Value::as_object)
+            .ok_or_else(|| RippleError::InvalidInput(format!("Invalid memo {}", memo)))?;
+        let field = |name: &str| -> Result<Option<Vec<u8>>> {
+            let Some(encoded) = fields.get(name).and_then(Value::as_str) else {
+                return Ok(None);
+            };
+            hex::decode(encoded)
+                .map(Some)
+                .map_err(|_| RippleError::InvalidInput(format!("{} is not hex: {:?}", name, encoded)))
+        };
+        let text_field = |name: &str| -> Result<Option<String>> {
+            field(name)?
+                .map(|bytes| {
+                    String::from_utf8(bytes)
+                        .map_err(|_| RippleError::InvalidInput(format!("{} is not UTF-8 text", name)))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            memo_type: text_field("MemoType")?,
+            memo_format: text_field("MemoFormat")?,
+            memo_data: field("MemoData")?,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut fields = Map::new();
+        for (name, bytes) in self.fields() {
+            if let Some(bytes) = bytes {
+                fields.insert(name.to_string(), json!(hex::encode_upper(bytes)));
+            }
+        }
+        json!({ "Memo": fields })
+    }
+
+    fn fields(&self) -> [(&'static str, Option<&[u8]>); 3] {
+        [
+            ("MemoType", self.memo_type.as_deref().map(str::as_bytes)),
+            ("MemoFormat", self.memo_format.as_deref().map(str::as_bytes)),
+            ("MemoData", self.memo_data.as_deref()),
+        ]
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.fields().iter().all(|(_, bytes)| bytes.is_none()) {
+            return Err(RippleError::InvalidInput("A memo needs a type, a format or data".to_string()));
+        }
+        for (name, text) in [("MemoType", &self.memo_type), ("MemoFormat", &self.memo_format)] {
+            let url_safe = |c: char| c.is_ascii_alphanumeric() || MEMO_URL_SYMBOLS.contains(c);
+            if let Some(text) = text
+                && !text.chars().all(url_safe)
+            {
+                return Err(RippleError::InvalidInput(format!(
+                    "{} may only use characters allowed in URLs, got {:?}",
+                    name, text
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Serialized size: object header and end marker, then a header and length prefix per field
+    fn serialized_len(&self) -> usize {
+        let field_len = |bytes: &[u8]| {
+            let length_prefix = if bytes.len() <= 192 { 1 } else { 2 };
+            1 + length_prefix + bytes.len()
+        };
+        2 + self.fields().iter().filter_map(|(_, bytes)| bytes.map(field_len)).sum::<usize>()
+    }
+}
+
+// Memo data as a hex string in serialized memos, as on ledger
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(bytes: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&hex::encode_upper(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|encoded| hex::decode(encoded).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+/// Optional fields every payment API accepts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaymentOptions {
+    /// Recipient behind a shared address, e.g. a customer of an exchange
+    pub destination_tag: Option<u32>,
+    /// Sender behind a shared address
+    pub source_tag: Option<u32>,
+    /// 256-bit reference to what the payment is for (64 hex digits), e.g. a hash of the invoice
+    pub invoice_id: Option<String>,
+    pub memos: Vec<Memo>,
+}
+
+impl PaymentOptions {
+    pub fn with_destination_tag(mut self, destination_tag: u32) -> Self {
+        self.destination_tag = Some(destination_tag);
+        self
+    }
+
+    pub fn with_source_tag(mut self, source_tag: u32) -> Self {
+        self.source_tag = Some(source_tag);
+        self
+    }
+
+    pub fn with_invoice_id(mut self, invoice_id: &str) -> Self {
+        self.invoice_id = Some(invoice_id.to_uppercase());
+        self
+    }
+
+    pub fn with_memo(mut self, memo: Memo) -> Self {
+        self.memos.push(memo);
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Some(invoice_id) = &self.invoice_id
+            && (invoice_id.len() != INVOICE_ID_HEX_LENGTH || !invoice_id.bytes().all(|b| b.is_ascii_hexdigit()))
+        {
+            return Err(RippleError::InvalidInput(format!(
+                "InvoiceID must be {} hex digits, got {:?}",
+                INVOICE_ID_HEX_LENGTH, invoice_id
+            )));
+        }
+        for memo in &self.memos {
+            memo.validate()?;
+        }
+        let memos_len = MEMOS_ARRAY_OVERHEAD + self.memos.iter().map(Memo::serialized_len).sum::<usize>();
+        if !self.memos.is_empty() && memos_len > MAX_MEMOS_BYTES {
+            return Err(RippleError::InvalidInput(format!(
+                "Memos take {} bytes, more than the {} allowed",
+                memos_len, MAX_MEMOS_BYTES
+            )));
+        }
+        Ok(())
+    }
+
+    // Set the fields on a transaction in rippled's JSON form
+    pub(crate) fn apply(&self, transaction: &mut Value) -> Result<()> {
+        self.validate()?;
+        if let Some(destination_tag) = self.destination_tag {
+            transaction["DestinationTag"] = json!(destination_tag);
+        }
+        if let Some(source_tag) = self.source_tag {
+            transaction["SourceTag"] = json!(source_tag);
+        }
+        if let Some(invoice_id) = &self.invoice_id {
+            transaction["InvoiceID"] = json!(invoice_id);
+        }
+        if !self.memos.is_empty() {
+            transaction["Memos"] = self.memos.iter().map(Memo::to_json).collect();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use crate::error::{Result, RippleError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds from the Unix epoch to the Ripple epoch, 2000-01-01T00:00:00Z
+pub const RIPPLE_EPOCH_OFFSET: u64 = 946_684_800;
+
+/// Seconds since the Ripple epoch, as ledger time fields (FinishAfter, CancelAfter, Expiration)
+/// hold it. Sub-second precision is dropped.
+pub fn to_ripple_time(time: SystemTime) -> Result<u32> {
+    let unix = time
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| RippleError::InvalidInput("Time is before the Unix epoch".to_string()))?
+        .as_secs();
+    unix.checked_sub(RIPPLE_EPOCH_OFFSET)
+        .and_then(|seconds| u32::try_from(seconds).ok())
+        .ok_or_else(|| RippleError::InvalidInput(format!("Unix time {} is outside the Ripple epoch range", unix)))
+}
+
+pub fn from_ripple_time(seconds: u32) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(RIPPLE_EPOCH_OFFSET + u64::from(seconds))
+}
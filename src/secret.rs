@@ -0,0 +1,51 @@
+use crate::error::{Result, RippleError};
+use std::fmt;
+use xrpl::wallet::Wallet;
+use zeroize::Zeroizing;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Seed or private key. The buffer is zeroed on drop and `Debug`/`Display`
+/// never print the value, so passing it to a logger cannot leak key material.
+#[derive(Clone)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(Zeroizing::new(value.into()))
+    }
+
+    /// Raw secret value, for handing to signing code
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    // Derive the signing wallet (sequence 0 for the master key pair)
+    pub(crate) fn wallet(&self) -> Result<Wallet> {
+        Wallet::new(self.expose_secret(), 0).map_err(RippleError::wallet)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Secret").field(&REDACTED).finish()
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
@@ -0,0 +1,320 @@
+use crate::client::with_timeout;
+use crate::error::{Result, RippleError};
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+use tracing::{debug, info, instrument, warn};
+use xrpl::{
+    asynch::{
+        clients::XRPLAsyncClient,
+        ledger::get_latest_validated_ledger_sequence,
+    },
+    models::{
+        requests::tx::Tx,
+        results::{
+            XRPLResult, metadata::TransactionMetadata, submit::Submit as SubmitResult,
+            tx::TxVersionMap,
+        },
+    },
+};
+
+/// Delay between checks for a validated ledger; ledgers close every 3-5 seconds
+const VALIDATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Category of a rippled engine result, given by its three-letter prefix.
+/// See <https://xrpl.org/docs/references/protocol/transactions/transaction-results>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineResultClass {
+    /// `tes`: applied to the ledger
+    Success,
+    /// `tec`: included in a ledger and the fee was claimed, but the action failed
+    ClaimedCost,
+    /// `tef`: not applied and cannot be applied in its current form
+    Failure,
+    /// `tel`: rejected locally by the server that received it
+    LocalError,
+    /// `tem`: malformed, can never succeed
+    Malformed,
+    /// `ter`: not applied yet, may succeed once prior transactions apply
+    Retry,
+    Unknown,
+}
+
+/// Engine result code returned by rippled for a submitted transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineResult {
+    code: String,
+    class: EngineResultClass,
+}
+
+impl EngineResult {
+    pub fn new(code: &str) -> Self {
+        let class = match code.get(..3) {
+            Some("tes") => EngineResultClass::Success,
+            Some("tec") => EngineResultClass::ClaimedCost,
+            Some("tef") => EngineResultClass::Failure,
+            Some("tel") => EngineResultClass::LocalError,
+            Some("tem") => EngineResultClass::Malformed,
+            Some("ter") => EngineResultClass::Retry,
+            _ => EngineResultClass::Unknown,
+        };
+        Self {
+            code: code.to_string(),
+            class,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn class(&self) -> EngineResultClass {
+        self.class
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.class == EngineResultClass::Success
+    }
+
+    /// Held in the server's transaction queue; it will be applied to a later ledger
+    pub fn is_queued(&self) -> bool {
+        self.code == "terQUEUED"
+    }
+
+    /// Whether the outcome can no longer change by waiting or resubmitting.
+    /// tes/tec results still only become final once they are in a validated ledger.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self.class,
+            EngineResultClass::Success
+                | EngineResultClass::ClaimedCost
+                | EngineResultClass::Failure
+                | EngineResultClass::Malformed
+        )
+    }
+
+    /// Resubmitting the same signed transaction later may still succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.class,
+            EngineResultClass::LocalError | EngineResultClass::Retry
+        ) && !self.is_queued()
+    }
+
+    /// Whether the transaction cost was destroyed (tes and tec results)
+    pub fn claimed_fee(&self) -> bool {
+        matches!(
+            self.class,
+            EngineResultClass::Success | EngineResultClass::ClaimedCost
+        )
+    }
+
+    /// LastLedgerSequence has already passed
+    pub fn is_expired(&self) -> bool {
+        self.code == "tefMAX_LEDGER"
+    }
+}
+
+impl fmt::Display for EngineResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.code)
+    }
+}
+
+impl From<&str> for EngineResult {
+    fn from(code: &str) -> Self {
+        Self::new(code)
+    }
+}
+
+// Turn a submit response into the transaction hash, or a retryable/permanent error
+pub(crate) fn check_submit_result(result: &SubmitResult<'_>) -> Result<String> {
+    let engine_result = EngineResult::new(&result.engine_result);
+    let tx_hash = result
+        .tx_json
+        .get("hash")
+        .and_then(|h| h.as_str())
+        .map(|h| h.to_string());
+    let message = result.engine_result_message.to_string();
+
+    if engine_result.is_queued() {
+        info!(tx_hash = tx_hash.as_deref(), "transaction queued for a later ledger");
+    }
+    if engine_result.is_success() || engine_result.is_queued() {
+        return tx_hash.ok_or_else(|| RippleError::client("No transaction hash in submit response"));
+    }
+
+    warn!(
+        %engine_result,
+        tx_hash = tx_hash.as_deref(),
+        message = %message,
+        "transaction not applied"
+    );
+    if engine_result.is_retryable() {
+        Err(RippleError::Retryable {
+            engine_result,
+            message,
+            tx_hash,
+        })
+    } else {
+        Err(RippleError::Rejected {
+            engine_result,
+            message,
+            tx_hash,
+        })
+    }
+}
+
+/// Final outcome of a transaction included in a validated ledger
+#[derive(Debug, Clone)]
+pub struct ValidatedTransaction {
+    pub tx_hash: String,
+    /// `TransactionResult` from the metadata; a tec code means the fee was claimed but nothing else happened
+    pub engine_result: EngineResult,
+    pub ledger_index: u32,
+    pub meta: TransactionMetadata<'static>,
+}
+
+impl ValidatedTransaction {
+    pub fn is_success(&self) -> bool {
+        self.engine_result.is_success()
+    }
+}
+
+// Follow a submit response until the transaction is in a validated ledger.
+// tes/tec/ter results can still end up in a ledger, so only tem/tef/tel fail early.
+pub(crate) async fn wait_for_submit_result<C: XRPLAsyncClient>(
+    client: &C,
+    result: &SubmitResult<'_>,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let engine_result = EngineResult::new(&result.engine_result);
+    let tx_hash = result
+        .tx_json
+        .get("hash")
+        .and_then(|h| h.as_str())
+        .map(|h| h.to_string());
+    let message = result.engine_result_message.to_string();
+
+    match engine_result.class() {
+        EngineResultClass::Success | EngineResultClass::ClaimedCost | EngineResultClass::Retry => {}
+        EngineResultClass::LocalError => {
+            return Err(RippleError::Retryable {
+                engine_result,
+                message,
+                tx_hash,
+            });
+        }
+        _ => {
+            return Err(RippleError::Rejected {
+                engine_result,
+                message,
+                tx_hash,
+            });
+        }
+    }
+
+    let tx_hash = tx_hash.ok_or_else(|| RippleError::client("No transaction hash in submit response"))?;
+    let last_ledger_sequence = result
+        .tx_json
+        .get("LastLedgerSequence")
+        .and_then(|l| l.as_u64())
+        .and_then(|l| u32::try_from(l).ok())
+        .ok_or_else(|| RippleError::Transaction {
+            message: "Transaction has no LastLedgerSequence, so it cannot be waited on".to_string(),
+            tx_hash: Some(tx_hash.clone()),
+            source: None,
+        })?;
+
+    wait_for_validation(client, &tx_hash, last_ledger_sequence, timeout).await
+}
+
+/// Poll the node until `tx_hash` is in a validated ledger, or until a ledger past
+/// `last_ledger_sequence` is validated without it (the transaction can then never apply).
+/// `timeout` bounds each poll, not the whole wait.
+#[instrument(skip(client))]
+pub async fn wait_for_validation<C: XRPLAsyncClient>(
+    client: &C,
+    tx_hash: &str,
+    last_ledger_sequence: u32,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    debug!("waiting for validated ledger");
+
+    loop {
+        // Read the validated ledger before the lookup: if the transaction is missing
+        // afterwards, it is also missing from every ledger up to this one
+        let validated_ledger = with_timeout("ledger", timeout, async {
+            get_latest_validated_ledger_sequence(client)
+                .await
+                .map_err(|e| RippleError::client_source("Failed to get latest validated ledger", e))
+        })
+        .await?;
+
+        if let Some(validated) = with_timeout("tx", timeout, lookup_validated(client, tx_hash)).await? {
+            info!(
+                ledger_index = validated.ledger_index,
+                engine_result = %validated.engine_result,
+                "transaction validated"
+            );
+            return Ok(validated);
+        }
+
+        if validated_ledger > last_ledger_sequence {
+            warn!(validated_ledger, "LastLedgerSequence passed without the transaction");
+            return Err(RippleError::Expired {
+                current_ledger: validated_ledger,
+                last_ledger_sequence,
+                tx_hash: Some(tx_hash.to_string()),
+            });
+        }
+
+        debug!(validated_ledger, "transaction not validated yet");
+        tokio::time::sleep(VALIDATION_POLL_INTERVAL).await;
+    }
+}
+
+// None while the transaction is unknown or only in an open/closed ledger
+async fn lookup_validated<C: XRPLAsyncClient>(client: &C, tx_hash: &str) -> Result<Option<ValidatedTransaction>> {
+    let request = Tx::new(None, None, None, None, Some(Cow::Owned(tx_hash.to_string())));
+    let response = client
+        .request(request.into())
+        .await
+        .map_err(|e| RippleError::client_source("tx request failed", e))?;
+
+    let tx = match response.result {
+        Some(XRPLResult::Tx(tx)) => tx,
+        _ => {
+            return match response.error.as_deref() {
+                Some("txnNotFound") => Ok(None),
+                error => Err(RippleError::Transaction {
+                    message: error.unwrap_or("Unexpected response type").to_string(),
+                    tx_hash: Some(tx_hash.to_string()),
+                    source: None,
+                }),
+            };
+        }
+    };
+
+    let base = match &tx {
+        TxVersionMap::Default(tx) => &tx.base,
+        TxVersionMap::V1(tx) => &tx.base,
+    };
+    if !base.validated.unwrap_or(false) {
+        return Ok(None);
+    }
+    let ledger_index = base
+        .ledger_index
+        .ok_or_else(|| RippleError::client("Validated transaction without ledger_index"))?;
+    let meta = tx
+        .get_transaction_metadata()
+        .cloned()
+        .ok_or_else(|| RippleError::client("Validated transaction without metadata"))?;
+
+    Ok(Some(ValidatedTransaction {
+        tx_hash: tx_hash.to_string(),
+        engine_result: EngineResult::new(&meta.transaction_result),
+        ledger_index,
+        meta,
+    }))
+}
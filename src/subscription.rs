@@ -0,0 +1,326 @@
+use crate::client::with_timeout;
+use crate::error::{Result, RippleError};
+use crate::network::{NetworkConfig, Transport};
+use crate::submission::EngineResult;
+use futures::{SinkExt, Stream, StreamExt, future};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+use tracing::{debug, info, instrument, warn};
+use xrpl::models::{Currency, results::metadata::TransactionMetadata};
+
+// ACCOUNT_ZERO: order book updates are not filtered for a particular taker
+const ANY_TAKER: &str = "rrrrrrrrrrrrrrrrrrrrrhoLvTp";
+
+/// Message published on a subscription stream
+pub trait StreamMessage: DeserializeOwned {
+    /// Value of the message's `type` field
+    const TYPE: &'static str;
+}
+
+/// A ledger was validated (`ledger` stream)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LedgerClosed {
+    pub ledger_index: u32,
+    pub ledger_hash: String,
+    /// Close time in seconds since the Ripple epoch (2000-01-01 UTC)
+    pub ledger_time: u64,
+    pub txn_count: u32,
+    pub fee_base: u64,
+    pub reserve_base: u64,
+    pub reserve_inc: u64,
+    /// Ledger ranges the node holds, e.g. "32570-85402"
+    pub validated_ledgers: Option<String>,
+}
+
+impl StreamMessage for LedgerClosed {
+    const TYPE: &'static str = "ledgerClosed";
+}
+
+/// A transaction in a validated ledger (`transactions`, `accounts` and order book streams)
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionEvent {
+    #[serde(deserialize_with = "engine_result")]
+    pub engine_result: EngineResult,
+    pub ledger_index: Option<u32>,
+    pub ledger_hash: Option<String>,
+    #[serde(default)]
+    pub validated: bool,
+    /// Transaction fields (`transaction` in API v1, `tx_json` in v2)
+    #[serde(alias = "tx_json")]
+    pub transaction: Value,
+    pub meta: Option<TransactionMetadata<'static>>,
+    // API v2 moves the hash out of the transaction fields
+    hash: Option<String>,
+}
+
+impl TransactionEvent {
+    pub fn tx_hash(&self) -> Option<&str> {
+        self.hash
+            .as_deref()
+            .or_else(|| self.transaction.get("hash").and_then(Value::as_str))
+    }
+
+    pub fn transaction_type(&self) -> Option<&str> {
+        self.transaction.get("TransactionType").and_then(Value::as_str)
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.engine_result.is_success()
+    }
+}
+
+impl StreamMessage for TransactionEvent {
+    const TYPE: &'static str = "transaction";
+}
+
+fn engine_result<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<EngineResult, D::Error> {
+    String::deserialize(deserializer).map(|code| EngineResult::new(&code))
+}
+
+/// Typed messages from a subscription. Each subscription has its own WebSocket
+/// connection; dropping it closes the connection, which ends the subscription.
+pub struct Subscription<T> {
+    connection: StreamConnection,
+    message: PhantomData<fn() -> T>,
+}
+
+impl<T: StreamMessage> fmt::Debug for Subscription<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("stream", &T::TYPE)
+            .field("closed", &self.connection.closed)
+            .finish()
+    }
+}
+
+impl<T: StreamMessage> Stream for Subscription<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match ready!(self.connection.poll_message(cx)) {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(None),
+            };
+            if message["type"] != T::TYPE {
+                debug!(message_type = %message["type"], "skipping stream message");
+                continue;
+            }
+            return Poll::Ready(Some(
+                serde_json::from_value(message)
+                    .map_err(|e| RippleError::client_source(format!("Malformed {} message", T::TYPE), e)),
+            ));
+        }
+    }
+}
+
+// WebSocket connection speaking rippled's JSON directly. xrpl-rust's client has no way
+// to read stream messages, and drops the fields of results it has no model for (account_tx).
+pub(crate) struct StreamConnection {
+    websocket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    // Stream messages that arrived while waiting for a response
+    backlog: VecDeque<Value>,
+    next_id: u64,
+    closed: bool,
+}
+
+impl StreamConnection {
+    pub(crate) async fn open(network: &NetworkConfig, timeout: Duration) -> Result<Self> {
+        if network.transport() != Transport::WebSocket {
+            return Err(RippleError::InvalidInput(format!(
+                "Streams need a ws:// or wss:// endpoint, got {}",
+                network.url
+            )));
+        }
+
+        let (websocket, _) = with_timeout("connect", timeout, async {
+            connect_async(network.url.as_str())
+                .await
+                .map_err(|e| RippleError::network(format!("Failed to connect to {}", network.url), e))
+        })
+        .await?;
+        Ok(Self {
+            websocket,
+            backlog: VecDeque::new(),
+            next_id: 0,
+            closed: false,
+        })
+    }
+
+    /// Send `command` with `params` and return the `result` of its response
+    pub(crate) async fn request(&mut self, command: &str, mut params: Value, timeout: Duration) -> Result<Value> {
+        self.next_id += 1;
+        let id = format!("{}-{}", command, self.next_id);
+        params["command"] = json!(command);
+        params["id"] = json!(id);
+
+        with_timeout(command, timeout, async {
+            self.websocket
+                .send(Message::Text(params.to_string()))
+                .await
+                .map_err(|e| RippleError::network(format!("Failed to send {} request", command), e))?;
+
+            loop {
+                let mut message = match future::poll_fn(|cx| self.poll_json(cx)).await {
+                    Some(message) => message?,
+                    None => {
+                        return Err(RippleError::Network {
+                            message: format!("Connection closed before the {} response", command),
+                            source: None,
+                        });
+                    }
+                };
+                if message.get("id").is_none() {
+                    self.backlog.push_back(message);
+                    continue;
+                }
+                if message["id"] != id.as_str() {
+                    continue;
+                }
+                if message["status"] != "success" {
+                    let error = message["error"].as_str().unwrap_or("Unexpected response type");
+                    warn!(command, error, "request failed");
+                    return Err(RippleError::client(format!("{} failed: {}", command, error)));
+                }
+                return Ok(message["result"].take());
+            }
+        })
+        .await
+    }
+
+    // Next stream message: queued ones first, then the socket. Yields one error when the
+    // connection is lost and then ends.
+    pub(crate) fn poll_message(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Value>>> {
+        if let Some(message) = self.backlog.pop_front() {
+            return Poll::Ready(Some(Ok(message)));
+        }
+        loop {
+            match ready!(self.poll_json(cx)) {
+                // Late response to a request that timed out
+                Some(Ok(message)) if message.get("id").is_some() => continue,
+                message => return Poll::Ready(message),
+            }
+        }
+    }
+
+    pub(crate) async fn next_message(&mut self) -> Option<Result<Value>> {
+        future::poll_fn(|cx| self.poll_message(cx)).await
+    }
+
+    fn poll_json(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Value>>> {
+        if self.closed {
+            return Poll::Ready(None);
+        }
+        loop {
+            let text = match ready!(self.websocket.poll_next_unpin(cx)) {
+                Some(Ok(Message::Text(text))) => text,
+                // Pings are answered by tungstenite while reading
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_) | Message::Binary(_))) => continue,
+                Some(Ok(Message::Close(_))) | None => {
+                    self.closed = true;
+                    warn!("stream connection closed by the node");
+                    return Poll::Ready(Some(Err(RippleError::Network {
+                        message: "Stream connection closed".to_string(),
+                        source: None,
+                    })));
+                }
+                Some(Err(e)) => {
+                    self.closed = true;
+                    return Poll::Ready(Some(Err(RippleError::network("Stream connection failed", e))));
+                }
+            };
+            return Poll::Ready(Some(
+                serde_json::from_str(&text).map_err(|e| RippleError::client_source("Malformed stream message", e)),
+            ));
+        }
+    }
+}
+
+/// Subscribe to validated ledgers
+pub async fn subscribe_ledgers(network: &NetworkConfig, timeout: Duration) -> Result<Subscription<LedgerClosed>> {
+    subscribe(network, json!({ "streams": ["ledger"] }), timeout).await
+}
+
+/// Subscribe to every validated transaction on the network
+pub async fn subscribe_transactions(
+    network: &NetworkConfig,
+    timeout: Duration,
+) -> Result<Subscription<TransactionEvent>> {
+    subscribe(network, json!({ "streams": ["transactions"] }), timeout).await
+}
+
+/// Subscribe to validated transactions that affect any of `accounts`
+pub async fn subscribe_accounts(
+    network: &NetworkConfig,
+    accounts: &[&str],
+    timeout: Duration,
+) -> Result<Subscription<TransactionEvent>> {
+    if accounts.is_empty() {
+        return Err(RippleError::InvalidInput(
+            "At least one account is required".to_string(),
+        ));
+    }
+    subscribe(network, json!({ "accounts": accounts }), timeout).await
+}
+
+/// Subscribe to transactions that change the order book in either direction
+pub async fn subscribe_order_book(
+    network: &NetworkConfig,
+    taker_gets: &Currency<'_>,
+    taker_pays: &Currency<'_>,
+    timeout: Duration,
+) -> Result<Subscription<TransactionEvent>> {
+    let book = json!({
+        "taker": ANY_TAKER,
+        "taker_gets": taker_gets,
+        "taker_pays": taker_pays,
+        "both": true,
+    });
+    subscribe(network, json!({ "books": [book] }), timeout).await
+}
+
+#[instrument(skip(network, request, timeout), fields(url = %network.url))]
+async fn subscribe<T: StreamMessage>(
+    network: &NetworkConfig,
+    request: Value,
+    timeout: Duration,
+) -> Result<Subscription<T>> {
+    let mut connection = StreamConnection::open(network, timeout).await?;
+    connection.request("subscribe", request, timeout).await?;
+
+    info!(stream = T::TYPE, "subscribed");
+    Ok(Subscription {
+        connection,
+        message: PhantomData,
+    })
+}
+
+// Stream opened by `command` rather than `subscribe` (path_find). The response
+// already holds the first message, so it is delivered before any update.
+pub(crate) async fn open_stream<T: StreamMessage>(
+    network: &NetworkConfig,
+    command: &str,
+    request: Value,
+    timeout: Duration,
+) -> Result<Subscription<T>> {
+    let mut connection = StreamConnection::open(network, timeout).await?;
+    let mut first = connection.request(command, request, timeout).await?;
+    first["type"] = json!(T::TYPE);
+    connection.backlog.push_front(first);
+
+    info!(stream = T::TYPE, "stream opened");
+    Ok(Subscription {
+        connection,
+        message: PhantomData,
+    })
+}
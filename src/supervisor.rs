@@ -0,0 +1,220 @@
+use crate::client::{Client, RawRequest};
+use crate::error::{Result, RippleError};
+use crate::network::NetworkConfig;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use tracing::{info, warn};
+use url::Url;
+use xrpl::{
+    asynch::clients::{
+        client::XRPLClient,
+        exceptions::{XRPLClientException, XRPLClientResult},
+    },
+    models::{requests::XRPLRequest, results::XRPLResponse},
+};
+
+/// How a SupervisedClient retries after losing its connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the second connection attempt; doubled after every failure
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Connection attempts made for one request, across all endpoints and reconnects,
+    /// before it fails; also the attempts `connect` makes
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            max_attempts: 8,
+        }
+    }
+}
+
+struct Connection {
+    client: Rc<Client>,
+    endpoint: usize,
+    // Bumped on every reconnect so concurrent failures only reconnect once
+    generation: u64,
+}
+
+/// Client that reconnects with backoff when the connection drops, rotating through
+/// the configured endpoints. Requests that failed on a dead connection are replayed
+/// on the new one, except submissions and other calls that are not idempotent.
+pub struct SupervisedClient {
+    endpoints: Vec<NetworkConfig>,
+    policy: ReconnectPolicy,
+    // Client is not Send, so neither is the supervisor; borrows never span an await
+    connection: RefCell<Connection>,
+    reconnecting: tokio::sync::Mutex<()>,
+}
+
+impl SupervisedClient {
+    pub async fn connect(endpoints: Vec<NetworkConfig>, policy: ReconnectPolicy) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(RippleError::InvalidInput(
+                "At least one endpoint is required".to_string(),
+            ));
+        }
+
+        // Start at the first endpoint: open_next moves one past `endpoints.len() - 1`
+        let mut attempts_left = policy.max_attempts;
+        let (endpoint, client) = open_next(&endpoints, &policy, endpoints.len() - 1, &mut attempts_left).await?;
+        Ok(Self {
+            endpoints,
+            policy,
+            connection: RefCell::new(Connection {
+                client: Rc::new(client),
+                endpoint,
+                generation: 0,
+            }),
+            reconnecting: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Endpoint the client is currently connected to
+    pub fn current_endpoint(&self) -> &NetworkConfig {
+        &self.endpoints[self.connection.borrow().endpoint]
+    }
+
+    pub fn endpoints(&self) -> &[NetworkConfig] {
+        &self.endpoints
+    }
+
+    fn current(&self) -> (Rc<Client>, u64) {
+        let connection = self.connection.borrow();
+        (connection.client.clone(), connection.generation)
+    }
+
+    // Replace the connection that failed at `generation`, spending connection attempts from
+    // `attempts_left`. Returns false once they are used up.
+    async fn reconnect(&self, generation: u64, attempts_left: &mut u32) -> bool {
+        let _reconnecting = self.reconnecting.lock().await;
+        let failed_endpoint = {
+            let connection = self.connection.borrow();
+            if connection.generation != generation {
+                // Another request already replaced the dead connection
+                return true;
+            }
+            connection.endpoint
+        };
+
+        match open_next(&self.endpoints, &self.policy, failed_endpoint, attempts_left).await {
+            Ok((endpoint, client)) => {
+                let mut connection = self.connection.borrow_mut();
+                *connection = Connection {
+                    client: Rc::new(client),
+                    endpoint,
+                    generation: generation + 1,
+                };
+                info!(url = %self.endpoints[endpoint].url, "reconnected to XRPL");
+                true
+            }
+            Err(e) => {
+                warn!(error = %e, "giving up reconnecting to XRPL");
+                false
+            }
+        }
+    }
+}
+
+impl XRPLClient for SupervisedClient {
+    async fn request_impl<'a: 'b, 'b>(
+        &self,
+        request: XRPLRequest<'a>,
+    ) -> XRPLClientResult<XRPLResponse<'b>> {
+        let replayable = is_replayable(&request);
+        let mut attempts_left = self.policy.max_attempts;
+        loop {
+            let (client, generation) = self.current();
+            match client.request_impl(request.clone()).await {
+                Err(e) if is_connection_error(&e) && attempts_left > 0 => {
+                    warn!(url = %client.get_host(), error = %e, "XRPL connection lost");
+                    if !self.reconnect(generation, &mut attempts_left).await || !replayable {
+                        return Err(e);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn get_host(&self) -> Url {
+        self.current().0.get_host()
+    }
+}
+
+// Raw requests are read-only queries, so they are always replayed on the new connection
+impl RawRequest for SupervisedClient {
+    async fn request_json(&self, command: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let mut attempts_left = self.policy.max_attempts;
+        loop {
+            let (client, generation) = self.current();
+            match client.request_json(command, params.clone(), timeout).await {
+                Err(e @ RippleError::Network { .. }) if attempts_left > 0 => {
+                    warn!(url = %client.get_host(), error = %e, "XRPL connection lost");
+                    if !self.reconnect(generation, &mut attempts_left).await {
+                        return Err(e);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+// Open a connection to the endpoints after `after`, wrapping around, with exponential backoff.
+// Every attempt is taken from `attempts_left`; at least one is made.
+async fn open_next(
+    endpoints: &[NetworkConfig],
+    policy: &ReconnectPolicy,
+    after: usize,
+    attempts_left: &mut u32,
+) -> Result<(usize, Client)> {
+    let mut backoff = policy.initial_backoff;
+    let mut last_error = None;
+    for attempt in 0..(*attempts_left).max(1) {
+        *attempts_left = attempts_left.saturating_sub(1);
+        if attempt > 0 {
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(policy.max_backoff);
+        }
+        let endpoint = (after + 1 + attempt as usize) % endpoints.len();
+        match Client::connect(&endpoints[endpoint]).await {
+            Ok(client) => return Ok((endpoint, client)),
+            Err(e) => {
+                warn!(url = %endpoints[endpoint].url, attempt, error = %e, "XRPL connection attempt failed");
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.expect("at least one connection attempt is made"))
+}
+
+// Socket and HTTP failures; malformed responses are not a reason to switch nodes
+fn is_connection_error(error: &XRPLClientException) -> bool {
+    matches!(
+        error,
+        XRPLClientException::XRPLWebSocketError(_)
+            | XRPLClientException::XRPLJsonRpcError(_)
+            | XRPLClientException::IoError(_)
+    )
+}
+
+// A submit may have reached the node before the connection dropped, and subscriptions
+// belong to the old connection, so those are reported to the caller instead of replayed
+fn is_replayable(request: &XRPLRequest<'_>) -> bool {
+    !matches!(
+        request,
+        XRPLRequest::Submit(_)
+            | XRPLRequest::SubmitMultisigned(_)
+            | XRPLRequest::Subscribe(_)
+            | XRPLRequest::Unsubscribe(_)
+            | XRPLRequest::PathFind(_)
+    )
+}
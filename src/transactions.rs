@@ -1,39 +1,94 @@
-use anyhow::Result;
+use crate::amount::{self, CurrencyAmount, Drops, IssuedValue};
+use crate::client::{Path, PathAlternative, PathRequest, RawRequest, get_account_info, ripple_path_find, with_timeout};
+use crate::currency::CurrencyCode;
+use crate::error::{Result, RippleError};
+use crate::offline_signing::submit_blob;
+use crate::payment_options::PaymentOptions;
+use crate::secret::Secret;
+use crate::submission::{ValidatedTransaction, check_submit_result, wait_for_submit_result};
+use serde::Serialize;
+use serde_json::{Value, json};
 use std::borrow::Cow;
+use std::time::Duration;
+use tracing::{Span, debug, info, instrument};
 use xrpl::{
     asynch::{
-        clients::{AsyncWebSocketClient, WebSocketOpen},
-        transaction::sign_and_submit,
+        clients::XRPLAsyncClient,
+        transaction::{autofill, sign_and_submit},
+    },
+    core::{
+        addresscodec::decode_classic_address,
+        binarycodec::{encode, encode_for_signing},
+        keypairs,
     },
     models::{
-        Amount, IssuedCurrencyAmount, XRPAmount,
-        transactions::{payment::Payment, trust_set::TrustSet},
+        Amount, Currency, FlagCollection, IssuedCurrencyAmount,
+        results::submit::Submit as SubmitResult,
+        transactions::{
+            payment::{Payment, PaymentFlag},
+            trust_set::TrustSet,
+        },
     },
     wallet::Wallet,
 };
 
-type XRPLClientType =
-    AsyncWebSocketClient<xrpl::asynch::clients::SingleExecutorMutex, WebSocketOpen>;
+// TransferRate is a fraction of one billion; 1_000_000_000 (or unset) means no fee
+const TRANSFER_RATE_PARITY: u32 = 1_000_000_000;
+
+// Binary form of the Paths field: header for type code 18 (PathSet), field code 1,
+// then each step as a type byte and its 20-byte values, paths separated by 0xFF
+const PATHS_FIELD_HEADER: [u8; 2] = [0x01, 0x12];
+const PATH_STEP_ACCOUNT: u8 = 0x01;
+const PATH_STEP_CURRENCY: u8 = 0x10;
+const PATH_STEP_ISSUER: u8 = 0x20;
+const PATH_SEPARATOR: u8 = 0xFF;
+const PATH_SET_END: u8 = 0x00;
 
 /// Send XRP from one account to another
-pub async fn send_xrp(
-    client: &XRPLClientType,
-    user1_secret: &str,
+#[instrument(skip(client, user1_secret), fields(account))]
+pub async fn send_xrp<C: XRPLAsyncClient>(
+    client: &C,
+    user1_secret: &Secret,
     user2_address: &str,
     amount_drops: u64,
+    options: &PaymentOptions,
+    timeout: Duration,
 ) -> Result<String> {
-    println!("Preparing XRP transfer...");
-    println!("  From seed: {}...", &user1_secret[..8]);
-    println!("  To address: {}", user2_address);
-    println!("  Amount: {} drops", amount_drops);
+    let result = submit_xrp_payment(client, user1_secret, user2_address, amount_drops, options, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
 
-    let wallet =
-        Wallet::new(user1_secret, 0).map_err(|e| anyhow::anyhow!("Wallet error: {:?}", e))?;
-    println!("From address: {}", wallet.classic_address);
+    info!(%tx_hash, "XRP payment submitted");
+    Ok(tx_hash)
+}
 
-    let xrp_amount = XRPAmount(Cow::Owned(amount_drops.to_string()));
+/// Send XRP and wait until the payment is in a validated ledger; `timeout` bounds each lookup while waiting
+#[instrument(skip(client, user1_secret), fields(account))]
+pub async fn send_xrp_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    user1_secret: &Secret,
+    user2_address: &str,
+    amount_drops: u64,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_xrp_payment(client, user1_secret, user2_address, amount_drops, options, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
 
-    let mut payment = Payment::new(
+async fn submit_xrp_payment<C: XRPLAsyncClient>(
+    client: &C,
+    user1_secret: &Secret,
+    user2_address: &str,
+    amount_drops: u64,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let wallet = user1_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let xrp_amount = CurrencyAmount::Xrp(Drops::new(amount_drops)?);
+
+    let payment = Payment::new(
         Cow::Owned(wallet.classic_address.clone()),
         None,
         None,
@@ -44,7 +99,7 @@ pub async fn send_xrp(
         None,
         None,
         None,
-        Amount::XRPAmount(xrp_amount),
+        Amount::from(&xrp_amount),
         Cow::Owned(user2_address.to_string()),
         None,
         None,
@@ -53,42 +108,54 @@ pub async fn send_xrp(
         None,
     );
 
-    println!("Submitting XRP transaction...");
-
-    let result = sign_and_submit(&mut payment, client, &wallet, true, false)
-        .await
-        .map_err(|e| anyhow::anyhow!("Transaction error: {:?}", e))?;
+    debug!("submitting XRP payment");
+    submit_signed_payment(client, &wallet, payment, options, &[], timeout).await
+}
 
-    let tx_hash = result
-        .tx_json
-        .get("hash")
-        .and_then(|h| h.as_str())
-        .unwrap_or("")
-        .to_string();
+#[instrument(skip(client, user_secret), fields(account))]
+pub async fn setup_trustline<C: XRPLAsyncClient>(
+    client: &C,
+    user_secret: &Secret,
+    issuer_address: &str,
+    currency_code: &CurrencyCode,
+    limit: &str,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_trustline(client, user_secret, issuer_address, currency_code, limit, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
 
-    println!("XRP transaction submitted successfully!");
-    println!("Transaction hash: {}", tx_hash);
-    println!("Engine result: {}", result.engine_result);
+    info!(%tx_hash, "trustline submitted");
     Ok(tx_hash)
 }
 
-pub async fn setup_trustline(
-    client: &XRPLClientType,
-    user_secret: &str,
+#[instrument(skip(client, user_secret), fields(account))]
+pub async fn setup_trustline_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    user_secret: &Secret,
     issuer_address: &str,
-    currency_code: &str,
+    currency_code: &CurrencyCode,
     limit: &str,
-) -> Result<String> {
-    println!("Setting up trustline...");
-    println!("  User seed: {}...", &user_secret[..8]);
-    println!("  Issuer: {}", issuer_address);
-    println!("  Currency: {}", currency_code);
-    println!("  Limit: {}", limit);
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_trustline(client, user_secret, issuer_address, currency_code, limit, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
 
-    let wallet =
-        Wallet::new(user_secret, 0).map_err(|e| anyhow::anyhow!("Wallet error: {:?}", e))?;
-    println!("User address: {}", wallet.classic_address);
+async fn submit_trustline<C: XRPLAsyncClient>(
+    client: &C,
+    user_secret: &Secret,
+    issuer_address: &str,
+    currency_code: &CurrencyCode,
+    limit: &str,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let wallet = user_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
 
+    let limit: IssuedValue = limit.parse()?;
+    if limit.is_negative() {
+        return Err(RippleError::InvalidInput(format!("Trust line limit cannot be negative, got {}", limit)));
+    }
     let limit_amount = IssuedCurrencyAmount::new(
         Cow::Owned(currency_code.to_string()),
         Cow::Owned(issuer_address.to_string()),
@@ -111,49 +178,87 @@ pub async fn setup_trustline(
         None,
     );
 
-    println!("Submitting trustline transaction...");
+    debug!("submitting trustline");
+
+    let result = with_timeout("submit", timeout, async {
+        sign_and_submit(&mut trust_set, client, &wallet, true, false)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to submit trustline", e))
+    })
+    .await?;
 
-    let result = sign_and_submit(&mut trust_set, client, &wallet, true, false)
-        .await
-        .map_err(|e| anyhow::anyhow!("Transaction error: {:?}", e))?;
+    debug!(engine_result = %result.engine_result, "submit response received");
+    Ok(result)
+}
 
-    let tx_hash = result
-        .tx_json
-        .get("hash")
-        .and_then(|h| h.as_str())
-        .unwrap_or("")
-        .to_string();
+#[instrument(skip(client, issuer_secret), fields(account))]
+pub async fn send_issued_token<C: XRPLAsyncClient>(
+    client: &C,
+    issuer_secret: &Secret,
+    user_address: &str,
+    currency_code: &CurrencyCode,
+    amount: &IssuedValue,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_issued_token(
+        client,
+        issuer_secret,
+        user_address,
+        currency_code,
+        amount,
+        options,
+        timeout,
+    )
+    .await?;
+    let tx_hash = check_submit_result(&result)?;
 
-    println!("Trustline transaction submitted successfully!");
-    println!("  Transaction hash: {}", tx_hash);
-    println!("  Engine result: {}", result.engine_result);
+    info!(%tx_hash, "issued token payment submitted");
     Ok(tx_hash)
 }
 
-pub async fn send_issued_token(
-    client: &XRPLClientType,
-    issuer_secret: &str,
+#[instrument(skip(client, issuer_secret), fields(account))]
+pub async fn send_issued_token_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    issuer_secret: &Secret,
     user_address: &str,
-    currency_code: &str,
-    amount: &str,
-) -> Result<String> {
-    println!("Preparing issued token transfer...");
-    println!("  Issuer seed: {}...", &issuer_secret[..8]);
-    println!("  To address: {}", user_address);
-    println!("  Currency: {}", currency_code);
-    println!("  Amount: {}", amount);
+    currency_code: &CurrencyCode,
+    amount: &IssuedValue,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_issued_token(
+        client,
+        issuer_secret,
+        user_address,
+        currency_code,
+        amount,
+        options,
+        timeout,
+    )
+    .await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
 
-    let wallet =
-        Wallet::new(issuer_secret, 0).map_err(|e| anyhow::anyhow!("Wallet error: {:?}", e))?;
-    println!("Issuer address: {}", wallet.classic_address);
+async fn submit_issued_token<C: XRPLAsyncClient>(
+    client: &C,
+    issuer_secret: &Secret,
+    user_address: &str,
+    currency_code: &CurrencyCode,
+    amount: &IssuedValue,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let wallet = issuer_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
 
-    let issued_amount = IssuedCurrencyAmount::new(
-        Cow::Owned(currency_code.to_string()),
-        Cow::Owned(wallet.classic_address.clone()),
-        Cow::Owned(amount.to_string()),
-    );
+    let issued_amount = CurrencyAmount::Issued {
+        currency: currency_code.clone(),
+        issuer: wallet.classic_address.clone(),
+        value: amount::positive_value(amount)?,
+    };
 
-    let mut payment = Payment::new(
+    let payment = Payment::new(
         Cow::Owned(wallet.classic_address.clone()),
         None,
         None,
@@ -164,7 +269,7 @@ pub async fn send_issued_token(
         None,
         None,
         None,
-        Amount::IssuedCurrencyAmount(issued_amount),
+        Amount::from(&issued_amount),
         Cow::Owned(user_address.to_string()),
         None,
         None,
@@ -173,21 +278,483 @@ pub async fn send_issued_token(
         None,
     );
 
-    println!("Submitting issued token transaction...");
+    debug!("submitting issued token payment");
+    submit_signed_payment(client, &wallet, payment, options, &[], timeout).await
+}
+
+/// Send tokens issued by any account (`amount` names the issuer). Unless the sender or the
+/// destination is the issuer, the issuer's TransferRate is charged on top: when `send_max` is
+/// None it is set to `amount` plus that fee. `timeout` bounds the issuer lookup.
+#[instrument(skip(client, sender_secret, amount, send_max), fields(account))]
+pub async fn send_token<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    destination: &str,
+    amount: &CurrencyAmount,
+    send_max: Option<&CurrencyAmount>,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_token_payment(client, sender_secret, destination, amount, send_max, options, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "token payment submitted");
+    Ok(tx_hash)
+}
+
+/// `send_token`, then wait until the payment is in a validated ledger
+#[instrument(skip(client, sender_secret, amount, send_max), fields(account))]
+pub async fn send_token_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    destination: &str,
+    amount: &CurrencyAmount,
+    send_max: Option<&CurrencyAmount>,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_token_payment(client, sender_secret, destination, amount, send_max, options, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_token_payment<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    destination: &str,
+    amount: &CurrencyAmount,
+    send_max: Option<&CurrencyAmount>,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let CurrencyAmount::Issued { issuer, value, .. } = amount else {
+        return Err(RippleError::InvalidInput(
+            "send_token moves issued currencies; use send_xrp for XRP".to_string(),
+        ));
+    };
+    if !value.is_positive() {
+        return Err(RippleError::InvalidInput(format!("Amount must be positive, got {}", value)));
+    }
+
+    let wallet = sender_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let send_max = match send_max {
+        Some(send_max) => Some(send_max.clone()),
+        None if wallet.classic_address == *issuer || destination == issuer => None,
+        None => with_transfer_fee(client, amount, timeout).await?,
+    };
+
+    let payment = Payment::new(
+        Cow::Owned(wallet.classic_address.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Amount::from(amount),
+        Cow::Owned(destination.to_string()),
+        None,
+        None,
+        None,
+        None,
+        send_max.as_ref().map(Amount::from),
+    );
+
+    debug!(send_max = ?send_max.as_ref().map(CurrencyAmount::value_text), "submitting token payment");
+    submit_signed_payment(client, &wallet, payment, options, &[], timeout).await
+}
+
+// `amount` plus the issuer's transfer fee, or None when the issuer charges none
+async fn with_transfer_fee<C: XRPLAsyncClient>(
+    client: &C,
+    amount: &CurrencyAmount,
+    timeout: Duration,
+) -> Result<Option<CurrencyAmount>> {
+    let CurrencyAmount::Issued { currency, issuer, value } = amount else {
+        return Ok(None);
+    };
+    let account_info = get_account_info(client, issuer, timeout).await?;
+    let transfer_rate = account_info
+        .get_account_root()
+        .transfer_rate
+        .filter(|&rate| rate > TRANSFER_RATE_PARITY);
+    let Some(transfer_rate) = transfer_rate else {
+        return Ok(None);
+    };
+
+    debug!(issuer = %issuer, transfer_rate, "issuer charges a transfer fee");
+    Ok(Some(CurrencyAmount::Issued {
+        currency: currency.clone(),
+        issuer: issuer.clone(),
+        value: value.mul_ratio(transfer_rate, TRANSFER_RATE_PARITY)?,
+    }))
+}
+
+/// A Payment with the fields used for cross-currency and partial payments.
+/// `amount` is what the destination receives; the sender spends at most `send_max`,
+/// which may be in another currency, or the same currency as `amount` when unset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentBuilder {
+    destination: String,
+    amount: CurrencyAmount,
+    send_max: Option<CurrencyAmount>,
+    deliver_min: Option<CurrencyAmount>,
+    paths: Vec<Path>,
+    options: PaymentOptions,
+}
+
+impl PaymentBuilder {
+    pub fn new(destination: &str, amount: CurrencyAmount) -> Self {
+        Self {
+            destination: destination.to_string(),
+            amount,
+            send_max: None,
+            deliver_min: None,
+            paths: Vec::new(),
+            options: PaymentOptions::default(),
+        }
+    }
+
+    /// Most the sender may spend, including transfer fees and exchange
+    pub fn with_send_max(mut self, send_max: CurrencyAmount) -> Self {
+        self.send_max = Some(send_max);
+        self
+    }
+
+    /// Make this a partial payment: it succeeds as long as `deliver_min` (same
+    /// currency as `amount`) arrives, and delivers as much up to `amount` as SendMax buys
+    pub fn with_deliver_min(mut self, deliver_min: CurrencyAmount) -> Self {
+        self.deliver_min = Some(deliver_min);
+        self
+    }
+
+    pub fn with_paths(mut self, paths: Vec<Path>) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Destination tag, source tag, InvoiceID and memos
+    pub fn with_options(mut self, options: PaymentOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Use a path finding result: its paths, spending at most its source amount.
+    /// Rates can move before the payment applies; call `with_send_max` afterwards to allow some slippage.
+    pub fn with_alternative(self, alternative: &PathAlternative) -> Self {
+        self.with_paths(alternative.paths.clone())
+            .with_send_max(alternative.source_amount.clone())
+    }
+
+    pub fn amount(&self) -> &CurrencyAmount {
+        &self.amount
+    }
+
+    pub fn send_max(&self) -> Option<&CurrencyAmount> {
+        self.send_max.as_ref()
+    }
 
-    let result = sign_and_submit(&mut payment, client, &wallet, true, false)
-        .await
-        .map_err(|e| anyhow::anyhow!("Transaction error: {:?}", e))?;
+    pub fn deliver_min(&self) -> Option<&CurrencyAmount> {
+        self.deliver_min.as_ref()
+    }
 
-    let tx_hash = result
-        .tx_json
-        .get("hash")
-        .and_then(|h| h.as_str())
-        .unwrap_or("")
-        .to_string();
+    pub fn paths(&self) -> &[Path] {
+        &self.paths
+    }
+
+    pub fn options(&self) -> &PaymentOptions {
+        &self.options
+    }
+
+    // Reject what rippled would refuse with a tem code
+    fn validate(&self) -> Result<()> {
+        if !self.amount.is_positive() || self.send_max.as_ref().is_some_and(|send_max| !send_max.is_positive()) {
+            return Err(RippleError::InvalidInput("Payment amounts must be positive".to_string()));
+        }
+
+        let xrp_to_xrp = matches!(self.amount, CurrencyAmount::Xrp(_))
+            && self.send_max.as_ref().is_none_or(|send_max| matches!(send_max, CurrencyAmount::Xrp(_)));
+        if xrp_to_xrp && (self.send_max.is_some() || self.deliver_min.is_some() || !self.paths.is_empty()) {
+            return Err(RippleError::InvalidInput(
+                "XRP-to-XRP payments cannot use SendMax, DeliverMin or paths".to_string(),
+            ));
+        }
+
+        if let Some(deliver_min) = &self.deliver_min {
+            match deliver_min.partial_cmp(&self.amount) {
+                None => {
+                    return Err(RippleError::InvalidInput(format!(
+                        "DeliverMin must be in the currency of the amount ({}), got {}",
+                        self.amount.currency(),
+                        deliver_min.currency()
+                    )));
+                }
+                Some(std::cmp::Ordering::Greater) => {
+                    return Err(RippleError::InvalidInput("DeliverMin cannot exceed the amount".to_string()));
+                }
+                Some(_) if !deliver_min.is_positive() => {
+                    return Err(RippleError::InvalidInput("DeliverMin must be positive".to_string()));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Everything but Paths and the options, which are added when signing (see `sign_payment`)
+    fn to_payment(&self, account: &str) -> Payment<'static> {
+        let flags = self
+            .deliver_min
+            .is_some()
+            .then(|| FlagCollection::from(vec![PaymentFlag::TfPartialPayment]));
+
+        Payment::new(
+            Cow::Owned(account.to_string()),
+            None,
+            None,
+            flags,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Amount::from(&self.amount),
+            Cow::Owned(self.destination.clone()),
+            self.deliver_min.as_ref().map(Amount::from),
+            None,
+            None,
+            None,
+            self.send_max.as_ref().map(Amount::from),
+        )
+    }
+}
+
+/// Cheapest way to deliver `amount`, paying in `pay_with`: how much the sender would spend,
+/// and the paths to do it. Pass the result to `PaymentBuilder::with_alternative`.
+#[instrument(skip(client, amount, pay_with, timeout))]
+pub async fn quote_payment<C: RawRequest>(
+    client: &C,
+    source_account: &str,
+    destination: &str,
+    amount: &CurrencyAmount,
+    pay_with: &Currency<'static>,
+    timeout: Duration,
+) -> Result<PathAlternative> {
+    let request = PathRequest::new(source_account, destination, amount.clone()).with_source_currency(pay_with.clone());
+    let alternatives = ripple_path_find(client, &request, timeout).await?;
+
+    // Amounts from different issuers do not compare, so only alternatives spending exactly
+    // `pay_with` compete
+    let cheapest = alternatives
+        .into_iter()
+        .filter(|alternative| spends(&alternative.source_amount, pay_with))
+        .reduce(|best, next| if next.source_amount < best.source_amount { next } else { best })
+        .ok_or_else(|| {
+            RippleError::client(format!(
+                "No path from {} delivers {} {} to {}",
+                source_account,
+                amount.value_text(),
+                amount.currency(),
+                destination
+            ))
+        })?;
+
+    info!(
+        source_amount = %cheapest.source_amount.value_text(),
+        source_currency = cheapest.source_amount.currency(),
+        paths = cheapest.paths.len(),
+        "payment quoted"
+    );
+    Ok(cheapest)
+}
+
+fn spends(amount: &CurrencyAmount, currency: &Currency<'_>) -> bool {
+    match currency {
+        Currency::XRP(_) => matches!(amount, CurrencyAmount::Xrp(_)),
+        Currency::IssuedCurrency(issued) => {
+            amount.currency() == issued.currency.as_ref() && amount.issuer() == Some(issued.issuer.as_ref())
+        }
+    }
+}
 
-    println!("Issued token transaction submitted successfully!");
-    println!("  Transaction hash: {}", tx_hash);
-    println!("  Engine result: {}", result.engine_result);
+/// Submit the payment `payment` describes from the account of `sender_secret`
+#[instrument(skip(client, sender_secret, payment), fields(account))]
+pub async fn send_payment<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    payment: &PaymentBuilder,
+    timeout: Duration,
+) -> Result<String> {
+    let result = submit_payment(client, sender_secret, payment, timeout).await?;
+    let tx_hash = check_submit_result(&result)?;
+
+    info!(%tx_hash, "payment submitted");
     Ok(tx_hash)
 }
+
+/// `send_payment`, then wait until the payment is in a validated ledger
+#[instrument(skip(client, sender_secret, payment), fields(account))]
+pub async fn send_payment_and_wait<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    payment: &PaymentBuilder,
+    timeout: Duration,
+) -> Result<ValidatedTransaction> {
+    let result = submit_payment(client, sender_secret, payment, timeout).await?;
+    wait_for_submit_result(client, &result, timeout).await
+}
+
+async fn submit_payment<C: XRPLAsyncClient>(
+    client: &C,
+    sender_secret: &Secret,
+    payment: &PaymentBuilder,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    payment.validate()?;
+    let wallet = sender_secret.wallet()?;
+    Span::current().record("account", wallet.classic_address.as_str());
+
+    let transaction = payment.to_payment(&wallet.classic_address);
+
+    debug!(
+        send_max = ?payment.send_max.as_ref().map(CurrencyAmount::value_text),
+        partial = payment.deliver_min.is_some(),
+        paths = payment.paths.len(),
+        "submitting payment"
+    );
+    submit_signed_payment(client, &wallet, transaction, &payment.options, &payment.paths, timeout).await
+}
+
+// Every payment API ends here: fill in Sequence, Fee and LastLedgerSequence, then sign and submit
+async fn submit_signed_payment<C: XRPLAsyncClient>(
+    client: &C,
+    wallet: &Wallet,
+    mut payment: Payment<'static>,
+    options: &PaymentOptions,
+    paths: &[Path],
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    options.validate()?;
+    with_timeout("autofill", timeout, async {
+        autofill(&mut payment, client, None)
+            .await
+            .map_err(|e| RippleError::transaction("Failed to autofill payment", e))
+    })
+    .await?;
+    let blob = sign_payment(&payment, wallet, options, paths)?;
+    submit_blob(client, &blob, timeout).await
+}
+
+// Sign `payment` together with the fields its xrpl-rust model cannot carry. `Payment::new`
+// types InvoiceID as a u32 although it is a 256-bit hash, so the options are set on the JSON
+// form, which is what gets signed. Paths cannot go through JSON either: xrpl-rust's PathStep
+// writes "Account" where the codec reads "account", and the codec has no JSON form for
+// PathSet at all. They are encoded here instead; PathSet has the highest type code of any
+// Payment field, so it goes last, both in the data that is signed and in the blob.
+pub(crate) fn sign_payment(
+    payment: &Payment<'_>,
+    wallet: &Wallet,
+    options: &PaymentOptions,
+    paths: &[Path],
+) -> Result<String> {
+    let transaction =
+        serde_json::to_value(payment).map_err(|e| RippleError::transaction("Failed to encode payment", e))?;
+    let paths_field = if paths.is_empty() { Vec::new() } else { encode_paths(paths)? };
+    sign_with_options(transaction, wallet, options, &paths_field)
+}
+
+// Every transaction signed by this crate rather than by xrpl-rust goes through here: sign the
+// JSON form of a filled-in transaction with `options` set on it. `trailing_field` is already
+// encoded, and sorts after every field of the JSON form.
+pub(crate) fn sign_with_options(
+    mut transaction: Value,
+    wallet: &Wallet,
+    options: &PaymentOptions,
+    trailing_field: &[u8],
+) -> Result<String> {
+    options.apply(&mut transaction)?;
+    transaction["SigningPubKey"] = json!(wallet.public_key);
+
+    let encoded = encode_for_signing(&transaction)
+        .map_err(|e| RippleError::transaction("Failed to encode transaction for signing", e))?;
+    let mut signing_data = hex::decode(encoded)
+        .map_err(|e| RippleError::transaction("Failed to encode transaction for signing", e))?;
+    signing_data.extend_from_slice(trailing_field);
+    let signature = keypairs::sign(&signing_data, &wallet.private_key)
+        .map_err(|e| RippleError::transaction("Failed to sign transaction", e))?;
+    transaction["TxnSignature"] = json!(signature);
+
+    let blob = encode(&transaction).map_err(|e| RippleError::transaction("Failed to encode signed transaction", e))?;
+    Ok(blob + &hex::encode_upper(trailing_field))
+}
+
+// Sign an autofilled transaction model with `options` set on it and submit it
+pub(crate) async fn submit_signed_transaction<C: XRPLAsyncClient, T: Serialize>(
+    client: &C,
+    wallet: &Wallet,
+    transaction: &T,
+    options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<SubmitResult<'static>> {
+    let transaction =
+        serde_json::to_value(transaction).map_err(|e| RippleError::transaction("Failed to encode transaction", e))?;
+    let blob = sign_with_options(transaction, wallet, options, &[])?;
+    let result = submit_blob(client, &blob, timeout).await?;
+
+    debug!(engine_result = %result.engine_result, "submit response received");
+    Ok(result)
+}
+
+fn encode_paths(paths: &[Path]) -> Result<Vec<u8>> {
+    let mut field = PATHS_FIELD_HEADER.to_vec();
+    for (i, path) in paths.iter().enumerate() {
+        if i > 0 {
+            field.push(PATH_SEPARATOR);
+        }
+        for step in path {
+            let mut step_type = 0;
+            let mut values = Vec::new();
+            if let Some(account) = &step.account {
+                step_type |= PATH_STEP_ACCOUNT;
+                values.extend(account_id(account)?);
+            }
+            if let Some(currency) = &step.currency {
+                step_type |= PATH_STEP_CURRENCY;
+                values.extend(currency_bytes(currency)?);
+            }
+            if let Some(issuer) = &step.issuer {
+                step_type |= PATH_STEP_ISSUER;
+                values.extend(account_id(issuer)?);
+            }
+            if step_type == 0 {
+                return Err(RippleError::InvalidInput(
+                    "Path steps need an account, a currency or an issuer".to_string(),
+                ));
+            }
+            field.push(step_type);
+            field.extend(values);
+        }
+    }
+    field.push(PATH_SET_END);
+    Ok(field)
+}
+
+fn account_id(address: &str) -> Result<Vec<u8>> {
+    decode_classic_address(address)
+        .map_err(|_| RippleError::InvalidInput(format!("Invalid account {:?} in path", address)))
+}
+
+// XRP is the all-zero currency code in a path step
+fn currency_bytes(currency: &str) -> Result<Vec<u8>> {
+    let hex_code = if currency == "XRP" {
+        "0".repeat(40)
+    } else {
+        CurrencyCode::new(currency)?.to_hex()
+    };
+    hex::decode(hex_code).map_err(|e| RippleError::transaction("Failed to encode path currency", e))
+}
@@ -1,153 +1,303 @@
+use crate::amount::CurrencyAmount;
 use crate::client;
-use anyhow::Result;
+use crate::error::Result;
+use crate::payment_options::{Memo, PaymentOptions};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Duration;
+use tracing::{debug, info, instrument};
 use xrpl::{
-    asynch::clients::{AsyncWebSocketClient, WebSocketOpen},
-    models::results::tx::TxVersionMap,
+    asynch::clients::XRPLAsyncClient,
+    models::results::{
+        metadata::TransactionMetadata,
+        tx::{TxBase, TxVersionMap},
+    },
 };
 
-type XRPLClientType =
-    AsyncWebSocketClient<xrpl::asynch::clients::SingleExecutorMutex, WebSocketOpen>;
+// tfPartialPayment: the payment may deliver less than its Amount
+const PARTIAL_PAYMENT_FLAG: u64 = 0x0002_0000;
+// Before this ledger (and close time) rippled did not record DeliveredAmount for partial payments
+const FIRST_LEDGER_WITH_DELIVERED_AMOUNT: u32 = 4_594_095;
+const FIRST_CLOSE_TIME_WITH_DELIVERED_AMOUNT: u32 = 446_000_000;
 
-pub async fn verify_transfer(
-    client: &XRPLClientType,
+/// What a `Check` compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckKind {
+    TransactionType,
+    Sender,
+    Destination,
+    /// Amount delivered to the destination (drops for XRP, a decimal string for tokens)
+    Amount,
+    Currency,
+    Issuer,
+    Validated,
+    /// `TransactionResult` from the metadata
+    Result,
+    DestinationTag,
+    SourceTag,
+    InvoiceId,
+    /// Memos as JSON, in the plain text form of `Memo`
+    Memos,
+    /// CheckID of a CheckCash
+    CheckId,
+}
+
+/// One expectation compared against the ledger. `None` means the field is absent
+/// (for `expected`: the field must be absent, e.g. the issuer of an XRP payment).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Check {
+    pub kind: CheckKind,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub passed: bool,
+}
+
+impl Check {
+    fn new(kind: CheckKind, expected: Option<String>, actual: Option<String>) -> Self {
+        let passed = expected == actual;
+        Self { kind, expected, actual, passed }
+    }
+}
+
+/// Outcome of `verify_transfer` or `verify_check_cash`: every check with its expected and actual value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub tx_hash: String,
+    pub ledger_index: Option<u32>,
+    /// Close time of the ledger in seconds since the Ripple epoch (2000-01-01 UTC)
+    pub close_time: Option<u32>,
+    pub checks: Vec<Check>,
+}
+
+impl VerificationReport {
+    /// True when every check passed
+    pub fn is_verified(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(|check| check.passed)
+    }
+
+    pub fn check(&self, kind: CheckKind) -> Option<&Check> {
+        self.checks.iter().find(|check| check.kind == kind)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|check| !check.passed)
+    }
+}
+
+/// Compare `tx_hash` with a payment from `expected_from` to `expected_to` that delivered
+/// `expected` (XRP, or a token of the issuer named in it, who need not be the sender) and
+/// succeeded in a validated ledger.
+///
+/// The amount compared is what reached the destination, not the `Amount` field: a partial
+/// payment that delivered less than expected does not verify. Each field set in
+/// `expected_options` adds a check; unset fields are not compared. Mismatches are reported in
+/// the returned `VerificationReport`; an `Err` means the transaction could not be looked up.
+#[instrument(skip(client))]
+pub async fn verify_transfer<C: XRPLAsyncClient>(
+    client: &C,
     tx_hash: &str,
     expected_from: &str,
     expected_to: &str,
-    expected_amount: &str,
-    currency_code: Option<&str>,
-) -> Result<bool> {
-    println!("Verifying transfer...");
-    println!("  Transaction: {}", tx_hash);
-    println!("  Expected from: {}", expected_from);
-    println!("  Expected to: {}", expected_to);
-    println!("  Expected amount: {}", expected_amount);
-    if let Some(currency) = currency_code {
-        println!("  Expected currency: {}", currency);
-    } else {
-        println!("  Expected currency: XRP");
+    expected: &CurrencyAmount,
+    expected_options: &PaymentOptions,
+    timeout: Duration,
+) -> Result<VerificationReport> {
+    let tx_result = client::get_transaction(client, tx_hash, timeout).await?;
+    let (base, tx_json, meta) = transaction_parts(&tx_result);
+
+    let delivered = delivered(base, meta, tx_json);
+    if let Some(delivered) = &delivered
+        && let Some(amount) = tx_json.get("Amount").and_then(|amount| CurrencyAmount::from_json(amount).ok())
+        && *delivered != amount
+    {
+        let partial = is_partial_payment(tx_json);
+        info!(partial, amount = %amount.value_text(), delivered = %delivered.value_text(), "payment delivered less than its Amount");
     }
 
-    let tx_result = client::get_transaction(client, tx_hash).await?;
+    let text = |name: &str| tx_json.get(name).and_then(Value::as_str).map(str::to_string);
+    let mut checks = vec![
+        Check::new(CheckKind::TransactionType, Some("Payment".to_string()), text("TransactionType")),
+        Check::new(CheckKind::Sender, Some(expected_from.to_string()), text("Account")),
+        Check::new(CheckKind::Destination, Some(expected_to.to_string()), text("Destination")),
+    ];
+    checks.extend(amount_checks(expected, delivered.as_ref()));
+    checks.extend(outcome_checks(base, meta));
+    checks.extend(option_checks(expected_options, tx_json));
 
-    let tx_json = match &tx_result {
-        TxVersionMap::Default(tx) => &tx.tx_json,
-        TxVersionMap::V1(tx_v1) => &tx_v1.tx_json,
-    };
+    Ok(report(tx_hash, base, checks, "transfer"))
+}
 
-    if let Some(transaction_type) = tx_json.get("TransactionType") {
-        if transaction_type != "Payment" {
-            println!("Transaction is not a Payment transaction");
-            return Ok(false);
-        }
-    } else {
-        println!("Transaction type not found");
-        return Ok(false);
-    }
+/// Compare `tx_hash` with a CheckCash of check `check_id` by `expected_casher` (the check's
+/// destination) that delivered `expected` and succeeded in a validated ledger. As with
+/// `verify_transfer`, the amount compared is what was delivered, so a check cashed with
+/// DeliverMin verifies only if it paid exactly `expected`.
+#[instrument(skip(client))]
+pub async fn verify_check_cash<C: XRPLAsyncClient>(
+    client: &C,
+    tx_hash: &str,
+    check_id: &str,
+    expected_casher: &str,
+    expected: &CurrencyAmount,
+    timeout: Duration,
+) -> Result<VerificationReport> {
+    let tx_result = client::get_transaction(client, tx_hash, timeout).await?;
+    let (base, tx_json, meta) = transaction_parts(&tx_result);
 
-    println!("Verifying Payment transaction...");
+    let text = |name: &str| tx_json.get(name).and_then(Value::as_str).map(str::to_string);
+    let mut checks = vec![
+        Check::new(CheckKind::TransactionType, Some("CheckCash".to_string()), text("TransactionType")),
+        Check::new(
+            CheckKind::CheckId,
+            Some(check_id.to_uppercase()),
+            text("CheckID").map(|id| id.to_uppercase()),
+        ),
+        Check::new(CheckKind::Destination, Some(expected_casher.to_string()), text("Account")),
+    ];
+    checks.extend(amount_checks(expected, delivered(base, meta, tx_json).as_ref()));
+    checks.extend(outcome_checks(base, meta));
 
-    let actual_from = tx_json
-        .get("Account")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Account field not found"))?;
+    Ok(report(tx_hash, base, checks, "check cash"))
+}
 
-    if actual_from != expected_from {
-        println!(
-            "Sender mismatch: expected {}, got {}",
-            expected_from, actual_from
-        );
-        return Ok(false);
+fn transaction_parts<'a, 'b>(
+    tx_result: &'a TxVersionMap<'b>,
+) -> (&'a TxBase<'b>, &'a Value, Option<&'a TransactionMetadata<'b>>) {
+    match tx_result {
+        TxVersionMap::Default(tx) => (&tx.base, &tx.tx_json, tx.meta.as_ref()),
+        TxVersionMap::V1(tx_v1) => (&tx_v1.base, &tx_v1.tx_json, tx_v1.meta.as_ref()),
     }
-    println!("Sender verified: {}", actual_from);
-
-    let actual_to = tx_json
-        .get("Destination")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Destination field not found"))?;
-
-    if actual_to != expected_to {
-        println!(
-            "Destination mismatch: expected {}, got {}",
-            expected_to, actual_to
-        );
-        return Ok(false);
-    }
-    println!("Destination verified: {}", actual_to);
-
-    let amount_field = tx_json
-        .get("Amount")
-        .ok_or_else(|| anyhow::anyhow!("Amount field not found"))?;
+}
 
-    match amount_field {
-        Value::String(amount_str) => {
-            if currency_code.is_some() {
-                println!("Expected issued currency but got XRP");
-                return Ok(false);
+// xrpl-rust's TransactionMetadata keeps the recorded DeliveredAmount but not the synthetic
+// `delivered_amount` field of the response
+fn delivered(base: &TxBase, meta: Option<&TransactionMetadata>, tx_json: &Value) -> Option<CurrencyAmount> {
+    let recorded = meta.and_then(|meta| meta.delivered_amount.as_ref());
+    meta.and(delivered_amount(tx_json, None, recorded, base.ledger_index, base.date))
+        .and_then(|delivered| match CurrencyAmount::from_json(delivered) {
+            Ok(delivered) => Some(delivered),
+            Err(e) => {
+                info!(%delivered, error = %e, "unreadable delivered amount");
+                None
             }
-            if amount_str != expected_amount {
-                println!(
-                    "XRP amount mismatch: expected {}, got {}",
-                    expected_amount, amount_str
-                );
-                return Ok(false);
-            }
-            println!("XRP amount verified: {} drops", amount_str);
+        })
+}
+
+fn amount_checks(expected: &CurrencyAmount, delivered: Option<&CurrencyAmount>) -> [Check; 3] {
+    // Compared as numbers: "100", "100.0" and "1e2" are the same token amount
+    let amount_matches = match (expected, delivered) {
+        (CurrencyAmount::Xrp(expected), Some(CurrencyAmount::Xrp(actual))) => expected == actual,
+        (CurrencyAmount::Issued { value: expected, .. }, Some(CurrencyAmount::Issued { value: actual, .. })) => {
+            expected == actual
         }
-        Value::Object(amount_obj) => match currency_code {
-            Some(expected_currency) => {
-                let actual_currency = amount_obj
-                    .get("currency")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Currency field not found"))?;
-
-                let actual_amount = amount_obj
-                    .get("value")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Value field not found"))?;
-
-                let actual_issuer = amount_obj
-                    .get("issuer")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| anyhow::anyhow!("Issuer field not found"))?;
-
-                if actual_currency != expected_currency {
-                    println!(
-                        "Currency mismatch: expected {}, got {}",
-                        expected_currency, actual_currency
-                    );
-                    return Ok(false);
-                }
-                if actual_amount != expected_amount {
-                    println!(
-                        "Amount mismatch: expected {}, got {}",
-                        expected_amount, actual_amount
-                    );
-                    return Ok(false);
-                }
-                if actual_issuer != expected_from {
-                    println!(
-                        "Issuer mismatch: expected {}, got {}",
-                        expected_from, actual_issuer
-                    );
-                    return Ok(false);
-                }
-                println!(
-                    "Issued currency verified: {} {} (issuer: {})",
-                    actual_amount, actual_currency, actual_issuer
-                );
-            }
-            None => {
-                println!("Expected XRP but got issued currency");
-                return Ok(false);
-            }
+        _ => false,
+    };
+    [
+        Check {
+            kind: CheckKind::Amount,
+            expected: Some(expected.value_text()),
+            actual: delivered.map(CurrencyAmount::value_text),
+            passed: amount_matches,
         },
-        _ => {
-            println!("Invalid amount format");
-            return Ok(false);
-        }
+        Check::new(
+            CheckKind::Currency,
+            Some(expected.currency().to_string()),
+            delivered.map(|amount| amount.currency().to_string()),
+        ),
+        Check::new(
+            CheckKind::Issuer,
+            expected.issuer().map(str::to_string),
+            delivered.and_then(|amount| amount.issuer().map(str::to_string)),
+        ),
+    ]
+}
+
+fn outcome_checks(base: &TxBase, meta: Option<&TransactionMetadata>) -> [Check; 2] {
+    [
+        Check::new(
+            CheckKind::Validated,
+            Some(true.to_string()),
+            Some(base.validated.unwrap_or(false).to_string()),
+        ),
+        Check::new(
+            CheckKind::Result,
+            Some("tesSUCCESS".to_string()),
+            meta.map(|meta| meta.transaction_result.to_string()),
+        ),
+    ]
+}
+
+fn report(tx_hash: &str, base: &TxBase, checks: Vec<Check>, what: &str) -> VerificationReport {
+    let report = VerificationReport {
+        tx_hash: tx_hash.to_string(),
+        ledger_index: base.ledger_index,
+        close_time: base.date,
+        checks,
+    };
+    for check in report.failures() {
+        info!(check = ?check.kind, expected = ?check.expected, actual = ?check.actual, "check failed");
+    }
+    if report.is_verified() {
+        info!(ledger_index = ?report.ledger_index, "{} verified", what);
+    } else {
+        debug!(failed = report.failures().count(), "{} not verified", what);
     }
+    report
+}
+
+fn option_checks(expected: &PaymentOptions, tx_json: &Value) -> Vec<Check> {
+    let number = |name: &str| tx_json.get(name).and_then(Value::as_u64).map(|n| n.to_string());
+    let mut checks = Vec::new();
+    if let Some(destination_tag) = expected.destination_tag {
+        checks.push(Check::new(CheckKind::DestinationTag, Some(destination_tag.to_string()), number("DestinationTag")));
+    }
+    if let Some(source_tag) = expected.source_tag {
+        checks.push(Check::new(CheckKind::SourceTag, Some(source_tag.to_string()), number("SourceTag")));
+    }
+    if let Some(invoice_id) = &expected.invoice_id {
+        let actual = tx_json.get("InvoiceID").and_then(Value::as_str).map(str::to_uppercase);
+        checks.push(Check::new(CheckKind::InvoiceId, Some(invoice_id.to_uppercase()), actual));
+    }
+    if !expected.memos.is_empty() {
+        let actual = tx_json
+            .get("Memos")
+            .and_then(Value::as_array)
+            .and_then(|memos| memos.iter().map(Memo::from_json).collect::<Result<Vec<_>>>().ok());
+        checks.push(Check::new(CheckKind::Memos, Some(memos_text(&expected.memos)), actual.as_deref().map(memos_text)));
+    }
+    checks
+}
+
+fn memos_text(memos: &[Memo]) -> String {
+    serde_json::to_string(memos).unwrap_or_default()
+}
+
+// What reached the destination of a payment, following rippled's `delivered_amount`: the
+// synthetic `delivered_amount` of the response, else the DeliveredAmount recorded in the
+// metadata, else `Amount`. A partial payment without either field delivered an unknown amount,
+// and so did any payment in ledgers from before rippled recorded DeliveredAmount: both are None.
+pub(crate) fn delivered_amount<'a>(
+    tx_json: &'a Value,
+    synthetic: Option<&'a Value>,
+    recorded: Option<&'a Value>,
+    ledger_index: Option<u32>,
+    close_time: Option<u32>,
+) -> Option<&'a Value> {
+    let known = |amount: &&Value| !amount.is_null() && amount.as_str() != Some("unavailable");
+    if let Some(delivered) = synthetic.filter(known).or(recorded.filter(known)) {
+        return Some(delivered);
+    }
+    if is_partial_payment(tx_json) {
+        return None;
+    }
+    let recorded_then = ledger_index.is_some_and(|l| l >= FIRST_LEDGER_WITH_DELIVERED_AMOUNT)
+        || close_time.is_some_and(|d| d > FIRST_CLOSE_TIME_WITH_DELIVERED_AMOUNT);
+    if recorded_then { tx_json.get("Amount") } else { None }
+}
 
-    println!("Transfer verification successful!");
-    Ok(true)
+pub(crate) fn is_partial_payment(tx_json: &Value) -> bool {
+    tx_json
+        .get("Flags")
+        .and_then(Value::as_u64)
+        .is_some_and(|flags| flags & PARTIAL_PAYMENT_FLAG != 0)
 }
@@ -0,0 +1,375 @@
+use crate::amount::CurrencyAmount;
+use crate::error::{Result, RippleError};
+use crate::network::NetworkConfig;
+use crate::subscription::StreamConnection;
+use crate::verification::{delivered_amount, is_partial_payment};
+use serde_json::{Value, json};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, info, instrument, warn};
+
+// Transactions per account_tx page
+const ACCOUNT_TX_PAGE_SIZE: u32 = 200;
+// Upper bound on the ledger range fetched at once when catching up after downtime
+const MAX_LEDGERS_PER_SCAN: u32 = 1000;
+
+/// Validated payment into one of the watched accounts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncomingPayment {
+    pub tx_hash: String,
+    pub ledger_index: u32,
+    /// Position within the ledger (`TransactionIndex` in the metadata)
+    pub transaction_index: u32,
+    pub sender: String,
+    pub destination: String,
+    pub destination_tag: Option<u32>,
+    /// What actually arrived (`delivered_amount`), which is less than `Amount` for partial payments
+    pub delivered_amount: CurrencyAmount,
+}
+
+impl IncomingPayment {
+    pub fn is_xrp(&self) -> bool {
+        matches!(self.delivered_amount, CurrencyAmount::Xrp(_))
+    }
+
+    pub fn position(&self) -> CursorPosition {
+        CursorPosition {
+            ledger_index: self.ledger_index,
+            transaction_index: Some(self.transaction_index),
+        }
+    }
+}
+
+/// Point in the validated history up to which payments have been handed out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CursorPosition {
+    pub ledger_index: u32,
+    /// Last transaction handed out in `ledger_index`, or None once the whole ledger is done
+    pub transaction_index: Option<u32>,
+}
+
+impl CursorPosition {
+    /// Position after every transaction of `ledger_index`
+    pub fn ledger(ledger_index: u32) -> Self {
+        Self {
+            ledger_index,
+            transaction_index: None,
+        }
+    }
+
+    /// Whether the transaction at `transaction_index` of `ledger_index` is at or before this position
+    pub fn covers(&self, ledger_index: u32, transaction_index: u32) -> bool {
+        (ledger_index, transaction_index) <= self.key()
+    }
+
+    fn key(&self) -> (u32, u32) {
+        (self.ledger_index, self.transaction_index.unwrap_or(u32::MAX))
+    }
+}
+
+impl Ord for CursorPosition {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for CursorPosition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Where a PaymentWatcher records the position of the last payment that was handled
+pub trait LedgerCursor {
+    fn load(&mut self) -> Result<Option<CursorPosition>>;
+    fn save(&mut self, position: CursorPosition) -> Result<()>;
+}
+
+/// Cursor kept in memory only: a new watcher starts at the current validated ledger
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryCursor(pub Option<CursorPosition>);
+
+impl LedgerCursor for MemoryCursor {
+    fn load(&mut self) -> Result<Option<CursorPosition>> {
+        Ok(self.0)
+    }
+
+    fn save(&mut self, position: CursorPosition) -> Result<()> {
+        self.0 = Some(position);
+        Ok(())
+    }
+}
+
+/// Cursor persisted in a text file as `ledger_index` for a whole ledger, or
+/// `ledger_index:transaction_index` partway through one
+#[derive(Debug, Clone)]
+pub struct FileCursor {
+    path: PathBuf,
+}
+
+impl FileCursor {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl LedgerCursor for FileCursor {
+    fn load(&mut self) -> Result<Option<CursorPosition>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(RippleError::storage(format!("Failed to read {}", self.path.display()), e)),
+        };
+        let position = match text.trim().split_once(':') {
+            None => text.trim().parse().ok().map(CursorPosition::ledger),
+            Some((ledger_index, transaction_index)) => ledger_index
+                .parse()
+                .ok()
+                .zip(transaction_index.parse().ok())
+                .map(|(ledger_index, transaction_index)| CursorPosition {
+                    ledger_index,
+                    transaction_index: Some(transaction_index),
+                }),
+        };
+        // Ledger indexes start at 1
+        let position = position.filter(|position| position.ledger_index > 0);
+        position.map(Some).ok_or_else(|| RippleError::Storage {
+            message: format!("{} does not hold a ledger position", self.path.display()),
+            source: None,
+        })
+    }
+
+    // Written to a temporary file and renamed, so a crash never leaves a partial cursor
+    fn save(&mut self, position: CursorPosition) -> Result<()> {
+        let text = match position.transaction_index {
+            Some(transaction_index) => format!("{}:{}", position.ledger_index, transaction_index),
+            None => position.ledger_index.to_string(),
+        };
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, text)
+            .and_then(|_| fs::rename(&temporary, &self.path))
+            .map_err(|e| RippleError::storage(format!("Failed to write {}", self.path.display()), e))
+    }
+}
+
+/// Follows validated ledgers and reports every payment into the watched accounts, in ledger order.
+///
+/// The cursor records the position of each handled payment: call `ack` once a payment is credited,
+/// or call `next()` again, which acknowledges the payment it returned last. After a restart, only
+/// payments past the saved position are reported, so none is reported twice.
+pub struct PaymentWatcher<S> {
+    connection: StreamConnection,
+    accounts: Vec<String>,
+    cursor: S,
+    timeout: Duration,
+    // Ledgers up to `scanned` are queued or handed out, payments up to `handed_out` were returned
+    // by `next()`, and payments up to `saved` are persisted
+    scanned: u32,
+    handed_out: CursorPosition,
+    saved: CursorPosition,
+    validated: u32,
+    ready: VecDeque<IncomingPayment>,
+}
+
+impl<S: LedgerCursor> PaymentWatcher<S> {
+    /// Start after the position stored in `cursor`, or at the current validated ledger if it is empty
+    #[instrument(skip_all, fields(url = %network.url))]
+    pub async fn start(network: &NetworkConfig, accounts: &[&str], mut cursor: S, timeout: Duration) -> Result<Self> {
+        if accounts.is_empty() {
+            return Err(RippleError::InvalidInput(
+                "At least one account is required".to_string(),
+            ));
+        }
+
+        let mut connection = StreamConnection::open(network, timeout).await?;
+        let subscribed = connection
+            .request("subscribe", json!({ "streams": ["ledger"] }), timeout)
+            .await?;
+        let validated = ledger_index(&subscribed)
+            .ok_or_else(|| RippleError::client("subscribe response has no ledger_index"))?;
+        let saved = cursor.load()?.unwrap_or(CursorPosition::ledger(validated));
+        if saved.ledger_index == 0 {
+            return Err(RippleError::InvalidInput("Cursor position must be at ledger 1 or later".to_string()));
+        }
+        // A ledger that was handled only partway is scanned again; `scan` drops what was handled
+        let scanned = match saved.transaction_index {
+            Some(_) => saved.ledger_index - 1,
+            None => saved.ledger_index,
+        };
+
+        info!(from_ledger = scanned + 1, validated, accounts = accounts.len(), "payment watcher started");
+        Ok(Self {
+            connection,
+            accounts: accounts.iter().map(|account| account.to_string()).collect(),
+            cursor,
+            timeout,
+            scanned,
+            handed_out: saved,
+            saved,
+            validated,
+            ready: VecDeque::new(),
+        })
+    }
+
+    /// Next incoming payment, waiting for new ledgers when there is none. The payment returned
+    /// by the previous call is taken as handled.
+    pub async fn next(&mut self) -> Result<IncomingPayment> {
+        loop {
+            // Every payment handed out, and every scanned ledger once none is queued, has been handled
+            let handled = if self.ready.is_empty() {
+                CursorPosition::ledger(self.scanned)
+            } else {
+                self.handed_out
+            };
+            self.save(handled)?;
+            if let Some(payment) = self.ready.pop_front() {
+                self.handed_out = payment.position();
+                return Ok(payment);
+            }
+            if self.scanned < self.validated {
+                let to = self.validated.min(self.scanned + MAX_LEDGERS_PER_SCAN);
+                self.ready = self.scan(self.scanned + 1, to).await?.into();
+                self.scanned = to;
+                continue;
+            }
+
+            let message = self.connection.next_message().await.unwrap_or_else(|| {
+                Err(RippleError::Network {
+                    message: "Stream connection closed".to_string(),
+                    source: None,
+                })
+            })?;
+            if message["type"] == "ledgerClosed"
+                && let Some(ledger_index) = ledger_index(&message)
+            {
+                self.validated = self.validated.max(ledger_index);
+            }
+        }
+    }
+
+    /// Record `payment` as handled right away instead of on the next call to `next()`
+    pub fn ack(&mut self, payment: &IncomingPayment) -> Result<()> {
+        self.save(payment.position())
+    }
+
+    /// Last ledger whose payments have all been queued or handed out
+    pub fn ledger_index(&self) -> u32 {
+        self.scanned
+    }
+
+    pub fn cursor(&self) -> &S {
+        &self.cursor
+    }
+
+    fn save(&mut self, position: CursorPosition) -> Result<()> {
+        if position > self.saved {
+            self.cursor.save(position)?;
+            self.saved = position;
+            debug!(ledger_index = position.ledger_index, transaction_index = ?position.transaction_index, "cursor saved");
+        }
+        Ok(())
+    }
+
+    // Payments into the watched accounts in ledgers `from..=to` that were not handled yet, in the
+    // order they were applied
+    async fn scan(&mut self, from: u32, to: u32) -> Result<Vec<IncomingPayment>> {
+        let mut found = Vec::new();
+        for account in &self.accounts {
+            let mut marker: Option<Value> = None;
+            loop {
+                let mut params = json!({
+                    "account": account,
+                    "ledger_index_min": from,
+                    "ledger_index_max": to,
+                    "forward": true,
+                    "limit": ACCOUNT_TX_PAGE_SIZE,
+                });
+                if let Some(marker) = marker.take() {
+                    params["marker"] = marker;
+                }
+                let mut page = self.connection.request("account_tx", params, self.timeout).await?;
+                // rippled narrows the range to the ledgers it has instead of failing
+                let (min, max) = (page["ledger_index_min"].as_u64(), page["ledger_index_max"].as_u64());
+                if min.is_none_or(|min| min > u64::from(from)) || max.is_none_or(|max| max < u64::from(to)) {
+                    return Err(RippleError::client(format!(
+                        "account_tx covered ledgers {}..={} instead of {}..={}; the server is missing history",
+                        min.map_or("?".to_string(), |min| min.to_string()),
+                        max.map_or("?".to_string(), |max| max.to_string()),
+                        from,
+                        to
+                    )));
+                }
+
+                for entry in page["transactions"].as_array().into_iter().flatten() {
+                    if let Some(payment) = incoming_payment(entry, account)
+                        && !self.saved.covers(payment.ledger_index, payment.transaction_index)
+                    {
+                        found.push(payment);
+                    }
+                }
+                match page.get_mut("marker").map(Value::take) {
+                    Some(next) if !next.is_null() => marker = Some(next),
+                    _ => break,
+                }
+            }
+        }
+
+        found.sort_by_key(IncomingPayment::position);
+        found.dedup_by(|a, b| a.tx_hash == b.tx_hash);
+        debug!(from, to, payments = found.len(), "ledgers scanned");
+        Ok(found)
+    }
+}
+
+fn ledger_index(message: &Value) -> Option<u32> {
+    message["ledger_index"].as_u64().and_then(|l| u32::try_from(l).ok())
+}
+
+// account_tx entry (API v1 `tx` or v2 `tx_json`) to a payment into `account`, if it is one
+fn incoming_payment(entry: &Value, account: &str) -> Option<IncomingPayment> {
+    let tx = entry.get("tx_json").or_else(|| entry.get("tx"))?;
+    let meta = &entry["meta"];
+    if tx["TransactionType"] != "Payment"
+        || tx["Destination"] != account
+        || meta["TransactionResult"] != "tesSUCCESS"
+        || entry["validated"] == false
+    {
+        return None;
+    }
+
+    let tx_hash = entry["hash"].as_str().or_else(|| tx["hash"].as_str())?.to_string();
+    let ledger_index = ledger_index(entry).or_else(|| ledger_index(tx))?;
+    let transaction_index = meta["TransactionIndex"].as_u64().and_then(|index| u32::try_from(index).ok()).unwrap_or(0);
+    let delivered = delivered_amount(
+        tx,
+        meta.get("delivered_amount"),
+        meta.get("DeliveredAmount"),
+        Some(ledger_index),
+        tx["date"].as_u64().and_then(|date| u32::try_from(date).ok()),
+    );
+    let Some(delivered) = delivered else {
+        warn!(%tx_hash, partial = is_partial_payment(tx), "payment without a known delivered amount skipped");
+        return None;
+    };
+    let delivered_amount = match CurrencyAmount::from_json(delivered) {
+        Ok(amount) => amount,
+        Err(e) => {
+            warn!(%tx_hash, error = %e, "payment without a readable amount");
+            return None;
+        }
+    };
+
+    Some(IncomingPayment {
+        tx_hash,
+        ledger_index,
+        transaction_index,
+        sender: tx["Account"].as_str()?.to_string(),
+        destination: account.to_string(),
+        destination_tag: tx["DestinationTag"].as_u64().and_then(|tag| u32::try_from(tag).ok()),
+        delivered_amount,
+    })
+}
@@ -0,0 +1,114 @@
+use ripple_task::{CurrencyAmount, Drops, IssuedValue, RippleError};
+use serde_json::json;
+
+fn value(text: &str) -> IssuedValue {
+    text.parse().unwrap()
+}
+
+#[test]
+fn issued_values_compare_by_number() {
+    assert_eq!(value("100"), value("100.0"));
+    assert_eq!(value("100"), value("1e2"));
+    assert_eq!(value("0.001"), value("1E-3"));
+    assert_eq!(value("-0"), IssuedValue::ZERO);
+    assert!(value("99.99") < value("100"));
+    assert!(value("-5") < value("-0.5"));
+    assert!(value("-0.5") < IssuedValue::ZERO);
+    assert!(value("1e-80") > IssuedValue::ZERO);
+}
+
+#[test]
+fn issued_values_format_like_rippled() {
+    assert_eq!(value("100.0").to_string(), "100");
+    assert_eq!(value("1e2").to_string(), "100");
+    assert_eq!(value("-0.25").to_string(), "-0.25");
+    assert_eq!(value("1234567890.123456").to_string(), "1234567890.123456");
+    assert_eq!(value("1e20").to_string(), "1000000000000000e5");
+    assert_eq!(value("1e-30").to_string(), "1000000000000000e-45");
+    assert_eq!(value("0").to_string(), "0");
+}
+
+#[test]
+fn issued_values_reject_what_the_ledger_cannot_hold() {
+    for invalid in ["", "abc", "1.", ".5", "01", "1e", "1.2.3", "1e+-2", "--1"] {
+        let err = invalid.parse::<IssuedValue>().unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{invalid:?}: {err:?}");
+    }
+    // 17 significant digits would be rounded by the ledger
+    assert!("1.2345678901234567".parse::<IssuedValue>().is_err());
+    assert!("1.234567890123456".parse::<IssuedValue>().is_ok());
+    assert!("1e97".parse::<IssuedValue>().is_err());
+    assert!("1e-97".parse::<IssuedValue>().is_err());
+}
+
+#[test]
+fn drops_arithmetic_is_exact() {
+    let fee: Drops = "12".parse().unwrap();
+    let amount = Drops::from_xrp("1.5").unwrap();
+    assert_eq!(amount.as_u64(), 1_500_000);
+    assert_eq!(amount.checked_add(fee).unwrap().to_string(), "1500012");
+    assert_eq!(amount.checked_sub(fee).unwrap().to_xrp(), "1.499988");
+    assert_eq!(fee.checked_sub(amount), None);
+
+    let supply = Drops::from_xrp("100000000000").unwrap();
+    assert_eq!(supply.checked_add(Drops::new(1).unwrap()), None);
+    assert!(Drops::new(supply.as_u64() + 1).is_err());
+    assert!(Drops::from_xrp("0.0000001").is_err());
+    assert!("1.5".parse::<Drops>().is_err());
+    assert!("-1".parse::<Drops>().is_err());
+}
+
+#[test]
+fn currency_amounts_round_trip_through_json() {
+    let issued = CurrencyAmount::from_json(&json!({ "currency": "USD", "issuer": "rIssuer", "value": "1.50" })).unwrap();
+    assert_eq!(issued.currency(), "USD");
+    assert_eq!(issued.issuer(), Some("rIssuer"));
+    assert_eq!(issued.to_json(), json!({ "currency": "USD", "issuer": "rIssuer", "value": "1.5" }));
+
+    let xrp = CurrencyAmount::from_json(&json!("1000")).unwrap();
+    assert_eq!(xrp, CurrencyAmount::Xrp(Drops::new(1000).unwrap()));
+    assert_eq!(xrp.currency(), "XRP");
+    assert_eq!(xrp.issuer(), None);
+
+    assert_eq!(serde_json::to_value(value("1e2")).unwrap(), json!("100"));
+    assert_eq!(serde_json::from_value::<Drops>(json!("25")).unwrap().as_u64(), 25);
+}
+
+#[test]
+fn issued_values_add_and_subtract_to_sixteen_digits() {
+    let sum = |a: &str, b: &str| value(a).checked_add(&value(b)).unwrap().to_string();
+    assert_eq!(sum("100", "0.25"), "100.25");
+    assert_eq!(sum("1", "-1"), "0");
+    assert_eq!(sum("-5", "2"), "-3");
+    // Digits beyond the 16th are dropped, as rippled does
+    assert_eq!(sum("1000000000000000", "0.1"), "1000000000000000");
+    assert_eq!(value("0.3").checked_sub(&value("0.1")).unwrap(), value("0.2"));
+    assert_eq!(value("9e95").checked_add(&value("9e95")), None);
+
+    let usd = |value: &str| CurrencyAmount::from_json(&json!({ "currency": "USD", "issuer": "rIssuer", "value": value })).unwrap();
+    assert_eq!(usd("10").checked_sub(&usd("2.5")), Some(usd("7.5")));
+    assert_eq!(usd("10").checked_add(&CurrencyAmount::drops(1).unwrap()), None);
+    assert!(usd("10").zero().is_zero());
+    assert!(usd("0.1").is_positive() && !usd("0").is_positive() && !usd("-1").is_positive());
+    assert!(!CurrencyAmount::drops(0).unwrap().is_positive());
+    assert_eq!(CurrencyAmount::drops(1).unwrap().checked_sub(&CurrencyAmount::drops(2).unwrap()), None);
+}
+
+#[test]
+fn issued_values_multiply_and_divide_to_sixteen_digits() {
+    assert_eq!(value("1.5").checked_mul(&value("-4")).unwrap(), value("-6"));
+    assert_eq!(value("1").checked_div(&value("3")).unwrap().to_string(), "0.3333333333333333");
+    assert_eq!(value("12000000").checked_div(&value("0.5")).unwrap(), value("24000000"));
+    assert_eq!(value("1").checked_div(&IssuedValue::ZERO), None);
+    assert_eq!(value("9e80").checked_mul(&value("9e80")), None);
+}
+
+#[test]
+fn drops_convert_to_issued_values_only_when_exact() {
+    assert_eq!(IssuedValue::try_from(Drops::new(2_500).unwrap()).unwrap(), value("2500"));
+    assert_eq!(IssuedValue::try_from(Drops::new(99_000_000_000_000_000).unwrap()).unwrap(), value("99e15"));
+    assert_eq!(IssuedValue::try_from(Drops::new(9_999_999_999_999_999).unwrap()).unwrap(), value("9999999999999999"));
+
+    let err = IssuedValue::try_from(Drops::new(12_345_678_901_234_567).unwrap()).unwrap_err();
+    assert!(matches!(err, RippleError::InvalidInput(_)), "{err:?}");
+}
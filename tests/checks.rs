@@ -0,0 +1,188 @@
+mod common;
+
+use common::*;
+use ripple_task::checks::check_id;
+use ripple_task::{CheckBuilder, CheckCashAmount, CheckKind, PaymentOptions, RippleError, from_ripple_time};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const INVOICE_ID: &str = "6F1DFD1D0FE8A32E40E1F2C05CF1C15545BAB56B617F9C6C2D63A6B704BEF59B";
+const CHECK_CASH_HASH: &str = "8C3E1A5F7B9D2F4A6C8E0B2D4F6A8C1E3B5D7F9A0C2E4B6D8F1A3C5E7B9D0F2A";
+// 2026-05-01T00:00:00Z
+const EXPIRATION: u32 = 830_822_400;
+
+fn check_object(owner: &str, destination: &str, sequence: u32, expiration: u32) -> Value {
+    json!({
+        "LedgerEntryType": "Check",
+        "Account": owner,
+        "Destination": destination,
+        "SendMax": { "currency": "TST", "issuer": ISSUER_ADDRESS, "value": "10" },
+        "Sequence": sequence,
+        "Expiration": expiration,
+        "Flags": 0,
+        "index": check_id(owner, sequence).unwrap(),
+    })
+}
+
+#[test]
+fn check_ids_match_rippled() {
+    assert_eq!(
+        check_id("rUn84CUYbNjRoTQ6mSW7BVJPSVJNLb1QLo", 2).unwrap(),
+        "49647F0D748DC3FE26BDACBC57F251AADEFFF391403EC9BF87C97F67E9977FB0"
+    );
+    assert!(check_id("not an address", 2).is_err());
+}
+
+#[tokio::test]
+async fn create_check_signs_send_max_expiration_and_options() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let send_max = tst_amount(ISSUER_ADDRESS, "10");
+    let check = CheckBuilder::new(RECEIVER_ADDRESS, send_max.clone())
+        .expiration(from_ripple_time(EXPIRATION))
+        .with_options(PaymentOptions::default().with_destination_tag(7).with_invoice_id(INVOICE_ID));
+
+    let placed = xrpl.create_check(&sender_secret(), &check).await.unwrap();
+
+    let blob = &mock.submitted_blobs()[0];
+    assert_eq!(placed.tx_hash, transaction_hash(blob));
+    assert_eq!(placed.sequence, ACCOUNT_SEQUENCE);
+    assert_eq!(placed.check_id, check_id(SENDER_ADDRESS, ACCOUNT_SEQUENCE).unwrap());
+    assert!(blob.contains(&send_max_field(&send_max)), "SendMax: {blob}");
+    assert!(blob.contains(&format!("2A{:08X}", EXPIRATION)), "Expiration: {blob}");
+    assert!(blob.contains("2E00000007"), "DestinationTag: {blob}");
+    assert!(blob.contains(&format!("5011{}", INVOICE_ID)), "InvoiceID: {blob}");
+
+    let invalid = [
+        CheckBuilder::new(RECEIVER_ADDRESS, drops(0)),
+        CheckBuilder::new(RECEIVER_ADDRESS, drops(1_000)).with_options(PaymentOptions::default().with_invoice_id("AB")),
+    ];
+    for check in invalid {
+        let err = xrpl.create_check(&sender_secret(), &check).await.unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{check:?}: {err:?}");
+    }
+    assert_eq!(mock.submitted_blobs().len(), 1);
+}
+
+#[tokio::test]
+async fn cash_for_an_exact_amount_or_deliver_min_and_cancel() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let id = check_id(ISSUER_ADDRESS, 5).unwrap();
+    let amount = tst_amount(ISSUER_ADDRESS, "7.5");
+
+    xrpl.cash_check(&sender_secret(), &id, &CheckCashAmount::Exact(amount.clone())).await.unwrap();
+    let validated = xrpl
+        .cash_check_and_wait(&sender_secret(), &id.to_lowercase(), &CheckCashAmount::DeliverMin(drops(2_000)))
+        .await
+        .unwrap();
+    assert!(validated.is_success());
+    let tx_hash = xrpl.cancel_check(&sender_secret(), &id).await.unwrap();
+
+    let blobs = mock.submitted_blobs();
+    for blob in &blobs {
+        assert!(blob.contains(&format!("5018{}", id)), "CheckID: {blob}");
+    }
+    assert!(blobs[0].contains(&amount_value_field(&amount)), "Amount: {}", blobs[0]);
+    assert!(!blobs[0].contains(&deliver_min_field(&amount)), "{}", blobs[0]);
+    assert!(blobs[1].contains(&deliver_min_field(&drops(2_000))), "DeliverMin: {}", blobs[1]);
+    assert!(!blobs[1].contains(&amount_value_field(&drops(2_000))), "{}", blobs[1]);
+    assert_eq!(tx_hash, transaction_hash(&blobs[2]));
+
+    let invalid = [
+        xrpl.cash_check(&sender_secret(), &id, &CheckCashAmount::Exact(drops(0))).await,
+        xrpl.cash_check(&sender_secret(), "ABCD", &CheckCashAmount::Exact(amount.clone())).await,
+        xrpl.cancel_check(&sender_secret(), "ABCD").await,
+    ];
+    for result in invalid {
+        assert!(matches!(result, Err(RippleError::InvalidInput(_))), "{result:?}");
+    }
+    assert_eq!(mock.submitted_blobs().len(), 3);
+}
+
+#[tokio::test]
+async fn list_cashable_checks_skips_outgoing_and_expired_checks() {
+    let mock = MockRippled::start().await;
+    mock.set_result(
+        "account_objects",
+        json!({
+            "account": RECEIVER_ADDRESS,
+            "account_objects": [
+                check_object(SENDER_ADDRESS, RECEIVER_ADDRESS, 3, u32::MAX),
+                check_object(SENDER_ADDRESS, RECEIVER_ADDRESS, 4, EXPIRATION - 86_400 * 365),
+                check_object(RECEIVER_ADDRESS, SENDER_ADDRESS, 9, u32::MAX),
+            ],
+            "ledger_index": VALIDATED_LEDGER_INDEX,
+            "validated": true,
+        }),
+    );
+    let xrpl = mock.connect().await;
+
+    let checks = xrpl.list_cashable_checks(RECEIVER_ADDRESS).await.unwrap();
+
+    assert_eq!(mock.requests("account_objects")[0]["type"], "check");
+    assert_eq!(checks.len(), 1);
+    let check = &checks[0];
+    assert_eq!((check.owner.as_str(), check.sequence), (SENDER_ADDRESS, 3));
+    assert_eq!(check.check_id, check_id(SENDER_ADDRESS, 3).unwrap());
+    assert_eq!(check.send_max, tst_amount(ISSUER_ADDRESS, "10"));
+    let expiration = from_ripple_time(EXPIRATION);
+    let expiring = check_object(SENDER_ADDRESS, RECEIVER_ADDRESS, 4, EXPIRATION);
+    let expiring: ripple_task::CheckEntry = serde_json::from_value(expiring).unwrap();
+    assert!(!expiring.is_expired_at(expiration - Duration::from_secs(1)));
+    assert!(expiring.is_expired_at(expiration));
+}
+
+#[tokio::test]
+async fn verify_check_cash_compares_the_delivered_amount() {
+    let mock = MockRippled::start().await;
+    let id = check_id(SENDER_ADDRESS, 3).unwrap();
+    let delivered = json!({ "currency": "TST", "issuer": ISSUER_ADDRESS, "value": "7.5" });
+    let check_cash = json!({
+        "TransactionType": "CheckCash",
+        "Account": RECEIVER_ADDRESS,
+        "CheckID": id,
+        "DeliverMin": { "currency": "TST", "issuer": ISSUER_ADDRESS, "value": "5" },
+        "Fee": "12",
+        "Sequence": 8,
+    });
+    // Like partial payments, CheckCash records DeliveredAmount in its metadata
+    let mut meta = success_meta(delivered.clone());
+    meta["DeliveredAmount"] = delivered;
+    mock.add_transaction(CHECK_CASH_HASH, check_cash.clone(), meta, true);
+    let xrpl = mock.connect().await;
+
+    let report = xrpl
+        .verify_check_cash(CHECK_CASH_HASH, &id.to_lowercase(), RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "7.50"))
+        .await
+        .unwrap();
+    assert!(report.is_verified(), "{report:?}");
+
+    let report = xrpl
+        .verify_check_cash(CHECK_CASH_HASH, &id, RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "10"))
+        .await
+        .unwrap();
+    let failed: Vec<_> = report.failures().map(|check| check.kind).collect();
+    assert_eq!(failed, [CheckKind::Amount]);
+
+    let other_check = check_id(SENDER_ADDRESS, 4).unwrap();
+    let report = xrpl
+        .verify_check_cash(CHECK_CASH_HASH, &other_check, SENDER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "7.5"))
+        .await
+        .unwrap();
+    let failed: Vec<_> = report.failures().map(|check| check.kind).collect();
+    assert_eq!(failed, [CheckKind::CheckId, CheckKind::Destination]);
+
+    // A DeliverMin cash without a recorded amount delivered an unknown amount, not DeliverMin
+    let unknown = "9D4F2B6A8C0E1F3A5C7E9B2D4F6A8C0E1B3D5F7A9C2E4B6D8F0A1C3E5B7D9F2A";
+    let mut meta = success_meta(json!(null));
+    meta.as_object_mut().unwrap().remove("delivered_amount");
+    mock.add_transaction(unknown, check_cash, meta, true);
+    let report = xrpl
+        .verify_check_cash(unknown, &id, RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "5"))
+        .await
+        .unwrap();
+    assert_eq!(report.check(CheckKind::Amount).unwrap().actual, None);
+}
@@ -0,0 +1,824 @@
+// In-process mock of the rippled WebSocket and JSON-RPC APIs used by the integration tests.
+//
+// The server answers account_info, tx, submit, ledger, fee, server_info and
+// server_state from scripted fixtures, so every XRPLManager operation can run
+// end-to-end without touching a live network. Both transports share one state.
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use ripple_task::{CurrencyAmount, CurrencyCode, NetworkConfig, RawRequest, RippleError, Secret, XRPLClient, XRPLManager};
+use url::Url;
+use xrpl::asynch::clients::exceptions::XRPLClientResult;
+use xrpl::core::binarycodec::types::Amount as BinaryAmount;
+use xrpl::models::{requests::XRPLRequest, results::XRPLResponse};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha512};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// Genesis account of a fresh rippled, handy as a deterministic signer
+pub const SENDER_SEED: &str = "snoPBrXtMeMyMHUVTgbuqAfg1SUTb";
+pub const SENDER_ADDRESS: &str = "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh";
+
+pub fn sender_secret() -> Secret {
+    Secret::from(SENDER_SEED)
+}
+
+/// Third party that issues tokens neither the sender nor the receiver controls
+pub const ISSUER_ADDRESS: &str = "rf1BiGeXwwQoi8Z2ueFYTEXSwuJYfV2Jpn";
+
+pub const RECEIVER_ADDRESS: &str = "rPT1Sjq2YGrBMTttX4GZHjKu9dyfzbpAYe";
+
+pub const VALIDATED_LEDGER_INDEX: u32 = 1000;
+pub const ACCOUNT_SEQUENCE: u32 = 42;
+
+// Prefix rippled hashes signed transactions with ("TXN\0")
+const TRANSACTION_ID_PREFIX: [u8; 4] = [0x54, 0x58, 0x4E, 0x00];
+
+#[derive(Debug, Clone)]
+pub enum Reply {
+    Result(Value),
+    Error(String),
+    /// Never answer, like a node that has stopped responding
+    Silent,
+}
+
+#[derive(Default)]
+struct MockState {
+    // Scripted replies per command; the last reply of a queue keeps being served
+    fixtures: HashMap<String, VecDeque<Reply>>,
+    // `tx` results keyed by transaction hash
+    transactions: HashMap<String, Value>,
+    // Engine result returned for the next submissions
+    engine_results: VecDeque<String>,
+    // AffectedNodes recorded in the metadata of the next validated submissions
+    affected_nodes: VecDeque<Value>,
+    // `tx` lookups of a submitted transaction answered with txnNotFound before it validates
+    pending_lookups: u32,
+    // account_tx history (API v1 entries) and how many entries one page holds
+    history: Vec<Value>,
+    history_page_size: usize,
+    // Oldest ledger the node has; account_tx clamps ledger_index_min to it
+    first_ledger: Option<u64>,
+    // Commands the mock receives but never answers
+    stalled: HashSet<String>,
+    submitted: Vec<String>,
+    requests: Vec<Value>,
+}
+
+pub struct MockRippled {
+    url: String,
+    http_url: String,
+    state: Arc<Mutex<MockState>>,
+    shutdown: watch::Sender<bool>,
+    // Stream messages for WebSocket connections that have subscribed
+    published: broadcast::Sender<Value>,
+}
+
+impl MockRippled {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock rippled");
+        let http_listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock rippled JSON-RPC");
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let http_url = format!("http://{}", http_listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let (shutdown, _) = watch::channel(false);
+        let (published, _) = broadcast::channel(64);
+
+        let mock = Self {
+            url,
+            http_url,
+            state,
+            shutdown,
+            published,
+        };
+        mock.set_result("ledger", ledger_fixture(VALIDATED_LEDGER_INDEX));
+        mock.set_result("fee", fee_fixture());
+        mock.set_result("server_state", server_state_fixture());
+        mock.set_result("server_info", server_info_fixture());
+        // Subscribing to the ledger stream reports the latest validated ledger
+        mock.set_result("subscribe", json!({ "ledger_index": VALIDATED_LEDGER_INDEX }));
+        mock.state.lock().unwrap().history_page_size = 200;
+
+        let state = mock.state.clone();
+        let mut stopped = mock.shutdown.subscribe();
+        let published = mock.published.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Ok((stream, _)) = listener.accept() => {
+                        let stream_messages = published.subscribe();
+                        tokio::spawn(serve_connection(stream, state.clone(), stopped.clone(), stream_messages));
+                    }
+                    _ = stopped.changed() => return,
+                }
+            }
+        });
+        let state = mock.state.clone();
+        let mut stopped = mock.shutdown.subscribe();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Ok((stream, _)) = http_listener.accept() => {
+                        tokio::spawn(serve_http_connection(stream, state.clone(), stopped.clone()));
+                    }
+                    _ = stopped.changed() => return,
+                }
+            }
+        });
+
+        mock
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Send a stream message (ledgerClosed, transaction, ...) to every subscribed connection
+    pub fn publish(&self, message: Value) {
+        let _ = self.published.send(message);
+    }
+
+    /// Simulate a node going down: stop listening and close every open connection
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Manager over an in-process client sharing this mock's fixtures, with no socket at all
+    pub fn in_memory(&self) -> XRPLManager<InMemoryClient> {
+        let client = InMemoryClient {
+            state: self.state.clone(),
+        };
+        XRPLManager::with_client(client, self.network())
+    }
+
+    pub fn network(&self) -> NetworkConfig {
+        NetworkConfig::custom(&self.url, None).unwrap()
+    }
+
+    /// Same mock reached through its JSON-RPC (HTTP) endpoint
+    pub fn http_network(&self) -> NetworkConfig {
+        NetworkConfig::custom(&self.http_url, None).unwrap()
+    }
+
+    pub async fn connect_http(&self) -> XRPLManager {
+        XRPLManager::connect(self.http_network())
+            .await
+            .expect("connect to mock rippled over HTTP")
+    }
+
+    pub async fn connect(&self) -> XRPLManager {
+        XRPLManager::connect(self.network())
+            .await
+            .expect("connect to mock rippled")
+    }
+
+    /// Replace every scripted reply for `command` with a single result
+    pub fn set_result(&self, command: &str, result: Value) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .fixtures
+            .insert(command.to_string(), VecDeque::from([Reply::Result(result)]));
+    }
+
+    /// Queue a reply for `command` after the ones already scripted
+    pub fn push_reply(&self, command: &str, reply: Reply) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .fixtures
+            .entry(command.to_string())
+            .or_default()
+            .push_back(reply);
+    }
+
+    pub fn set_error(&self, command: &str, error: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.fixtures.insert(
+            command.to_string(),
+            VecDeque::from([Reply::Error(error.to_string())]),
+        );
+    }
+
+    /// Stop answering `command` (requests are still recorded)
+    pub fn stall(&self, command: &str) {
+        self.state.lock().unwrap().stalled.insert(command.to_string());
+    }
+
+    pub fn set_account(&self, account: &str, sequence: u32) {
+        self.set_result("account_info", account_info_fixture(account, sequence));
+    }
+
+    /// Engine result for the next submit (defaults to tesSUCCESS)
+    pub fn push_engine_result(&self, engine_result: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.engine_results.push_back(engine_result.to_string());
+    }
+
+    /// AffectedNodes for the metadata of the next submitted transaction (defaults to none)
+    pub fn push_affected_nodes(&self, affected_nodes: Value) {
+        let mut state = self.state.lock().unwrap();
+        state.affected_nodes.push_back(affected_nodes);
+    }
+
+    /// Register a transaction returned by `tx` lookups for `hash`.
+    /// Results use the API v1 layout rippled serves by default: the
+    /// transaction fields sit at the top level next to `meta`.
+    pub fn add_transaction(&self, hash: &str, tx_json: Value, meta: Value, validated: bool) {
+        let mut result = tx_json;
+        let fields = result.as_object_mut().expect("tx_json is an object");
+        fields.insert("hash".to_string(), json!(hash));
+        fields.insert("ledger_index".to_string(), json!(VALIDATED_LEDGER_INDEX));
+        fields.insert("date".to_string(), json!(800000000));
+        fields.insert("validated".to_string(), json!(validated));
+        fields.insert("meta".to_string(), meta);
+        let mut state = self.state.lock().unwrap();
+        state.transactions.insert(hash.to_string(), result);
+    }
+
+    /// Add a validated transaction to the account_tx history of its Account and Destination
+    pub fn add_history(&self, hash: &str, ledger_index: u32, tx_json: Value, meta: Value) {
+        let mut tx = tx_json;
+        tx["hash"] = json!(hash);
+        tx["ledger_index"] = json!(ledger_index);
+        let mut state = self.state.lock().unwrap();
+        state.history.push(json!({ "meta": meta, "tx": tx, "validated": true }));
+    }
+
+    /// Split account_tx results into pages of `entries` (continued with a marker)
+    pub fn set_history_page_size(&self, entries: usize) {
+        self.state.lock().unwrap().history_page_size = entries;
+    }
+
+    /// Serve account_tx only from `ledger_index` on, like a node with partial history
+    pub fn set_first_ledger(&self, ledger_index: u32) {
+        self.state.lock().unwrap().first_ledger = Some(u64::from(ledger_index));
+    }
+
+    /// Keep submitted transactions out of validated ledgers for the next
+    /// `lookups` tx requests (u32::MAX: never validate them)
+    pub fn delay_validation(&self, lookups: u32) {
+        self.state.lock().unwrap().pending_lookups = lookups;
+    }
+
+    pub fn submitted_blobs(&self) -> Vec<String> {
+        self.state.lock().unwrap().submitted.clone()
+    }
+
+    /// Every request received for `command`, in arrival order
+    pub fn requests(&self, command: &str) -> Vec<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .requests
+            .iter()
+            .filter(|request| request["command"] == command)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Test double implementing XRPLClient directly on top of the scripted state
+pub struct InMemoryClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl XRPLClient for InMemoryClient {
+    async fn request_impl<'a: 'b, 'b>(
+        &self,
+        mut request: XRPLRequest<'a>,
+    ) -> XRPLClientResult<XRPLResponse<'b>> {
+        self.set_request_id(&mut request);
+        let request = serde_json::to_value(&request).expect("requests serialize to JSON");
+        let Some(response) = response_for(&self.state, &request) else {
+            return std::future::pending().await;
+        };
+        Ok(serde_json::from_value(response).expect("mock responses are valid"))
+    }
+
+    fn get_host(&self) -> Url {
+        Url::parse("memory://mock-rippled").unwrap()
+    }
+}
+
+impl RawRequest for InMemoryClient {
+    async fn request_json(&self, command: &str, mut params: Value, timeout: Duration) -> ripple_task::Result<Value> {
+        params["command"] = json!(command);
+        let Some(mut response) = response_for(&self.state, &params) else {
+            tokio::time::sleep(timeout).await;
+            return Err(RippleError::Timeout {
+                command: command.to_string(),
+                timeout,
+            });
+        };
+        if response["status"] != "success" {
+            return Err(RippleError::XRPLClient {
+                message: format!("{} failed: {}", command, response["error"]),
+                source: None,
+            });
+        }
+        Ok(response["result"].take())
+    }
+}
+
+/// Hash rippled assigns to a signed transaction blob
+pub fn transaction_hash(blob: &str) -> String {
+    let mut hasher = Sha512::new();
+    hasher.update(TRANSACTION_ID_PREFIX);
+    hasher.update(hex::decode(blob).expect("blob is hex"));
+    hex::encode_upper(&hasher.finalize()[..32])
+}
+
+pub fn tst() -> CurrencyCode {
+    CurrencyCode::new("TST").unwrap()
+}
+
+pub fn tst_amount(issuer: &str, value: &str) -> CurrencyAmount {
+    CurrencyAmount::issued(&tst(), issuer, value).unwrap()
+}
+
+pub fn drops(drops: u64) -> CurrencyAmount {
+    CurrencyAmount::drops(drops).unwrap()
+}
+
+/// Amount field header (type 6, field 1) followed by the amount in binary form
+pub fn amount_value_field(amount: &CurrencyAmount) -> String {
+    amount_field("61", amount)
+}
+
+/// SendMax field header (type 6, field 9) followed by the amount in binary form
+pub fn send_max_field(amount: &CurrencyAmount) -> String {
+    amount_field("69", amount)
+}
+
+/// DeliverMin field header (type 6, field 10) followed by the amount in binary form
+pub fn deliver_min_field(amount: &CurrencyAmount) -> String {
+    amount_field("6A", amount)
+}
+
+fn amount_field(header: &str, amount: &CurrencyAmount) -> String {
+    let binary = BinaryAmount::try_from(amount.to_json()).unwrap();
+    format!("{}{}", header, hex::encode_upper(binary.as_ref()))
+}
+
+pub fn xrp_payment(from: &str, to: &str, drops: &str) -> Value {
+    json!({
+        "TransactionType": "Payment",
+        "Account": from,
+        "Destination": to,
+        "Amount": drops,
+        "Fee": "12",
+        "Sequence": ACCOUNT_SEQUENCE,
+    })
+}
+
+pub fn issued_payment(from: &str, to: &str, currency: &str, issuer: &str, value: &str) -> Value {
+    json!({
+        "TransactionType": "Payment",
+        "Account": from,
+        "Destination": to,
+        "Amount": { "currency": currency, "issuer": issuer, "value": value },
+        "Fee": "12",
+        "Sequence": ACCOUNT_SEQUENCE,
+    })
+}
+
+pub fn success_meta(delivered_amount: Value) -> Value {
+    json!({
+        "TransactionIndex": 0,
+        "TransactionResult": "tesSUCCESS",
+        "AffectedNodes": [],
+        "delivered_amount": delivered_amount,
+    })
+}
+
+/// Metadata of a partial payment: rippled records DeliveredAmount and also
+/// reports it as the synthetic delivered_amount
+pub fn partial_payment_meta(delivered_amount: Value) -> Value {
+    let mut meta = success_meta(delivered_amount.clone());
+    meta["DeliveredAmount"] = delivered_amount;
+    meta
+}
+
+pub fn account_info_fixture(account: &str, sequence: u32) -> Value {
+    json!({
+        "account_data": {
+            "Account": account,
+            "Balance": "100000000000",
+            "Flags": 0,
+            "LedgerEntryType": "AccountRoot",
+            "OwnerCount": 0,
+            "PreviousTxnID": "4294BEBE5B569A18C0A2702387C9B1E7146DC3A5850C1E87204951C6FDAA4C42",
+            "PreviousTxnLgrSeq": 3,
+            "Sequence": sequence,
+            "index": "92FA6A9FC8EA6018D5D16532D7795C91BFB0831355BDFDA177E86C8BF997985F"
+        },
+        "ledger_index": VALIDATED_LEDGER_INDEX,
+        "validated": true
+    })
+}
+
+pub fn ledger_fixture(ledger_index: u32) -> Value {
+    json!({
+        "ledger": {
+            "account_hash": "B258A8BB4743FB74CBBD6E9F67E4A56C4432EA09E5805E4CC2DA26F2DBE8F3D1",
+            "close_flags": 0,
+            "close_time": 800000000,
+            "close_time_human": "2025-May-09 12:53:20.000000000 UTC",
+            "close_time_resolution": 10,
+            "closed": true,
+            "ledger_hash": "8A6E2F5C14BCA4E3C5B1A0E4E4B6F1D3C1A6F2B9E0C8D7A6B5C4D3E2F1A0B9C8",
+            "ledger_index": ledger_index.to_string(),
+            "parent_close_time": 799999990,
+            "parent_hash": "2B7E5D3C1A0F9E8D7C6B5A4F3E2D1C0B9A8F7E6D5C4B3A2F1E0D9C8B7A6F5E4D",
+            "total_coins": "99999999999999980",
+            "transaction_hash": "0000000000000000000000000000000000000000000000000000000000000000"
+        },
+        "ledger_hash": "8A6E2F5C14BCA4E3C5B1A0E4E4B6F1D3C1A6F2B9E0C8D7A6B5C4D3E2F1A0B9C8",
+        "ledger_index": ledger_index,
+        "validated": true
+    })
+}
+
+fn fee_fixture() -> Value {
+    json!({
+        "current_ledger_size": "14",
+        "current_queue_size": "0",
+        "drops": {
+            "base_fee": "10",
+            "median_fee": "5000",
+            "minimum_fee": "10",
+            "open_ledger_fee": "10"
+        },
+        "expected_ledger_size": "24",
+        "ledger_current_index": VALIDATED_LEDGER_INDEX + 1,
+        "levels": {
+            "median_level": "128000",
+            "minimum_level": "256",
+            "open_ledger_level": "256",
+            "reference_level": "256"
+        },
+        "max_queue_size": "480"
+    })
+}
+
+fn server_state_fixture() -> Value {
+    json!({
+        "state": {
+            "build_version": "2.3.0",
+            "complete_ledgers": "1-1000",
+            "server_state": "full",
+            "validated_ledger": {
+                "base_fee": 10,
+                "close_time": 800000000,
+                "hash": "8A6E2F5C14BCA4E3C5B1A0E4E4B6F1D3C1A6F2B9E0C8D7A6B5C4D3E2F1A0B9C8",
+                "reserve_base": 10000000,
+                "reserve_inc": 2000000,
+                "seq": VALIDATED_LEDGER_INDEX
+            }
+        }
+    })
+}
+
+fn server_info_fixture() -> Value {
+    json!({
+        "info": {
+            "build_version": "2.3.0",
+            "complete_ledgers": "1-1000",
+            "io_latency_ms": 1,
+            "last_close": { "converge_time_s": 2, "proposers": 4 },
+            "load_factor": 1,
+            "network_id": 1,
+            "peers": 10,
+            "pubkey_node": "n9KAa2zVWjPHgfzsE3iZ8HAbzJtPrnoh4H2M2HgE7dfqtvyEb1KJ",
+            "server_state": "full",
+            "validated_ledger": {
+                "age": 2,
+                "base_fee_xrp": "0.00001",
+                "hash": "8A6E2F5C14BCA4E3C5B1A0E4E4B6F1D3C1A6F2B9E0C8D7A6B5C4D3E2F1A0B9C8",
+                "reserve_base_xrp": "10",
+                "reserve_inc_xrp": "2",
+                "seq": VALIDATED_LEDGER_INDEX
+            },
+            "validation_quorum": 3
+        }
+    })
+}
+
+fn engine_result_message(engine_result: &str) -> &'static str {
+    match engine_result {
+        "tesSUCCESS" => "The transaction was applied. Only final in a validated ledger.",
+        "tefMAX_LEDGER" => "Ledger sequence too high.",
+        "tecUNFUNDED_PAYMENT" => "Insufficient XRP balance to send.",
+        "temMALFORMED" => "Malformed transaction.",
+        "terQUEUED" => "Held until escalated fee drops.",
+        "terPRE_SEQ" => "Missing/inapplicable prior transaction.",
+        "telINSUF_FEE_P" => "Fee insufficient.",
+        _ => "Scripted engine result.",
+    }
+}
+
+// Sequence and LastLedgerSequence of a signed blob. Both are UInt32 fields, and
+// canonical field order puts every UInt16/UInt32 field at the start of the blob.
+fn leading_sequence_fields(blob: &str) -> (u32, Option<u32>) {
+    let bytes = hex::decode(blob).expect("blob is hex");
+    let (mut sequence, mut last_ledger_sequence) = (0, None);
+    let mut i = 0;
+    while i < bytes.len() {
+        let type_code = bytes[i] >> 4;
+        let mut field_code = bytes[i] & 0x0F;
+        i += 1;
+        if field_code == 0 {
+            field_code = bytes[i];
+            i += 1;
+        }
+        let width = match type_code {
+            1 => 2,
+            2 => 4,
+            _ => break,
+        };
+        let value = bytes[i..i + width].iter().fold(0u32, |acc, b| (acc << 8) | *b as u32);
+        match (type_code, field_code) {
+            (2, 4) => sequence = value,
+            (2, 27) => last_ledger_sequence = Some(value),
+            _ => {}
+        }
+        i += width;
+    }
+    (sequence, last_ledger_sequence)
+}
+
+// rippled always sends a numeric code with errors; xrpl-rust relies on it to
+// tell error responses apart from subscription stream messages
+fn error_code(error: &str) -> i32 {
+    match error {
+        "unknownCmd" => 32,
+        "actNotFound" => 19,
+        "txnNotFound" => 29,
+        "lgrNotFound" => 21,
+        _ => -1,
+    }
+}
+
+fn reply_for(state: &Arc<Mutex<MockState>>, request: &Value) -> Reply {
+    let mut state = state.lock().unwrap();
+    state.requests.push(request.clone());
+    let command = request["command"].as_str().unwrap_or_default().to_string();
+    if state.stalled.contains(&command) {
+        return Reply::Silent;
+    }
+
+    match command.as_str() {
+        // A scripted reply (e.g. set_error) stands in for a blob the node refuses to parse
+        "submit" if state.fixtures.contains_key("submit") => state.fixtures["submit"][0].clone(),
+        "submit" => {
+            let blob = request["tx_blob"].as_str().unwrap_or_default().to_string();
+            let hash = transaction_hash(&blob);
+            let engine_result = state
+                .engine_results
+                .pop_front()
+                .unwrap_or_else(|| "tesSUCCESS".to_string());
+            let affected_nodes = state.affected_nodes.pop_front().unwrap_or_else(|| json!([]));
+            state.submitted.push(blob.clone());
+
+            let (sequence, last_ledger_sequence) = leading_sequence_fields(&blob);
+            let mut tx_json = json!({ "hash": hash, "Sequence": sequence });
+            if let Some(last_ledger_sequence) = last_ledger_sequence {
+                tx_json["LastLedgerSequence"] = json!(last_ledger_sequence);
+            }
+            // Submitted transactions are validated in the next ledger with their preliminary result
+            if !matches!(&engine_result[..3], "tem" | "tef" | "tel") {
+                let final_result = if engine_result == "terQUEUED" { "tesSUCCESS" } else { &engine_result };
+                state.transactions.insert(
+                    hash.clone(),
+                    json!({
+                        "hash": hash,
+                        "Sequence": sequence,
+                        "ledger_index": VALIDATED_LEDGER_INDEX + 1,
+                        "date": 800000000,
+                        "validated": true,
+                        "meta": {
+                            "TransactionIndex": 0,
+                            "TransactionResult": final_result,
+                            "AffectedNodes": affected_nodes
+                        }
+                    }),
+                );
+            }
+
+            Reply::Result(json!({
+                "accepted": true,
+                "applied": engine_result == "tesSUCCESS",
+                "broadcast": true,
+                "kept": true,
+                "queued": engine_result == "terQUEUED",
+                "engine_result": engine_result,
+                "engine_result_code": 0,
+                "engine_result_message": engine_result_message(&engine_result),
+                "tx_blob": blob,
+                "tx_json": tx_json,
+                "validated_ledger_index": VALIDATED_LEDGER_INDEX
+            }))
+        }
+        "account_tx" => {
+            let account = request["account"].as_str().unwrap_or_default();
+            let min = request["ledger_index_min"].as_u64().unwrap_or(0).max(state.first_ledger.unwrap_or(0));
+            let max = request["ledger_index_max"].as_u64().unwrap_or(u64::MAX);
+            let matching: Vec<Value> = state
+                .history
+                .iter()
+                .filter(|entry| entry["tx"]["Account"] == account || entry["tx"]["Destination"] == account)
+                .filter(|entry| (min..=max).contains(&entry["tx"]["ledger_index"].as_u64().unwrap_or(0)))
+                .cloned()
+                .collect();
+            let start = request["marker"].as_u64().unwrap_or(0) as usize;
+            let end = (start + state.history_page_size).min(matching.len());
+            let mut result = json!({
+                "account": account,
+                "ledger_index_min": min,
+                "ledger_index_max": max,
+                "transactions": matching[start..end],
+                "validated": true,
+            });
+            if end < matching.len() {
+                result["marker"] = json!(end);
+            }
+            Reply::Result(result)
+        }
+        "tx" => {
+            let hash = request["transaction"].as_str().unwrap_or_default().to_string();
+            let submitted = state.submitted.iter().any(|blob| transaction_hash(blob) == hash);
+            if submitted && state.pending_lookups > 0 {
+                state.pending_lookups -= 1;
+                return Reply::Error("txnNotFound".to_string());
+            }
+            match state.transactions.get(&hash) {
+                Some(result) => Reply::Result(result.clone()),
+                None => Reply::Error("txnNotFound".to_string()),
+            }
+        }
+        _ => match state.fixtures.get_mut(&command) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) if !queue.is_empty() => queue[0].clone(),
+            _ => Reply::Error("unknownCmd".to_string()),
+        },
+    }
+}
+
+// Full rippled response envelope for a request, or None when the reply is Silent
+fn response_for(state: &Arc<Mutex<MockState>>, request: &Value) -> Option<Value> {
+    let response = match reply_for(state, request) {
+        Reply::Result(result) => json!({
+            "id": request["id"],
+            "status": "success",
+            "type": "response",
+            "result": result,
+        }),
+        Reply::Error(error) => json!({
+            "id": request["id"],
+            "status": "error",
+            "type": "response",
+            "error": error,
+            "error_code": error_code(&error),
+            "error_message": error,
+            "request": request,
+        }),
+        Reply::Silent => return None,
+    };
+    Some(response)
+}
+
+async fn serve_connection(
+    stream: TcpStream,
+    state: Arc<Mutex<MockState>>,
+    mut stopped: watch::Receiver<bool>,
+    mut stream_messages: broadcast::Receiver<Value>,
+) {
+    let Ok(mut websocket) = accept_async(stream).await else {
+        return;
+    };
+    let mut subscribed = false;
+
+    loop {
+        let message = tokio::select! {
+            message = websocket.next() => message,
+            Ok(published) = stream_messages.recv() => {
+                if subscribed && websocket.send(Message::Text(published.to_string())).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+            _ = stopped.changed() => {
+                let _ = websocket.close(None).await;
+                return;
+            }
+        };
+        let Some(Ok(message)) = message else {
+            break;
+        };
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(request) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+
+        let Some(response) = response_for(&state, &request) else {
+            continue;
+        };
+        // path_find create streams updates like a subscription
+        let opens_stream = request["command"] == "subscribe"
+            || (request["command"] == "path_find" && request["subcommand"] == "create");
+        subscribed |= opens_stream && response["status"] == "success";
+        if websocket
+            .send(Message::Text(response.to_string()))
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+// Minimal HTTP/1.1 server for rippled's JSON-RPC API. Keeps the connection open
+// for as many requests as the client sends (reqwest reuses connections).
+async fn serve_http_connection(
+    stream: TcpStream,
+    state: Arc<Mutex<MockState>>,
+    mut stopped: watch::Receiver<bool>,
+) {
+    let mut stream = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        let read = tokio::select! {
+            read = stream.read_line(&mut line) => read,
+            _ = stopped.changed() => return,
+        };
+        if read.unwrap_or(0) == 0 {
+            return;
+        }
+        let mut content_length = 0;
+        loop {
+            line.clear();
+            if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                return;
+            }
+            if line == "\r\n" {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut body = vec![0; content_length];
+        if stream.read_exact(&mut body).await.is_err() {
+            return;
+        }
+
+        // {"method": "tx", "params": [{...}]} is answered like {"command": "tx", ...}
+        let rpc: Value = serde_json::from_slice(&body).unwrap_or_default();
+        let mut request = rpc["params"][0].clone();
+        request["command"] = rpc["method"].clone();
+
+        // JSON-RPC nests status and errors inside `result`
+        let result = match reply_for(&state, &request) {
+            Reply::Result(mut result) => {
+                result["status"] = json!("success");
+                result
+            }
+            Reply::Error(error) => json!({
+                "status": "error",
+                "error": error,
+                "error_code": error_code(&error),
+                "error_message": error,
+                "request": request,
+            }),
+            // Hold the connection open without answering until the mock shuts down
+            Reply::Silent => {
+                let _ = stopped.changed().await;
+                return;
+            }
+        };
+        let body = json!({ "result": result }).to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        if stream.get_mut().write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
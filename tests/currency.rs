@@ -0,0 +1,75 @@
+mod common;
+
+use common::*;
+use ripple_task::{CurrencyAmount, CurrencyCode, PaymentOptions, RippleError};
+use serde_json::json;
+
+const MYTOKEN_HEX: &str = "4D59544F4B454E00000000000000000000000000";
+
+#[test]
+fn standard_codes_are_kept_and_hex_wrapped_ones_unwrapped() {
+    let usd = CurrencyCode::new("USD").unwrap();
+    assert!(usd.is_standard());
+    assert_eq!(usd.as_str(), "USD");
+    assert_eq!(usd.to_hex(), "0000000000000000000000005553440000000000");
+    assert_eq!(CurrencyCode::new(&usd.to_hex()).unwrap(), usd);
+    assert_eq!(CurrencyCode::new("a?!").unwrap().as_str(), "a?!");
+}
+
+#[test]
+fn names_are_hex_encoded_and_decoded() {
+    let name = CurrencyCode::new("MYTOKEN").unwrap();
+    assert!(!name.is_standard());
+    assert_eq!(name.as_str(), MYTOKEN_HEX);
+    assert_eq!(name, CurrencyCode::new(&MYTOKEN_HEX.to_lowercase()).unwrap());
+    assert_eq!(name.name().as_deref(), Some("MYTOKEN"));
+
+    let utf8 = CurrencyCode::new("Grüße").unwrap();
+    assert_eq!(utf8.name().as_deref(), Some("Grüße"));
+    assert_eq!(CurrencyCode::new("€").unwrap().as_str(), "E282AC0000000000000000000000000000000000");
+
+    let binary = CurrencyCode::new("80FF000000000000000000000000000000000001").unwrap();
+    assert_eq!(binary.name(), None);
+}
+
+#[test]
+fn invalid_codes_are_rejected() {
+    for invalid in [
+        "XRP",
+        "0000000000000000000000005852500000000000",
+        "",
+        "A-B",
+        "TWENTY-ONE-BYTES-LONG",
+        "0000000000000000000000000000000000000001",
+    ] {
+        let err = CurrencyCode::new(invalid).unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{invalid:?}: {err:?}");
+    }
+    assert!(serde_json::from_value::<CurrencyCode>(json!("XRP")).is_err());
+}
+
+#[tokio::test]
+async fn verify_transfer_matches_hex_currency_by_name() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let hash = "9A3C5E7F1B2D4F6A8C0E2B4D6F8A1C3E5B7D9F0A2C4E6B8D1F3A5C7E9B0D2F4A";
+    mock.add_transaction(
+        hash,
+        issued_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, MYTOKEN_HEX, SENDER_ADDRESS, "5"),
+        success_meta(json!({ "currency": MYTOKEN_HEX, "issuer": SENDER_ADDRESS, "value": "5" })),
+        true,
+    );
+    let xrpl = mock.connect().await;
+    let mytoken = CurrencyCode::new("MYTOKEN").unwrap();
+    let expected = CurrencyAmount::issued(&mytoken, SENDER_ADDRESS, "5").unwrap();
+
+    let report = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &expected, &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(report.is_verified(), "{report:?}");
+
+    xrpl.send_issued_token(&sender_secret(), RECEIVER_ADDRESS, &mytoken, &"5".parse().unwrap(), &PaymentOptions::default()).await.unwrap();
+    let blobs = mock.submitted_blobs();
+    assert!(blobs[0].to_uppercase().contains(MYTOKEN_HEX));
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::*;
+use ripple_task::{EngineResult, EngineResultClass, PaymentOptions, RippleError};
+
+#[test]
+fn engine_result_classes_follow_prefix() {
+    assert_eq!(EngineResult::new("tesSUCCESS").class(), EngineResultClass::Success);
+    assert_eq!(EngineResult::new("tecNO_DST").class(), EngineResultClass::ClaimedCost);
+    assert_eq!(EngineResult::new("tefPAST_SEQ").class(), EngineResultClass::Failure);
+    assert_eq!(EngineResult::new("telINSUF_FEE_P").class(), EngineResultClass::LocalError);
+    assert_eq!(EngineResult::new("temBAD_FEE").class(), EngineResultClass::Malformed);
+    assert_eq!(EngineResult::new("terPRE_SEQ").class(), EngineResultClass::Retry);
+    assert_eq!(EngineResult::new("xyz").class(), EngineResultClass::Unknown);
+
+    assert!(EngineResult::new("tecUNFUNDED_PAYMENT").claimed_fee());
+    assert!(EngineResult::new("tecUNFUNDED_PAYMENT").is_final());
+    assert!(EngineResult::new("terPRE_SEQ").is_retryable());
+    assert!(!EngineResult::new("terQUEUED").is_retryable());
+    assert!(EngineResult::new("tefMAX_LEDGER").is_expired());
+}
+
+#[tokio::test]
+async fn tec_result_is_rejected_with_hash() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.push_engine_result("tecUNFUNDED_PAYMENT");
+    let xrpl = mock.connect().await;
+
+    let err = xrpl
+        .send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, RippleError::Rejected { .. }));
+    assert!(!err.is_retryable());
+    assert_eq!(err.engine_result().unwrap().code(), "tecUNFUNDED_PAYMENT");
+    let blobs = mock.submitted_blobs();
+    assert_eq!(err.tx_hash(), Some(transaction_hash(&blobs[0]).as_str()));
+}
+
+#[tokio::test]
+async fn ter_and_tel_results_are_retryable() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.push_engine_result("terPRE_SEQ");
+    mock.push_engine_result("telINSUF_FEE_P");
+    let xrpl = mock.connect().await;
+
+    for expected in ["terPRE_SEQ", "telINSUF_FEE_P"] {
+        let err = xrpl
+            .send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable());
+        assert_eq!(err.engine_result().unwrap().code(), expected);
+    }
+}
+
+#[tokio::test]
+async fn queued_transaction_is_accepted() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.push_engine_result("terQUEUED");
+    let xrpl = mock.connect().await;
+
+    let tx_hash = xrpl
+        .send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(tx_hash, transaction_hash(&mock.submitted_blobs()[0]));
+}
+
+#[tokio::test]
+async fn expired_blob_is_rejected_as_max_ledger() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let params = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    let blob = ripple_task::XRPLManager::offline_sign_transaction(
+        &sender_secret(),
+        RECEIVER_ADDRESS,
+        xrpl::models::Amount::XRPAmount("75".into()),
+        params,
+        &PaymentOptions::default(),
+    )
+    .unwrap();
+    mock.push_engine_result("tefMAX_LEDGER");
+
+    let err = xrpl.submit_signed_blob(&blob).await.unwrap_err();
+
+    assert!(err.engine_result().unwrap().is_expired());
+    assert_eq!(err.tx_hash(), Some(transaction_hash(&blob).as_str()));
+}
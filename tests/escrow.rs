@@ -0,0 +1,197 @@
+mod common;
+
+use common::*;
+use ripple_task::{Drops, EscrowBuilder, PreimageCondition, RippleError, XRPLManager, from_ripple_time, to_ripple_time};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, UNIX_EPOCH};
+
+const ESCROW_CREATE_HASH: &str = "5D1B7A6E3C9F2B8A4E0D6C1F7B3A9E5D2C8F4B0A6E1D7C3F9B5A2E8D4C0F6B1A";
+// 2026-05-01T00:00:00Z
+const FINISH_AFTER: u32 = 830_822_400;
+
+fn drops_of(drops: u64) -> Drops {
+    Drops::new(drops).unwrap()
+}
+
+#[test]
+fn preimage_conditions_follow_the_crypto_conditions_encoding() {
+    let condition = PreimageCondition::from_preimage(b"open sesame").unwrap();
+    let digest = hex::encode_upper(Sha256::digest(b"open sesame"));
+    assert_eq!(condition.condition(), format!("A0258020{}81010B", digest));
+    assert_eq!(
+        condition.fulfillment().expose_secret(),
+        format!("A00D800B{}", hex::encode_upper("open sesame"))
+    );
+    assert!(!format!("{condition:?}").contains(&hex::encode_upper("open sesame")));
+
+    let shared = PreimageCondition::from_fulfillment(&condition.fulfillment().expose_secret().to_lowercase()).unwrap();
+    assert_eq!(shared.condition(), condition.condition());
+
+    let generated = PreimageCondition::generate();
+    assert_eq!(generated.condition().len(), 78);
+    assert_ne!(generated.condition(), PreimageCondition::generate().condition());
+
+    assert!(PreimageCondition::from_preimage(&[]).is_err());
+    assert!(PreimageCondition::from_preimage(&[7; 65]).is_err());
+    assert!(PreimageCondition::from_fulfillment("A0258020").is_err());
+}
+
+#[test]
+fn ripple_time_counts_from_2000() {
+    let time = UNIX_EPOCH + Duration::from_secs(946_684_800 + 3_600);
+    assert_eq!(to_ripple_time(time).unwrap(), 3_600);
+    assert_eq!(from_ripple_time(3_600), time);
+    assert!(to_ripple_time(UNIX_EPOCH + Duration::from_secs(946_684_799)).is_err());
+}
+
+#[tokio::test]
+async fn create_escrow_signs_ripple_times_and_the_condition() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let condition = PreimageCondition::generate();
+    let finish_after = from_ripple_time(FINISH_AFTER);
+    let escrow = EscrowBuilder::new(RECEIVER_ADDRESS, drops_of(1_000_000))
+        .finish_after(finish_after)
+        .cancel_after(finish_after + Duration::from_secs(86_400))
+        .with_condition(condition.condition());
+
+    let placed = xrpl.create_escrow(&sender_secret(), &escrow).await.unwrap();
+
+    let blob = &mock.submitted_blobs()[0];
+    assert_eq!(placed.tx_hash, transaction_hash(blob));
+    assert_eq!((placed.owner.as_str(), placed.sequence), (SENDER_ADDRESS, ACCOUNT_SEQUENCE));
+    assert!(blob.contains(&format!("2025{:08X}", FINISH_AFTER)), "FinishAfter: {blob}");
+    assert!(blob.contains(&format!("2024{:08X}", FINISH_AFTER + 86_400)), "CancelAfter: {blob}");
+    assert!(blob.contains(&format!("701127{}", condition.condition())), "Condition: {blob}");
+
+    let invalid = [
+        EscrowBuilder::new(RECEIVER_ADDRESS, drops_of(1_000_000)),
+        EscrowBuilder::new(RECEIVER_ADDRESS, drops_of(0)).finish_after(finish_after),
+        EscrowBuilder::new(RECEIVER_ADDRESS, drops_of(1_000_000)).finish_after(finish_after).cancel_after(finish_after),
+        EscrowBuilder::new(RECEIVER_ADDRESS, drops_of(1_000_000)).with_condition("A025"),
+    ];
+    for escrow in invalid {
+        let err = xrpl.create_escrow(&sender_secret(), &escrow).await.unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{escrow:?}: {err:?}");
+    }
+    assert_eq!(mock.submitted_blobs().len(), 1);
+}
+
+#[tokio::test]
+async fn finish_pays_for_the_fulfillment_and_cancel_names_the_escrow() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let condition = PreimageCondition::from_preimage(&[7; 32]).unwrap();
+
+    let finished = xrpl
+        .finish_escrow_and_wait(&sender_secret(), RECEIVER_ADDRESS, 17, Some(&condition))
+        .await
+        .unwrap();
+    assert!(finished.is_success());
+    let blob = &mock.submitted_blobs()[0];
+    // 33 base fees plus 2 for the 36-byte fulfillment
+    assert!(blob.contains("68400000000000015E"), "Fee of 350 drops: {blob}");
+    assert!(blob.contains("201900000011"), "OfferSequence 17: {blob}");
+    assert!(blob.contains(&format!("701024{}", condition.fulfillment().expose_secret())), "Fulfillment: {blob}");
+
+    xrpl.finish_escrow(&sender_secret(), RECEIVER_ADDRESS, 18, None).await.unwrap();
+    assert!(mock.submitted_blobs()[1].contains("68400000000000000A"), "{}", mock.submitted_blobs()[1]);
+
+    let tx_hash = xrpl.cancel_escrow(&sender_secret(), RECEIVER_ADDRESS, 19).await.unwrap();
+    let blob = &mock.submitted_blobs()[2];
+    assert_eq!(tx_hash, transaction_hash(blob));
+    assert!(blob.contains("201900000013"), "OfferSequence 19: {blob}");
+}
+
+#[tokio::test]
+async fn list_escrows_reads_the_sequence_from_the_escrow_create() {
+    let mock = MockRippled::start().await;
+    let condition = PreimageCondition::generate();
+    mock.set_result(
+        "account_objects",
+        json!({
+            "account": RECEIVER_ADDRESS,
+            "account_objects": [{
+                "LedgerEntryType": "Escrow",
+                "Account": SENDER_ADDRESS,
+                "Destination": RECEIVER_ADDRESS,
+                "Amount": "1000000",
+                "Condition": condition.condition(),
+                "FinishAfter": FINISH_AFTER,
+                "CancelAfter": FINISH_AFTER + 86_400,
+                "Flags": 0,
+                "PreviousTxnID": ESCROW_CREATE_HASH,
+                "PreviousTxnLgrSeq": VALIDATED_LEDGER_INDEX,
+                "index": "E1",
+            }, {
+                // Created before the node's history starts
+                "LedgerEntryType": "Escrow",
+                "Account": SENDER_ADDRESS,
+                "Destination": RECEIVER_ADDRESS,
+                "Amount": "2000000",
+                "FinishAfter": FINISH_AFTER,
+                "Flags": 0,
+                "PreviousTxnID": "0000000000000000000000000000000000000000000000000000000000000E02",
+                "PreviousTxnLgrSeq": 2,
+                "index": "E2",
+            }],
+            "ledger_index": VALIDATED_LEDGER_INDEX,
+            "validated": true,
+        }),
+    );
+    let escrow_create = json!({
+        "TransactionType": "EscrowCreate",
+        "Account": SENDER_ADDRESS,
+        "Destination": RECEIVER_ADDRESS,
+        "Amount": "1000000",
+        "Sequence": 23,
+        "Fee": "12",
+    });
+    mock.add_transaction(ESCROW_CREATE_HASH, escrow_create, success_meta(json!("1000000")), true);
+    let xrpl = mock.connect().await;
+
+    let escrows = xrpl.list_escrows(RECEIVER_ADDRESS).await.unwrap();
+
+    assert_eq!(escrows.len(), 2);
+    assert_eq!((escrows[1].escrow_id.as_str(), escrows[1].sequence), ("E2", None));
+    let escrow = &escrows[0];
+    assert_eq!((escrow.owner.as_str(), escrow.sequence), (SENDER_ADDRESS, Some(23)));
+    assert_eq!(escrow.amount, drops_of(1_000_000));
+    assert_eq!(escrow.condition.as_deref(), Some(condition.condition()));
+    let finish_after = escrow.finish_after_time().unwrap();
+    assert!(!escrow.can_finish_at(finish_after));
+    assert!(escrow.can_finish_at(finish_after + Duration::from_secs(1)));
+    assert!(!escrow.can_cancel_at(finish_after + Duration::from_secs(86_400)));
+    assert!(escrow.can_cancel_at(finish_after + Duration::from_secs(86_401)));
+    assert_eq!(mock.requests("account_objects")[0]["type"], "escrow");
+}
+
+#[tokio::test]
+async fn escrow_transactions_sign_offline() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let params = mock.connect().await.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    let condition = PreimageCondition::generate();
+    let escrow = EscrowBuilder::new(RECEIVER_ADDRESS, drops_of(2_500_000))
+        .with_condition(condition.condition())
+        .cancel_after(from_ripple_time(FINISH_AFTER));
+
+    let blobs = [
+        XRPLManager::offline_sign_escrow_create(&sender_secret(), &escrow, &params).unwrap(),
+        XRPLManager::offline_sign_escrow_finish(&sender_secret(), SENDER_ADDRESS, 5, Some(&condition), &params).unwrap(),
+        XRPLManager::offline_sign_escrow_cancel(&sender_secret(), SENDER_ADDRESS, 5, &params).unwrap(),
+    ];
+
+    // The finish fee is scaled from the gathered 12 drops: 35 * 12 = 420
+    assert!(blobs[1].contains("6840000000000001A4"), "{}", blobs[1]);
+    assert!(blobs[2].contains("68400000000000000C"), "{}", blobs[2]);
+    let xrpl = mock.connect().await;
+    for blob in &blobs {
+        assert!(blob.contains(&format!("2400{:06X}", ACCOUNT_SEQUENCE)), "Sequence: {blob}");
+        let tx_hash = xrpl.submit_signed_blob(blob).await.unwrap();
+        assert_eq!(tx_hash, transaction_hash(blob));
+    }
+}
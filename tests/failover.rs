@@ -0,0 +1,136 @@
+mod common;
+
+use common::*;
+use ripple_task::{NetworkConfig, ReconnectPolicy, RippleError, XRPLAsyncClient, XRPLManager};
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use xrpl::models::requests::submit::Submit as SubmitRequest;
+
+fn fast_policy() -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        max_attempts: 4,
+    }
+}
+
+#[tokio::test]
+async fn fails_over_and_replays_idempotent_requests() {
+    let primary = MockRippled::start().await;
+    let backup = MockRippled::start().await;
+    primary.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    backup.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE + 1);
+
+    let xrpl = XRPLManager::connect_supervised(vec![primary.network(), backup.network()], fast_policy())
+        .await
+        .unwrap();
+    let params = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    assert_eq!(params.sequence, ACCOUNT_SEQUENCE);
+
+    primary.shutdown();
+
+    let params = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    assert_eq!(params.sequence, ACCOUNT_SEQUENCE + 1);
+    assert_eq!(xrpl.client().current_endpoint(), &backup.network());
+    assert!(!backup.requests("account_info").is_empty());
+}
+
+#[tokio::test]
+async fn fails_over_raw_queries() {
+    let primary = MockRippled::start().await;
+    let backup = MockRippled::start().await;
+    let listing = serde_json::json!({ "account": SENDER_ADDRESS, "offers": [] });
+    primary.set_result("account_offers", listing.clone());
+    backup.set_result("account_offers", listing);
+
+    let xrpl = XRPLManager::connect_supervised(vec![primary.network(), backup.network()], fast_policy())
+        .await
+        .unwrap();
+    xrpl.account_offers(SENDER_ADDRESS).await.unwrap();
+    xrpl.account_offers(SENDER_ADDRESS).await.unwrap();
+    assert_eq!(primary.requests("account_offers").len(), 2);
+
+    primary.shutdown();
+
+    xrpl.account_offers(SENDER_ADDRESS).await.unwrap();
+    assert_eq!(xrpl.client().current_endpoint(), &backup.network());
+    assert_eq!(backup.requests("account_offers").len(), 1);
+}
+
+#[tokio::test]
+async fn skips_unreachable_endpoints_on_connect() {
+    let down = MockRippled::start().await;
+    let up = MockRippled::start().await;
+    down.shutdown();
+    tokio::task::yield_now().await;
+
+    let xrpl = XRPLManager::connect_supervised(vec![down.network(), up.network()], fast_policy())
+        .await
+        .unwrap();
+    assert_eq!(xrpl.client().current_endpoint(), &up.network());
+}
+
+#[tokio::test]
+async fn submissions_are_not_replayed_after_a_disconnect() {
+    let primary = MockRippled::start().await;
+    let backup = MockRippled::start().await;
+    backup.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = XRPLManager::connect_supervised(vec![primary.network(), backup.network()], fast_policy())
+        .await
+        .unwrap();
+
+    primary.shutdown();
+
+    // The node may have applied the blob before the connection dropped
+    let submit = SubmitRequest::new(None, Cow::Borrowed("1200002280000000"), None);
+    assert!(xrpl.client().request(submit.into()).await.is_err());
+    assert!(backup.submitted_blobs().is_empty());
+
+    // The connection was still replaced for the next caller
+    assert_eq!(xrpl.client().current_endpoint(), &backup.network());
+    xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+}
+
+// Endpoint that counts connection attempts. With `handshake`, it completes the WebSocket
+// handshake and then hangs up, so connecting succeeds but every request fails.
+async fn dead_endpoint(handshake: bool) -> (NetworkConfig, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            if handshake {
+                drop(tokio_tungstenite::accept_async(stream).await);
+            }
+        }
+    });
+    (NetworkConfig::custom(&url, None).unwrap(), attempts)
+}
+
+#[tokio::test]
+async fn one_request_spends_at_most_max_attempts_connecting() {
+    let primary = MockRippled::start().await;
+    let (refusing, refused) = dead_endpoint(false).await;
+    let (hanging_up, hung_up) = dead_endpoint(true).await;
+    let policy = fast_policy();
+    let xrpl = XRPLManager::connect_supervised(vec![primary.network(), refusing, hanging_up], policy.clone())
+        .await
+        .unwrap();
+
+    primary.shutdown();
+
+    assert!(xrpl.gather_transaction_params(SENDER_ADDRESS).await.is_err());
+    let attempts = refused.load(Ordering::SeqCst) + hung_up.load(Ordering::SeqCst);
+    assert!(attempts > 0 && attempts <= policy.max_attempts as usize, "{attempts} connection attempts");
+}
+
+#[tokio::test]
+async fn requires_at_least_one_endpoint() {
+    let result = XRPLManager::connect_supervised(Vec::new(), ReconnectPolicy::default()).await;
+    assert!(matches!(result, Err(RippleError::InvalidInput(_))));
+}
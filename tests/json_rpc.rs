@@ -0,0 +1,81 @@
+mod common;
+
+use common::*;
+use ripple_task::{NetworkConfig, PaymentOptions, RippleError, Transport};
+use serde_json::json;
+
+#[test]
+fn transport_follows_url_scheme() {
+    assert_eq!(NetworkConfig::testnet().transport(), Transport::WebSocket);
+
+    let over_http = NetworkConfig::testnet().over_json_rpc();
+    assert_eq!(over_http.transport(), Transport::JsonRpc);
+    assert_eq!(over_http.url.as_str(), "https://s.altnet.rippletest.net:51234/");
+    assert_eq!(over_http.network_id, Some(1));
+}
+
+#[test]
+fn custom_endpoint_rejects_unknown_schemes() {
+    for url in ["htp://127.0.0.1:5005", "tcp://127.0.0.1:6006"] {
+        assert!(matches!(NetworkConfig::custom(url, None), Err(RippleError::InvalidInput(_))), "{}", url);
+    }
+    assert!(matches!(NetworkConfig::testnet().with_url("tcp://127.0.0.1:6006"), Err(RippleError::InvalidInput(_))));
+
+    for url in ["ws://127.0.0.1:6006", "wss://example.com", "http://127.0.0.1:5005", "https://example.com"] {
+        assert!(NetworkConfig::custom(url, None).is_ok(), "{}", url);
+    }
+}
+
+#[tokio::test]
+async fn operations_run_over_json_rpc() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect_http().await;
+    assert_eq!(xrpl.client().transport(), Transport::JsonRpc);
+
+    let params = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    assert_eq!(params.sequence, ACCOUNT_SEQUENCE);
+    assert_eq!(params.current_ledger_index, VALIDATED_LEDGER_INDEX);
+
+    let validated = xrpl
+        .send_xrp_and_wait(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(validated.is_success());
+    assert_eq!(validated.tx_hash, transaction_hash(&mock.submitted_blobs()[0]));
+    assert!(!mock.requests("fee").is_empty());
+}
+
+#[tokio::test]
+async fn verify_transfer_over_json_rpc() {
+    let mock = MockRippled::start().await;
+    let hash = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+    mock.add_transaction(
+        hash,
+        xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"),
+        success_meta(json!("1000")),
+        true,
+    );
+    let xrpl = mock.connect_http().await;
+
+    let verified = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(verified.is_verified());
+}
+
+#[tokio::test]
+async fn json_rpc_errors_keep_their_code() {
+    let mock = MockRippled::start().await;
+    mock.set_error("account_info", "actNotFound");
+    let xrpl = mock.connect_http().await;
+
+    let err = xrpl
+        .gather_transaction_params(RECEIVER_ADDRESS)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, RippleError::Account { .. }));
+    assert!(err.to_string().contains("actNotFound"));
+}
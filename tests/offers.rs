@@ -0,0 +1,129 @@
+mod common;
+
+use common::*;
+use ripple_task::{OfferBuilder, RippleError};
+use serde_json::{Value, json};
+
+fn tst_json(value: &str) -> Value {
+    tst_amount(ISSUER_ADDRESS, value).to_json()
+}
+
+// Sell 10 TST for 100 XRP: 6 TST trade against two offers of RECEIVER_ADDRESS, 4 TST rest on the book
+fn partial_fill_nodes() -> Value {
+    json!([
+        { "ModifiedNode": {
+            "LedgerEntryType": "AccountRoot",
+            "LedgerIndex": "A1",
+            "FinalFields": { "Account": SENDER_ADDRESS, "Balance": "100059999988" },
+            "PreviousFields": { "Balance": "100000000000" },
+        }},
+        { "ModifiedNode": {
+            "LedgerEntryType": "Offer",
+            "LedgerIndex": "B1",
+            "FinalFields": { "Account": RECEIVER_ADDRESS, "Sequence": 7, "TakerGets": "40000000", "TakerPays": tst_json("4") },
+            "PreviousFields": { "TakerGets": "80000000", "TakerPays": tst_json("8") },
+        }},
+        { "DeletedNode": {
+            "LedgerEntryType": "Offer",
+            "LedgerIndex": "B2",
+            "FinalFields": { "Account": RECEIVER_ADDRESS, "Sequence": 8, "TakerGets": "0", "TakerPays": tst_json("0") },
+            "PreviousFields": { "TakerGets": "20000000", "TakerPays": tst_json("2") },
+        }},
+        { "CreatedNode": {
+            "LedgerEntryType": "Offer",
+            "LedgerIndex": "C1",
+            "NewFields": { "Account": SENDER_ADDRESS, "Sequence": ACCOUNT_SEQUENCE, "TakerGets": tst_json("4"), "TakerPays": "40000000" },
+        }},
+    ])
+}
+
+fn sell_tst() -> OfferBuilder {
+    OfferBuilder::new(tst_amount(ISSUER_ADDRESS, "10"), drops(100_000_000))
+}
+
+#[tokio::test]
+async fn place_offer_sets_flags_and_returns_the_offer_sequence() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let placed = xrpl
+        .place_offer(&sender_secret(), &sell_tst().immediate_or_cancel().sell())
+        .await
+        .unwrap();
+
+    let blob = &mock.submitted_blobs()[0];
+    assert_eq!(placed.tx_hash, transaction_hash(blob));
+    assert_eq!(placed.offer_sequence, ACCOUNT_SEQUENCE);
+    assert!(blob.contains("22000A0000"), "tfImmediateOrCancel | tfSell: {blob}");
+
+    let invalid = [
+        sell_tst().immediate_or_cancel().fill_or_kill(),
+        OfferBuilder::new(drops(1_000), drops(2_000)),
+        OfferBuilder::new(tst_amount(ISSUER_ADDRESS, "1"), tst_amount(ISSUER_ADDRESS, "2")),
+        OfferBuilder::new(tst_amount(ISSUER_ADDRESS, "0"), drops(2_000)),
+    ];
+    for offer in invalid {
+        let err = xrpl.place_offer(&sender_secret(), &offer).await.unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{offer:?}: {err:?}");
+    }
+    assert_eq!(mock.submitted_blobs().len(), 1);
+}
+
+#[tokio::test]
+async fn place_offer_and_wait_reports_the_immediate_fill() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.push_affected_nodes(partial_fill_nodes());
+    let xrpl = mock.connect().await;
+
+    let fill = xrpl.place_offer_and_wait(&sender_secret(), &sell_tst()).await.unwrap();
+
+    assert!(fill.validated);
+    assert_eq!(fill.offer_sequence, ACCOUNT_SEQUENCE);
+    assert_eq!(fill.result.as_deref(), Some("tesSUCCESS"));
+    assert_eq!(fill.taker_gets_filled, tst_amount(ISSUER_ADDRESS, "6"));
+    assert_eq!(fill.taker_pays_filled, drops(60_000_000));
+    assert!(fill.has_traded() && fill.is_on_book());
+    assert_eq!(fill.remaining_taker_gets, Some(tst_amount(ISSUER_ADDRESS, "4")));
+    assert_eq!(fill.remaining_taker_pays, Some(drops(40_000_000)));
+
+    // The same report from a lookup by hash, e.g. for an offer placed without waiting
+    let hash = "0F1E2D3C4B5A69788796A5B4C3D2E1F00F1E2D3C4B5A69788796A5B4C3D2E1F0";
+    let offer_create = json!({
+        "TransactionType": "OfferCreate",
+        "Account": SENDER_ADDRESS,
+        "Sequence": ACCOUNT_SEQUENCE,
+        "TakerGets": tst_json("10"),
+        "TakerPays": "100000000",
+        "Fee": "12",
+    });
+    let meta = json!({ "TransactionIndex": 0, "TransactionResult": "tesSUCCESS", "AffectedNodes": partial_fill_nodes() });
+    mock.add_transaction(hash, offer_create, meta, true);
+    let looked_up = xrpl.verify_offer(hash).await.unwrap();
+    assert_eq!(looked_up.taker_pays_filled, fill.taker_pays_filled);
+    assert_eq!(looked_up.remaining_taker_gets, fill.remaining_taker_gets);
+    assert_eq!(looked_up.ledger_index, Some(VALIDATED_LEDGER_INDEX));
+}
+
+#[tokio::test]
+async fn killed_offer_fills_nothing_and_cancel_names_the_sequence() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.push_engine_result("tecKILLED");
+    let xrpl = mock.connect().await;
+
+    let fill = xrpl.place_offer_and_wait(&sender_secret(), &sell_tst().fill_or_kill()).await.unwrap();
+    assert_eq!(fill.result.as_deref(), Some("tecKILLED"));
+    assert!(!fill.has_traded() && !fill.is_on_book());
+    assert_eq!(fill.taker_gets_filled, tst_amount(ISSUER_ADDRESS, "0"));
+
+    let tx_hash = xrpl.cancel_offer(&sender_secret(), 41).await.unwrap();
+    let blob = &mock.submitted_blobs()[1];
+    assert_eq!(tx_hash, transaction_hash(blob));
+    assert!(blob.contains("201900000029"), "OfferSequence 41: {blob}");
+
+    let hash = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+    mock.add_transaction(hash, xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"), success_meta(json!("1000")), true);
+    assert!(matches!(xrpl.verify_offer(hash).await.unwrap_err(), RippleError::InvalidInput(_)));
+}
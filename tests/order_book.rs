@@ -0,0 +1,177 @@
+mod common;
+
+use common::*;
+use ripple_task::{BookOffer, RippleError, offers};
+use serde_json::{Value, json};
+use xrpl::models::{Currency, IssuedCurrency, XRP};
+
+fn tst_json(value: &str) -> Value {
+    tst_amount(ISSUER_ADDRESS, value).to_json()
+}
+
+fn tst_currency() -> Currency<'static> {
+    Currency::IssuedCurrency(IssuedCurrency::new("TST".into(), ISSUER_ADDRESS.into()))
+}
+
+fn xrp() -> Currency<'static> {
+    Currency::XRP(XRP::new())
+}
+
+// Offers selling TST for XRP, best first. The second owner can only fund 4 of its 10 TST.
+fn tst_book() -> Value {
+    json!({
+        "ledger_current_index": VALIDATED_LEDGER_INDEX + 1,
+        "offers": [
+            {
+                "Account": RECEIVER_ADDRESS,
+                "Sequence": 7,
+                "TakerGets": tst_json("5"),
+                "TakerPays": "50000000",
+                "Flags": 0,
+                "index": "B1",
+                "owner_funds": "5",
+                "quality": "10000000",
+            },
+            {
+                "Account": SENDER_ADDRESS,
+                "Sequence": 12,
+                "TakerGets": tst_json("10"),
+                "TakerPays": "120000000",
+                "Flags": 131072,
+                "Expiration": 800000000,
+                "index": "B2",
+                "owner_funds": "4",
+                "taker_gets_funded": tst_json("4"),
+                "taker_pays_funded": "48000000",
+                "quality": "12000000",
+            },
+            {
+                "Account": RECEIVER_ADDRESS,
+                "Sequence": 9,
+                "TakerGets": tst_json("10"),
+                "TakerPays": "150000000",
+                "index": "B3",
+                "quality": "15000000",
+            },
+        ],
+    })
+}
+
+fn book_offers() -> Vec<BookOffer> {
+    serde_json::from_value(tst_book()["offers"].clone()).unwrap()
+}
+
+#[tokio::test]
+async fn book_offers_reads_owner_quality_and_funded_amounts() {
+    let mock = MockRippled::start().await;
+    mock.set_result("book_offers", tst_book());
+
+    for xrpl in [mock.connect().await, mock.connect_http().await] {
+        let offers = xrpl.book_offers(&tst_currency(), &xrp(), 20).await.unwrap();
+        assert_eq!(offers.len(), 3);
+        assert_eq!(offers[0].owner, RECEIVER_ADDRESS);
+        assert_eq!(offers[0].quality, "10000000");
+        assert_eq!(offers[0].funded(), (&tst_amount(ISSUER_ADDRESS, "5"), &drops(50_000_000)));
+        assert_eq!(offers[1].sequence, 12);
+        assert_eq!(offers[1].owner_funds.as_deref(), Some("4"));
+        assert_eq!(offers[1].expiration, Some(800_000_000));
+        assert_eq!(offers[1].funded(), (&tst_amount(ISSUER_ADDRESS, "4"), &drops(48_000_000)));
+        assert_eq!(offers[2].owner_funds, None);
+        assert_eq!(offers[2].offer_id, "B3");
+    }
+
+    let request = &mock.requests("book_offers")[0];
+    assert_eq!(request["taker_gets"], json!({ "currency": "TST", "issuer": ISSUER_ADDRESS }));
+    assert_eq!(request["taker_pays"], json!({ "currency": "XRP" }));
+    assert_eq!(request["limit"], 20);
+}
+
+#[tokio::test]
+async fn account_offers_follows_markers() {
+    let mock = MockRippled::start().await;
+    let offer = |seq: u32, drops: &str| {
+        json!({ "seq": seq, "flags": 0, "taker_gets": tst_json("1"), "taker_pays": drops, "quality": drops })
+    };
+    mock.push_reply(
+        "account_offers",
+        Reply::Result(json!({
+            "account": SENDER_ADDRESS,
+            "ledger_index": VALIDATED_LEDGER_INDEX,
+            "offers": [offer(3, "1000000")],
+            "marker": "page2",
+        })),
+    );
+    mock.push_reply(
+        "account_offers",
+        Reply::Result(json!({ "account": SENDER_ADDRESS, "offers": [offer(5, "2000000")] })),
+    );
+    let xrpl = mock.connect().await;
+
+    let offers = xrpl.account_offers(SENDER_ADDRESS).await.unwrap();
+
+    assert_eq!(offers.iter().map(|offer| offer.sequence).collect::<Vec<_>>(), vec![3, 5]);
+    assert_eq!(offers[1].taker_pays, drops(2_000_000));
+    assert_eq!(offers[0].taker_gets, tst_amount(ISSUER_ADDRESS, "1"));
+    let requests = mock.requests("account_offers");
+    assert_eq!(requests[0]["ledger_index"], "validated");
+    assert!(requests[0].get("marker").is_none());
+    assert_eq!(requests[1]["marker"], "page2");
+    // The second page comes from the ledger the first was read from
+    assert_eq!(requests[1]["ledger_index"], VALIDATED_LEDGER_INDEX);
+}
+
+#[tokio::test]
+async fn order_book_queries_go_through_the_managers_client() {
+    let mock = MockRippled::start().await;
+    mock.set_result("book_offers", tst_book());
+    mock.set_result("account_offers", json!({ "account": SENDER_ADDRESS, "offers": [] }));
+    let xrpl = mock.in_memory();
+
+    let offers = xrpl.book_offers(&tst_currency(), &xrp(), 20).await.unwrap();
+    assert_eq!(offers.len(), 3);
+    assert!(xrpl.account_offers(SENDER_ADDRESS).await.unwrap().is_empty());
+    assert_eq!(mock.requests("book_offers").len(), 1);
+}
+
+#[tokio::test]
+async fn quote_fill_walks_the_funded_book() {
+    let mock = MockRippled::start().await;
+    mock.set_result("book_offers", tst_book());
+    let xrpl = mock.connect().await;
+
+    // 5 TST from the first offer, 3 of the 4 funded TST from the second
+    let quote = xrpl
+        .quote_fill(&tst_currency(), &xrp(), &tst_amount(ISSUER_ADDRESS, "8"), 20)
+        .await
+        .unwrap();
+    assert!(quote.is_complete());
+    assert_eq!(quote.cost, drops(86_000_000));
+    assert_eq!(quote.effective_price, Some("10750000".parse().unwrap()));
+    assert_eq!(quote.worst_price, Some("12000000".parse().unwrap()));
+    assert_eq!(quote.offers_consumed, 2);
+
+    let book = book_offers();
+    let deeper_than_the_book = offers::quote_fill(&book, &tst_amount(ISSUER_ADDRESS, "20")).unwrap();
+    assert!(!deeper_than_the_book.is_complete());
+    assert_eq!(deeper_than_the_book.filled, tst_amount(ISSUER_ADDRESS, "19"));
+    assert_eq!(deeper_than_the_book.cost, drops(248_000_000));
+
+    // A partial drop is rounded up
+    let third = offers::quote_fill(&book, &tst_amount(ISSUER_ADDRESS, "0.3333333333333333")).unwrap();
+    assert_eq!(third.cost, drops(3_333_334));
+
+    for size in [drops(1_000), tst_amount(ISSUER_ADDRESS, "0"), tst_amount(SENDER_ADDRESS, "1")] {
+        let err = offers::quote_fill(&book, &size).unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{size:?}: {err:?}");
+    }
+}
+
+#[test]
+fn quote_fill_refuses_xrp_amounts_it_cannot_price_exactly() {
+    let mut book = tst_book();
+    book["offers"][0]["TakerPays"] = json!("12345678901234567");
+    let offers: Vec<BookOffer> = serde_json::from_value(book["offers"].clone()).unwrap();
+
+    let err = offers::quote_fill(&offers, &tst_amount(ISSUER_ADDRESS, "1")).unwrap_err();
+    assert!(matches!(err, RippleError::InvalidInput(_)), "{err:?}");
+}
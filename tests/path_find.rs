@@ -0,0 +1,255 @@
+mod common;
+
+use common::*;
+use futures::StreamExt;
+use ripple_task::{PathRequest, PaymentBuilder, RippleError};
+use serde_json::{Value, json};
+use xrpl::core::addresscodec::decode_classic_address;
+use xrpl::core::binarycodec::types::{PathSet, TryFromParser};
+use xrpl::core::binarycodec::{BinaryParser, Parser};
+use xrpl::core::keypairs::is_valid_message;
+use xrpl::models::{Currency, IssuedCurrency, XRP};
+
+// XRP -> TST through the order book, as rippled reports it
+fn alternative(source_drops: &str) -> Value {
+    json!({
+        "paths_computed": [[{ "currency": "TST", "issuer": ISSUER_ADDRESS, "type": 48, "type_hex": "0000000000000030" }]],
+        "source_amount": source_drops,
+    })
+}
+
+// Field of a transaction blob: its header and value as serialized, and the value alone
+struct BlobField {
+    name: String,
+    is_signing: bool,
+    bytes: Vec<u8>,
+    value: Vec<u8>,
+}
+
+// Split a signed blob into its fields with xrpl-rust's binary parser
+fn decode_fields(blob: &str) -> Vec<BlobField> {
+    let bytes = hex::decode(blob).unwrap();
+    let mut parser = BinaryParser::from(bytes.clone());
+    let mut fields = Vec::new();
+    while !parser.is_end(None) {
+        let start = bytes.len() - parser.len();
+        let field = parser.read_field().unwrap();
+        let mut value_start = bytes.len() - parser.len();
+        match field.associated_type.as_str() {
+            "UInt16" => {
+                parser.skip_bytes(2).unwrap();
+            }
+            "UInt32" => {
+                parser.skip_bytes(4).unwrap();
+            }
+            // Issued amounts (top bit set) take 48 bytes, XRP 8
+            "Amount" => {
+                let issued = parser.peek().unwrap()[0] & 0x80 != 0;
+                parser.skip_bytes(if issued { 48 } else { 8 }).unwrap();
+            }
+            "PathSet" => {
+                PathSet::from_parser(&mut parser, None).unwrap();
+            }
+            _ if field.is_vl_encoded => {
+                let length = parser.read_length_prefix().unwrap();
+                value_start = bytes.len() - parser.len();
+                parser.skip_bytes(length).unwrap();
+            }
+            other => panic!("unexpected {} field {}", other, field.name),
+        }
+        let end = bytes.len() - parser.len();
+        fields.push(BlobField {
+            name: field.name,
+            is_signing: field.is_signing,
+            bytes: bytes[start..end].to_vec(),
+            value: bytes[value_start..end].to_vec(),
+        });
+    }
+    fields
+}
+
+fn path_request() -> PathRequest {
+    PathRequest::new(SENDER_ADDRESS, RECEIVER_ADDRESS, tst_amount(ISSUER_ADDRESS, "10"))
+}
+
+fn xrp() -> Currency<'static> {
+    Currency::XRP(XRP::new())
+}
+
+#[tokio::test]
+async fn ripple_path_find_reads_alternatives_over_both_transports() {
+    let mock = MockRippled::start().await;
+    mock.set_result("ripple_path_find", json!({ "alternatives": [alternative("1200000")] }));
+
+    for xrpl in [mock.connect().await, mock.connect_http().await] {
+        let alternatives = xrpl.ripple_path_find(&path_request()).await.unwrap();
+        assert_eq!(alternatives.len(), 1);
+        assert_eq!(alternatives[0].source_amount, drops(1_200_000));
+        assert_eq!(alternatives[0].paths[0][0].currency.as_deref(), Some("TST"));
+        assert_eq!(alternatives[0].paths[0][0].account, None);
+    }
+
+    let request = &mock.requests("ripple_path_find")[1];
+    assert_eq!(request["source_account"], SENDER_ADDRESS);
+    assert_eq!(request["destination_amount"], json!({ "currency": "TST", "issuer": ISSUER_ADDRESS, "value": "10" }));
+    assert!(request.get("send_max").is_none());
+
+    let both = path_request().with_send_max(drops(2_000_000)).with_source_currency(xrp());
+    let err = mock.connect().await.ripple_path_find(&both).await.unwrap_err();
+    assert!(matches!(err, RippleError::InvalidInput(_)));
+}
+
+#[tokio::test]
+async fn quote_returns_the_cheapest_alternative() {
+    let mock = MockRippled::start().await;
+    mock.set_result(
+        "ripple_path_find",
+        json!({ "alternatives": [alternative("1500000"), alternative("1200000"), alternative("1300000")] }),
+    );
+    let xrpl = mock.connect().await;
+
+    let quote = xrpl
+        .quote_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "10"), &xrp())
+        .await
+        .unwrap();
+
+    assert_eq!(quote.source_amount, drops(1_200_000));
+    assert_eq!(mock.requests("ripple_path_find")[0]["source_currencies"], json!([{ "currency": "XRP" }]));
+
+    mock.set_result("ripple_path_find", json!({ "alternatives": [] }));
+    let err = xrpl
+        .quote_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "10"), &xrp())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("No path"));
+}
+
+#[tokio::test]
+async fn quote_compares_only_alternatives_from_the_requested_issuer() {
+    let mock = MockRippled::start().await;
+    let spending = |issuer: &str, value: &str| {
+        json!({ "paths_computed": [], "source_amount": tst_amount(issuer, value).to_json() })
+    };
+    // Fewer TST, but issued by someone else: not comparable to the TST the sender pays with
+    let alternatives = [spending(RECEIVER_ADDRESS, "5"), spending(ISSUER_ADDRESS, "8"), spending(ISSUER_ADDRESS, "7")];
+    mock.set_result("ripple_path_find", json!({ "alternatives": alternatives }));
+    let xrpl = mock.connect().await;
+    let pay_with = Currency::IssuedCurrency(IssuedCurrency::new("TST".into(), ISSUER_ADDRESS.into()));
+
+    let quote = xrpl
+        .quote_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1_000_000), &pay_with)
+        .await
+        .unwrap();
+    assert_eq!(quote.source_amount, tst_amount(ISSUER_ADDRESS, "7"));
+
+    mock.set_result("ripple_path_find", json!({ "alternatives": [spending(RECEIVER_ADDRESS, "5")] }));
+    let err = xrpl
+        .quote_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1_000_000), &pay_with)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("No path"));
+}
+
+#[tokio::test]
+async fn payment_from_an_alternative_carries_its_paths_and_send_max() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.set_result("ripple_path_find", json!({ "alternatives": [alternative("1200000")] }));
+    let xrpl = mock.connect().await;
+
+    let quote = xrpl.ripple_path_find(&path_request()).await.unwrap().remove(0);
+    let payment = PaymentBuilder::new(RECEIVER_ADDRESS, tst_amount(ISSUER_ADDRESS, "10")).with_alternative(&quote);
+    let tx_hash = xrpl.send_payment(&sender_secret(), &payment).await.unwrap();
+
+    let blob = &mock.submitted_blobs()[0];
+    assert_eq!(tx_hash, transaction_hash(blob));
+    assert!(blob.contains(&send_max_field(&drops(1_200_000))));
+    // Paths is the last field: one step of type currency + issuer, then the end marker
+    let issuer = hex::encode_upper(decode_classic_address(ISSUER_ADDRESS).unwrap());
+    assert!(blob.ends_with(&format!("011230{}{}00", tst().to_hex(), issuer)), "{blob}");
+}
+
+#[tokio::test]
+async fn payment_signature_covers_its_paths() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.set_result("ripple_path_find", json!({ "alternatives": [alternative("1200000")] }));
+    let xrpl = mock.connect().await;
+
+    let quote = xrpl.ripple_path_find(&path_request()).await.unwrap().remove(0);
+    let payment = PaymentBuilder::new(RECEIVER_ADDRESS, tst_amount(ISSUER_ADDRESS, "10")).with_alternative(&quote);
+    xrpl.send_payment(&sender_secret(), &payment).await.unwrap();
+
+    let fields = decode_fields(&mock.submitted_blobs()[0]);
+    let field = |name: &str| fields.iter().find(|field| field.name == name).unwrap();
+    let paths = PathSet::from_parser(&mut BinaryParser::from(field("Paths").value.clone()), None).unwrap();
+    assert_eq!(serde_json::to_value(&paths).unwrap(), json!([[{ "currency": "TST", "issuer": ISSUER_ADDRESS }]]));
+
+    // What the sender signed: the signing prefix and every signing field, in blob order
+    let signed = |with_paths: bool| {
+        let mut message = vec![0x53, 0x54, 0x58, 0x00];
+        for field in fields.iter().filter(|field| field.is_signing && (with_paths || field.name != "Paths")) {
+            message.extend_from_slice(&field.bytes);
+        }
+        message
+    };
+    let signature = hex::encode_upper(&field("TxnSignature").value);
+    let public_key = hex::encode_upper(&field("SigningPubKey").value);
+    assert!(is_valid_message(&signed(true), &signature, &public_key));
+    // Signing only what encode_for_signing serializes would leave the paths unsigned
+    assert!(!is_valid_message(&signed(false), &signature, &public_key));
+}
+
+#[tokio::test]
+async fn deliver_min_makes_a_partial_payment() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let amount = tst_amount(ISSUER_ADDRESS, "10");
+
+    let payment = PaymentBuilder::new(RECEIVER_ADDRESS, amount.clone())
+        .with_send_max(drops(1_500_000))
+        .with_deliver_min(tst_amount(ISSUER_ADDRESS, "9.5"));
+    xrpl.send_payment(&sender_secret(), &payment).await.unwrap();
+
+    let blob = &mock.submitted_blobs()[0];
+    assert!(blob.contains("2200020000"), "tfPartialPayment: {blob}");
+    assert!(blob.contains(&deliver_min_field(&tst_amount(ISSUER_ADDRESS, "9.5"))));
+
+    let invalid = [
+        PaymentBuilder::new(RECEIVER_ADDRESS, drops(1_000)).with_paths(vec![vec![Default::default()]]),
+        PaymentBuilder::new(RECEIVER_ADDRESS, amount.clone()).with_deliver_min(drops(1)),
+        PaymentBuilder::new(RECEIVER_ADDRESS, amount.clone()).with_deliver_min(tst_amount(ISSUER_ADDRESS, "11")),
+    ];
+    for payment in invalid {
+        let err = xrpl.send_payment(&sender_secret(), &payment).await.unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{payment:?}: {err:?}");
+    }
+    assert_eq!(mock.submitted_blobs().len(), 1);
+}
+
+#[tokio::test]
+async fn path_find_streams_updates_until_the_search_completes() {
+    let mock = MockRippled::start().await;
+    mock.set_result("path_find", json!({ "alternatives": [alternative("1300000")], "full_reply": false }));
+    let xrpl = mock.connect().await;
+
+    let mut updates = xrpl.path_find(&path_request()).await.unwrap();
+    assert_eq!(mock.requests("path_find")[0]["subcommand"], "create");
+
+    let first = updates.next().await.unwrap().unwrap();
+    assert!(!first.full_reply);
+    assert_eq!(first.alternatives[0].source_amount, drops(1_300_000));
+
+    mock.publish(json!({
+        "type": "path_find",
+        "alternatives": [alternative("1200000")],
+        "full_reply": true,
+    }));
+    let complete = updates.next().await.unwrap().unwrap();
+    assert!(complete.full_reply);
+    assert_eq!(complete.alternatives[0].source_amount, drops(1_200_000));
+
+    let err = mock.connect_http().await.path_find(&path_request()).await.unwrap_err();
+    assert!(matches!(err, RippleError::InvalidInput(_)));
+}
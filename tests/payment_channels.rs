@@ -0,0 +1,162 @@
+mod common;
+
+use common::*;
+use ripple_task::payment_channels::channel_id;
+use ripple_task::{ChannelBuilder, ChannelClaimBuilder, ChannelTracker, Drops, RippleError, Secret, XRPLManager};
+use serde_json::json;
+use std::time::Duration;
+
+const CHANNEL_ID: &str = "E35708503B3C3143FB522D749AAFCC296E8060F0FB371A9A56FAE0B1ED127366";
+const OTHER_SEED: &str = "sEdTM1uX8pu2do5XvTnutH6HsouMaM2";
+
+fn drops_of(drops: u64) -> Drops {
+    Drops::new(drops).unwrap()
+}
+
+fn sender_public_key() -> String {
+    XRPLManager::sign_channel_claim(&sender_secret(), CHANNEL_ID, drops_of(1)).unwrap().public_key
+}
+
+#[test]
+fn channel_ids_and_claims_match_rippled() {
+    assert_eq!(channel_id("rDx69ebzbowuqztksVDmZXjizTd12BVr4x", "rLFtVprxUEfsH54eCWKsZrEQzMDsx1wqso", 82).unwrap(), CHANNEL_ID);
+
+    let claim = XRPLManager::sign_channel_claim(&sender_secret(), CHANNEL_ID, drops_of(1_000_000)).unwrap();
+    assert!(claim.verify());
+    // "CLM\0", the channel ID, then the amount as a big-endian u64
+    let message = hex::decode(format!("434C4D00{}00000000000F4240", CHANNEL_ID)).unwrap();
+    assert!(xrpl::core::keypairs::is_valid_message(&message, &claim.signature, &claim.public_key));
+
+    let verify = |amount: u64, signature: &str| {
+        XRPLManager::verify_channel_claim(CHANNEL_ID, drops_of(amount), signature, &claim.public_key)
+    };
+    assert!(verify(1_000_000, &claim.signature));
+    assert!(!verify(1_000_001, &claim.signature));
+    assert!(!verify(1_000_000, "not hex"));
+    assert!(!XRPLManager::verify_channel_claim("ABCD", drops_of(1_000_000), &claim.signature, &claim.public_key));
+    assert!(XRPLManager::sign_channel_claim(&sender_secret(), "ABCD", drops_of(1)).is_err());
+}
+
+#[test]
+fn tracker_keeps_the_highest_claim_on_both_sides() {
+    let mut payer = ChannelTracker::new(CHANNEL_ID, &sender_public_key(), drops_of(10_000)).unwrap();
+    let mut payee = payer.clone();
+
+    let first = payer.authorize(&sender_secret(), drops_of(4_000)).unwrap();
+    let second = payer.authorize(&sender_secret(), drops_of(2_500)).unwrap();
+    assert_eq!(second.amount, drops_of(6_500));
+    assert_eq!(payer.available(), drops_of(3_500));
+    assert!(matches!(payer.authorize(&sender_secret(), drops_of(3_501)), Err(RippleError::InvalidInput(_))));
+    assert!(payer.authorize(&Secret::new(OTHER_SEED), drops_of(1)).is_err());
+    assert_eq!(payer.highest_claim(), Some(&second));
+
+    assert_eq!(payee.accept(&first).unwrap(), drops_of(4_000));
+    assert_eq!(payee.accept(&second).unwrap(), drops_of(2_500));
+    assert!(payee.accept(&first).is_err(), "stale claims pay nothing more");
+    let forged = ripple_task::SignedClaim { amount: drops_of(9_000), ..second.clone() };
+    assert!(payee.accept(&forged).is_err());
+    let foreign = XRPLManager::sign_channel_claim(&Secret::new(OTHER_SEED), CHANNEL_ID, drops_of(7_000)).unwrap();
+    assert!(payee.accept(&foreign).is_err());
+    assert_eq!(payee.highest_amount(), drops_of(6_500));
+    assert_eq!(payee.unredeemed(), drops_of(6_500));
+}
+
+#[test]
+fn tracker_accepts_a_lowercase_channel_key() {
+    let lowercase_key = sender_public_key().to_lowercase();
+    let mut payer = ChannelTracker::new(&CHANNEL_ID.to_lowercase(), &lowercase_key, drops_of(10_000)).unwrap();
+    let claim = payer.authorize(&sender_secret(), drops_of(1_000)).unwrap();
+    assert!(payer.authorize(&Secret::new(OTHER_SEED), drops_of(1)).is_err());
+
+    // A tracker restored from storage keeps the key exactly as it was saved
+    let mut saved = serde_json::to_value(&payer).unwrap();
+    saved["public_key"] = json!(lowercase_key);
+    let mut restored: ChannelTracker = serde_json::from_value(saved).unwrap();
+    assert_eq!(restored.authorize(&sender_secret(), drops_of(500)).unwrap().amount, drops_of(1_500));
+
+    let mut payee = ChannelTracker::new(CHANNEL_ID, &lowercase_key, drops_of(10_000)).unwrap();
+    assert_eq!(payee.accept(&claim).unwrap(), drops_of(1_000));
+}
+
+#[tokio::test]
+async fn create_fund_and_claim_sign_the_channel_fields() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let channel = ChannelBuilder::new(RECEIVER_ADDRESS, drops_of(10_000_000), Duration::from_secs(3_600));
+    let placed = xrpl.create_channel(&sender_secret(), &channel).await.unwrap();
+    let blob = &mock.submitted_blobs()[0];
+    assert_eq!(placed.tx_hash, transaction_hash(blob));
+    assert_eq!(placed.channel_id, channel_id(SENDER_ADDRESS, RECEIVER_ADDRESS, ACCOUNT_SEQUENCE).unwrap());
+    assert!(blob.contains("202700000E10"), "SettleDelay: {blob}");
+    assert!(blob.contains(&format!("7121{}", sender_public_key())), "PublicKey: {blob}");
+
+    xrpl.fund_channel(&sender_secret(), &placed.channel_id, drops_of(5_000_000), None).await.unwrap();
+    assert!(mock.submitted_blobs()[1].contains(&format!("5016{}", placed.channel_id)), "Channel");
+
+    let claim = XRPLManager::sign_channel_claim(&sender_secret(), &placed.channel_id, drops_of(1_500_000)).unwrap();
+    let redeem = ChannelClaimBuilder::new(&placed.channel_id).redeem(&claim).close();
+    let validated = xrpl.claim_channel_and_wait(&sender_secret(), &redeem).await.unwrap();
+    assert!(validated.is_success());
+    let blob = &mock.submitted_blobs()[2];
+    assert!(blob.contains("220002"), "tfClose: {blob}");
+    assert!(blob.contains("61400000000016E360"), "Amount: {blob}");
+    assert!(blob.contains("62400000000016E360"), "Balance: {blob}");
+    assert!(blob.contains(&claim.signature), "Signature: {blob}");
+
+    // Claims received from elsewhere may carry the channel ID in lowercase
+    let lowercase = ripple_task::SignedClaim { channel_id: claim.channel_id.to_lowercase(), ..claim.clone() };
+    xrpl.claim_channel(&sender_secret(), &ChannelClaimBuilder::new(&placed.channel_id).redeem(&lowercase))
+        .await
+        .unwrap();
+
+    let invalid = [
+        ChannelClaimBuilder::new(&placed.channel_id).close().renew(),
+        ChannelClaimBuilder::new(CHANNEL_ID).redeem(&claim),
+        ChannelClaimBuilder::new(&placed.channel_id).redeem(&claim).with_balance(drops_of(1_500_001)),
+    ];
+    for claim in invalid {
+        let err = xrpl.claim_channel(&sender_secret(), &claim).await.unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{claim:?}: {err:?}");
+    }
+    assert_eq!(mock.submitted_blobs().len(), 4);
+}
+
+#[tokio::test]
+async fn list_channels_feeds_the_tracker() {
+    let mock = MockRippled::start().await;
+    mock.set_result(
+        "account_channels",
+        json!({
+            "account": SENDER_ADDRESS,
+            "channels": [{
+                "account": SENDER_ADDRESS,
+                "amount": "10000000",
+                "balance": "2000000",
+                "channel_id": CHANNEL_ID,
+                "destination_account": RECEIVER_ADDRESS,
+                "public_key": "aB44YfzW24VDEJQ2UuLPV2PvqcPCSoLnL7y5M1EzhdW4LnK5xMS3",
+                "public_key_hex": sender_public_key(),
+                "settle_delay": 3600,
+                "expiration": 830822400,
+            }],
+            "ledger_index": VALIDATED_LEDGER_INDEX,
+            "validated": true,
+        }),
+    );
+    let xrpl = mock.connect().await;
+
+    let channels = xrpl.list_channels(SENDER_ADDRESS, Some(RECEIVER_ADDRESS)).await.unwrap();
+
+    let request = &mock.requests("account_channels")[0];
+    assert_eq!(request["destination_account"], RECEIVER_ADDRESS);
+    assert_eq!(request["ledger_index"], "validated");
+    assert_eq!(channels[0].expiration_time(), Some(ripple_task::from_ripple_time(830_822_400)));
+    let mut tracker = ChannelTracker::from_channel(&channels[0]).unwrap();
+    assert_eq!((tracker.capacity(), tracker.balance()), (drops_of(10_000_000), drops_of(2_000_000)));
+    // Claims are totals, so the next one starts from what was already paid out
+    let claim = tracker.authorize(&sender_secret(), drops_of(500_000)).unwrap();
+    assert_eq!(claim.amount, drops_of(2_500_000));
+    assert_eq!(tracker.unredeemed(), drops_of(500_000));
+}
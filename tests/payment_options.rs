@@ -0,0 +1,164 @@
+mod common;
+
+use common::*;
+use ripple_task::{CheckKind, Memo, PaymentOptions, PaymentBuilder, RippleError, XRPLManager};
+use serde_json::json;
+
+const INVOICE_ID: &str = "6f1dfd1d0fe8a32e40e1f2c05cf1c15545bab56b617f9c6c2d63a6b704bef59b";
+
+fn invoice_memo() -> Memo {
+    Memo::new("invoice", "text/plain", "INV-0042")
+}
+
+#[tokio::test]
+async fn options_are_signed_into_the_payment() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let options = PaymentOptions::default()
+        .with_destination_tag(12345)
+        .with_source_tag(7)
+        .with_invoice_id(INVOICE_ID)
+        .with_memo(invoice_memo());
+
+    let tx_hash = xrpl.send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &options).await.unwrap();
+
+    let blob = &mock.submitted_blobs()[0];
+    assert_eq!(tx_hash, transaction_hash(blob));
+    assert!(blob.contains("2E00003039"), "DestinationTag: {blob}");
+    assert!(blob.contains("2300000007"), "SourceTag: {blob}");
+    assert!(blob.contains(&format!("5011{}", INVOICE_ID.to_uppercase())), "InvoiceID: {blob}");
+    // Memos > Memo > MemoType, MemoData, MemoFormat (in canonical field order)
+    let memo = format!(
+        "F9EA7C07{}7D08{}7E0A{}E1F1",
+        hex::encode_upper("invoice"),
+        hex::encode_upper("INV-0042"),
+        hex::encode_upper("text/plain")
+    );
+    assert!(blob.contains(&memo), "Memos: {blob}");
+
+    // Paths still go last when options are set
+    let payment = PaymentBuilder::new(RECEIVER_ADDRESS, tst_amount(ISSUER_ADDRESS, "10"))
+        .with_send_max(drops(1_500_000))
+        .with_paths(vec![vec![ripple_task::PathStep { currency: Some("TST".to_string()), ..Default::default() }]])
+        .with_options(PaymentOptions::default().with_destination_tag(12345));
+    xrpl.send_payment(&sender_secret(), &payment).await.unwrap();
+    let blob = &mock.submitted_blobs()[1];
+    assert!(blob.contains("2E00003039"));
+    assert!(blob.ends_with(&format!("011210{}00", tst().to_hex())), "{blob}");
+}
+
+#[tokio::test]
+async fn offline_signing_includes_the_options() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let params = mock.connect().await.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    let options = PaymentOptions::default().with_destination_tag(12345);
+
+    let blob = XRPLManager::offline_sign_transaction(
+        &sender_secret(),
+        RECEIVER_ADDRESS,
+        xrpl::models::Amount::XRPAmount("75".into()),
+        params,
+        &options,
+    )
+    .unwrap();
+
+    assert!(blob.contains("2E00003039"), "{blob}");
+    mock.connect().await.submit_signed_blob(&blob).await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_options_are_rejected_before_submission() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let invalid = [
+        PaymentOptions::default().with_invoice_id("ABCD"),
+        PaymentOptions::default().with_invoice_id(&"Z".repeat(64)),
+        PaymentOptions::default().with_memo(Memo::default()),
+        PaymentOptions::default().with_memo(Memo::new("not a url", "text/plain", "data")),
+        PaymentOptions::default().with_memo(Memo::text(&"x".repeat(1024))),
+    ];
+    for options in invalid {
+        let err = xrpl.send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &options).await.unwrap_err();
+        assert!(matches!(err, RippleError::InvalidInput(_)), "{options:?}: {err:?}");
+    }
+    assert!(mock.submitted_blobs().is_empty());
+}
+
+#[tokio::test]
+async fn memos_may_fill_exactly_the_size_limit() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    // Array header and end marker (2), memo object header and end marker (2),
+    // "text/plain" MemoFormat (1 + 1 + 10) and MemoData with a 2-byte length prefix (1 + 2 + n)
+    let at_limit = PaymentOptions::default().with_memo(Memo::text(&"x".repeat(1024 - 19)));
+    xrpl.send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &at_limit).await.unwrap();
+
+    let over_limit = PaymentOptions::default().with_memo(Memo::text(&"x".repeat(1024 - 18)));
+    let err = xrpl.send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &over_limit).await.unwrap_err();
+    assert!(matches!(err, RippleError::InvalidInput(_)), "{err:?}");
+    assert_eq!(mock.submitted_blobs().len(), 1);
+}
+
+#[test]
+fn memo_data_keeps_arbitrary_bytes() {
+    let digest = vec![0x00, 0xFF, 0x80, 0xC3, 0x28, 0x7F];
+    let memo = Memo::new("sha256", "application/octet-stream", digest.clone());
+    let on_ledger = memo.to_json();
+    assert_eq!(on_ledger["Memo"]["MemoData"], "00FF80C3287F");
+
+    let read = Memo::from_json(&on_ledger).unwrap();
+    assert_eq!(read.memo_data, Some(digest));
+    assert_eq!(read, memo);
+    assert_eq!(read.data_text(), None);
+    assert_eq!(Memo::text("INV-0042").data_text(), Some("INV-0042"));
+
+    let serialized = serde_json::to_value(&memo).unwrap();
+    assert_eq!(serialized["memo_data"], "00FF80C3287F");
+    assert_eq!(serde_json::from_value::<Memo>(serialized).unwrap(), memo);
+
+    // Type and format are text; bytes that are not UTF-8 are refused rather than mangled
+    let binary_type = json!({ "Memo": { "MemoType": "FF00" } });
+    assert!(matches!(Memo::from_json(&binary_type), Err(RippleError::InvalidInput(_))));
+}
+
+#[tokio::test]
+async fn verify_transfer_checks_the_options_that_are_set() {
+    let mock = MockRippled::start().await;
+    let hash = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+    let mut tx_json = xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000");
+    tx_json["DestinationTag"] = json!(12345);
+    tx_json["InvoiceID"] = json!(INVOICE_ID.to_uppercase());
+    tx_json["Memos"] = json!([invoice_memo().to_json()]);
+    mock.add_transaction(hash, tx_json, success_meta(json!("1000")), true);
+    let xrpl = mock.connect().await;
+
+    let expected = PaymentOptions::default()
+        .with_destination_tag(12345)
+        .with_invoice_id(INVOICE_ID)
+        .with_memo(invoice_memo());
+    let report = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &expected)
+        .await
+        .unwrap();
+    assert!(report.is_verified(), "{report:?}");
+    assert_eq!(report.checks.len(), 11);
+    assert!(report.check(CheckKind::SourceTag).is_none());
+
+    let wrong = PaymentOptions::default()
+        .with_destination_tag(54321)
+        .with_source_tag(7)
+        .with_memo(Memo::text("INV-0042"));
+    let report = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &wrong)
+        .await
+        .unwrap();
+    let failures: Vec<_> = report.failures().map(|check| check.kind).collect();
+    assert_eq!(failures, vec![CheckKind::DestinationTag, CheckKind::SourceTag, CheckKind::Memos]);
+    assert_eq!(report.check(CheckKind::SourceTag).unwrap().actual, None);
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use common::*;
+use ripple_task::{CurrencyAmount, CursorPosition, FileCursor, LedgerCursor, MemoryCursor, RippleError};
+use serde_json::json;
+use std::time::Duration;
+
+const DEPOSIT_HASH: &str = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+const TOKEN_HASH: &str = "E08D6E9754025BA2534A78707605E0601F03ACE063687A0CA1BDDACFCD1698C7";
+const LATER_HASH: &str = "5EF8B3E0C2B3C3D5E9D1A1A2B3C4D5E6F708192A3B4C5D6E7F8091A2B3C4D5E6";
+
+fn ledger_closed(ledger_index: u32) -> serde_json::Value {
+    json!({
+        "type": "ledgerClosed",
+        "fee_base": 10,
+        "ledger_hash": "687F604EF6B2F67319E8DCC8C66EF49D84D18A1E18F948421FC24D2C7C3DB464",
+        "ledger_index": ledger_index,
+        "ledger_time": 798000000,
+        "reserve_base": 1000000,
+        "reserve_inc": 200000,
+        "txn_count": 1
+    })
+}
+
+fn deposit_history(mock: &MockRippled) {
+    // Before the cursor: already reported by a previous run
+    mock.add_history(
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        VALIDATED_LEDGER_INDEX - 5,
+        xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "5"),
+        success_meta(json!("5")),
+    );
+    let mut deposit = xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000");
+    deposit["DestinationTag"] = json!(12345);
+    mock.add_history(DEPOSIT_HASH, VALIDATED_LEDGER_INDEX - 2, deposit, success_meta(json!("1000")));
+    // Outgoing from the watched account
+    mock.add_history(
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        VALIDATED_LEDGER_INDEX - 1,
+        xrp_payment(RECEIVER_ADDRESS, SENDER_ADDRESS, "7"),
+        success_meta(json!("7")),
+    );
+    // Partial payment: less arrived than Amount
+    let delivered = json!({ "currency": "USD", "issuer": SENDER_ADDRESS, "value": "40" });
+    mock.add_history(
+        TOKEN_HASH,
+        VALIDATED_LEDGER_INDEX,
+        issued_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "USD", SENDER_ADDRESS, "50"),
+        success_meta(delivered),
+    );
+}
+
+#[tokio::test]
+async fn resumes_after_the_persisted_ledger() {
+    let mock = MockRippled::start().await;
+    deposit_history(&mock);
+    let xrpl = mock.connect().await;
+
+    let cursor = MemoryCursor(Some(CursorPosition::ledger(VALIDATED_LEDGER_INDEX - 3)));
+    let mut watcher = xrpl.watch_payments(&[RECEIVER_ADDRESS], cursor).await.unwrap();
+
+    let deposit = watcher.next().await.unwrap();
+    assert_eq!(deposit.tx_hash, DEPOSIT_HASH);
+    assert_eq!(deposit.sender, SENDER_ADDRESS);
+    assert_eq!(deposit.destination, RECEIVER_ADDRESS);
+    assert_eq!(deposit.destination_tag, Some(12345));
+    assert_eq!(deposit.delivered_amount, CurrencyAmount::drops(1000).unwrap());
+    assert!(deposit.is_xrp());
+
+    let token = watcher.next().await.unwrap();
+    assert_eq!(token.tx_hash, TOKEN_HASH);
+    assert_eq!(token.ledger_index, VALIDATED_LEDGER_INDEX);
+    let usd = "USD".parse().unwrap();
+    assert_eq!(token.delivered_amount, CurrencyAmount::issued(&usd, SENDER_ADDRESS, "40").unwrap());
+    assert!(!token.is_xrp());
+
+    // At the deposit, but not past the token payment that may still be in flight
+    assert_eq!(watcher.cursor(), &MemoryCursor(Some(deposit.position())));
+    let waiting = tokio::time::timeout(Duration::from_millis(50), watcher.next()).await;
+    assert!(waiting.is_err());
+    assert_eq!(watcher.cursor(), &MemoryCursor(Some(CursorPosition::ledger(VALIDATED_LEDGER_INDEX))));
+}
+
+#[tokio::test]
+async fn resumes_partway_through_a_ledger_after_a_crash() {
+    let mock = MockRippled::start().await;
+    let hashes: Vec<String> = (1..=3).map(|i| format!("{:064X}", 0xD0 + i)).collect();
+    for (transaction_index, hash) in hashes.iter().enumerate() {
+        let mut meta = success_meta(json!("100"));
+        meta["TransactionIndex"] = json!(transaction_index);
+        let payment = xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "100");
+        mock.add_history(hash, VALIDATED_LEDGER_INDEX - 1, payment, meta);
+    }
+    let xrpl = mock.connect().await;
+    let mut reported = Vec::new();
+
+    // First run: credits two of the ledger's three payments, then crashes
+    let cursor = MemoryCursor(Some(CursorPosition::ledger(VALIDATED_LEDGER_INDEX - 2)));
+    let mut watcher = xrpl.watch_payments(&[RECEIVER_ADDRESS], cursor).await.unwrap();
+    reported.push(watcher.next().await.unwrap());
+    let second = watcher.next().await.unwrap();
+    watcher.ack(&second).unwrap();
+    reported.push(second);
+    let cursor = watcher.cursor().clone();
+    drop(watcher);
+    assert_eq!(
+        cursor,
+        MemoryCursor(Some(CursorPosition { ledger_index: VALIDATED_LEDGER_INDEX - 1, transaction_index: Some(1) }))
+    );
+
+    // Second run picks up with the third payment only
+    let mut watcher = xrpl.watch_payments(&[RECEIVER_ADDRESS], cursor).await.unwrap();
+    reported.push(watcher.next().await.unwrap());
+    let waiting = tokio::time::timeout(Duration::from_millis(50), watcher.next()).await;
+    assert!(waiting.is_err());
+
+    let reported: Vec<String> = reported.into_iter().map(|payment| payment.tx_hash).collect();
+    assert_eq!(reported, hashes);
+}
+
+#[tokio::test]
+async fn credits_only_known_delivered_amounts() {
+    let mock = MockRippled::start().await;
+    // Only the recorded DeliveredAmount, no synthetic field
+    let mut meta = json!({ "TransactionIndex": 0, "TransactionResult": "tesSUCCESS", "AffectedNodes": [] });
+    meta["DeliveredAmount"] = json!("400");
+    mock.add_history(DEPOSIT_HASH, VALIDATED_LEDGER_INDEX - 1, xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"), meta);
+    // Partial payment whose delivered amount is unknown: Amount is only an upper bound
+    let mut partial = xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000000");
+    partial["Flags"] = json!(0x0002_0000);
+    mock.add_history(TOKEN_HASH, VALIDATED_LEDGER_INDEX, partial, success_meta(json!("unavailable")));
+    let xrpl = mock.connect().await;
+
+    let cursor = MemoryCursor(Some(CursorPosition::ledger(VALIDATED_LEDGER_INDEX - 2)));
+    let mut watcher = xrpl.watch_payments(&[RECEIVER_ADDRESS], cursor).await.unwrap();
+
+    let deposit = watcher.next().await.unwrap();
+    assert_eq!(deposit.tx_hash, DEPOSIT_HASH);
+    assert_eq!(deposit.delivered_amount, CurrencyAmount::drops(400).unwrap());
+    let skipped = tokio::time::timeout(Duration::from_millis(50), watcher.next()).await;
+    assert!(skipped.is_err());
+}
+
+#[tokio::test]
+async fn fails_when_the_server_lacks_history() {
+    let mock = MockRippled::start().await;
+    deposit_history(&mock);
+    mock.set_first_ledger(VALIDATED_LEDGER_INDEX - 1);
+    let xrpl = mock.connect().await;
+
+    let cursor = MemoryCursor(Some(CursorPosition::ledger(VALIDATED_LEDGER_INDEX - 3)));
+    let mut watcher = xrpl.watch_payments(&[RECEIVER_ADDRESS], cursor).await.unwrap();
+
+    // The deposit at VALIDATED_LEDGER_INDEX - 2 must not be skipped silently
+    let error = watcher.next().await.unwrap_err();
+    assert!(error.to_string().contains("missing history"), "{}", error);
+    assert_eq!(watcher.cursor(), &MemoryCursor(Some(CursorPosition::ledger(VALIDATED_LEDGER_INDEX - 3))));
+}
+
+#[tokio::test]
+async fn reports_payments_in_newly_validated_ledgers() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+    let mut watcher = xrpl.watch_payments(&[RECEIVER_ADDRESS], MemoryCursor::default()).await.unwrap();
+    assert_eq!(watcher.ledger_index(), VALIDATED_LEDGER_INDEX);
+
+    mock.add_history(
+        LATER_HASH,
+        VALIDATED_LEDGER_INDEX + 1,
+        xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "250"),
+        success_meta(json!("250")),
+    );
+    mock.publish(ledger_closed(VALIDATED_LEDGER_INDEX + 1));
+
+    let payment = watcher.next().await.unwrap();
+    assert_eq!(payment.tx_hash, LATER_HASH);
+    assert_eq!(payment.ledger_index, VALIDATED_LEDGER_INDEX + 1);
+    let request = mock.requests("account_tx").pop().unwrap();
+    assert_eq!(request["ledger_index_min"], VALIDATED_LEDGER_INDEX + 1);
+    assert_eq!(request["ledger_index_max"], VALIDATED_LEDGER_INDEX + 1);
+}
+
+#[tokio::test]
+async fn follows_account_tx_markers() {
+    let mock = MockRippled::start().await;
+    deposit_history(&mock);
+    mock.set_history_page_size(1);
+    let xrpl = mock.connect().await;
+
+    let mut watcher = xrpl
+        .watch_payments(&[RECEIVER_ADDRESS], MemoryCursor(Some(CursorPosition::ledger(VALIDATED_LEDGER_INDEX - 3))))
+        .await
+        .unwrap();
+    assert_eq!(watcher.next().await.unwrap().tx_hash, DEPOSIT_HASH);
+    assert_eq!(watcher.next().await.unwrap().tx_hash, TOKEN_HASH);
+    assert_eq!(mock.requests("account_tx").len(), 3);
+}
+
+#[tokio::test]
+async fn rejects_a_cursor_at_ledger_zero() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+
+    let cursor = MemoryCursor(Some(CursorPosition { ledger_index: 0, transaction_index: Some(2) }));
+    let result = xrpl.watch_payments(&[RECEIVER_ADDRESS], cursor).await;
+    assert!(matches!(result, Err(RippleError::InvalidInput(_))));
+}
+
+#[test]
+fn file_cursor_round_trips() {
+    let path = std::env::temp_dir().join(format!("ripple_task_cursor_{}", std::process::id()));
+    let mut cursor = FileCursor::new(&path);
+    assert_eq!(cursor.load().unwrap(), None);
+
+    let whole_ledger = CursorPosition::ledger(VALIDATED_LEDGER_INDEX);
+    cursor.save(whole_ledger).unwrap();
+    assert_eq!(FileCursor::new(&path).load().unwrap(), Some(whole_ledger));
+    let partway = CursorPosition { ledger_index: VALIDATED_LEDGER_INDEX, transaction_index: Some(3) };
+    cursor.save(partway).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("{}:3", VALIDATED_LEDGER_INDEX));
+    assert_eq!(FileCursor::new(&path).load().unwrap(), Some(partway));
+
+    for invalid in ["not a ledger", "0", "0:3"] {
+        std::fs::write(&path, invalid).unwrap();
+        assert!(matches!(cursor.load(), Err(RippleError::Storage { .. })), "{invalid}");
+    }
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::*;
+use ripple_task::{PaymentOptions, RippleError, Secret, wallet_from_seed};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn secret_is_redacted_in_debug_and_display() {
+    let secret = sender_secret();
+
+    assert_eq!(format!("{}", secret), "[REDACTED]");
+    assert_eq!(format!("{:?}", secret), "Secret(\"[REDACTED]\")");
+    assert!(!format!("{:?} {}", secret, secret).contains(SENDER_SEED));
+    assert_eq!(secret.expose_secret(), SENDER_SEED);
+}
+
+#[test]
+fn wallet_is_derived_from_secret() {
+    let wallet = wallet_from_seed(&sender_secret()).unwrap();
+
+    assert_eq!(wallet.classic_address, SENDER_ADDRESS);
+}
+
+#[tokio::test]
+async fn short_secret_is_rejected_without_panicking() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+
+    let err = xrpl
+        .send_xrp(&Secret::from("sEd"), RECEIVER_ADDRESS, 1, &PaymentOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, RippleError::Wallet { .. }));
+    assert!(!err.to_string().contains("sEd"));
+}
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn tracing_output_has_tx_fields_but_no_seed() {
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let validated = xrpl
+        .send_xrp_and_wait(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(!output.contains(SENDER_SEED));
+    assert!(output.contains(&format!("account=\"{}\"", SENDER_ADDRESS)));
+    assert!(output.contains(&format!("tx_hash=\"{}\"", validated.tx_hash)));
+    assert!(output.contains("engine_result=tesSUCCESS"));
+}
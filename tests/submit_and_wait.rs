@@ -0,0 +1,101 @@
+mod common;
+
+use common::*;
+use ripple_task::{PaymentOptions, RippleError, XRPLManager};
+
+#[tokio::test]
+async fn send_xrp_and_wait_returns_validated_result() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let validated = xrpl
+        .send_xrp_and_wait(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    assert!(validated.is_success());
+    assert_eq!(validated.tx_hash, transaction_hash(&mock.submitted_blobs()[0]));
+    assert_eq!(validated.ledger_index, VALIDATED_LEDGER_INDEX + 1);
+    assert_eq!(validated.meta.transaction_result, "tesSUCCESS");
+}
+
+#[tokio::test]
+async fn wait_keeps_polling_until_transaction_is_validated() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.delay_validation(1);
+    let xrpl = mock.connect().await;
+
+    let validated = xrpl
+        .setup_trustline_and_wait(&sender_secret(), RECEIVER_ADDRESS, &tst(), "1000")
+        .await
+        .unwrap();
+
+    assert!(validated.is_success());
+    assert_eq!(mock.requests("tx").len(), 2);
+}
+
+#[tokio::test]
+async fn tec_result_is_reported_from_validated_ledger() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.push_engine_result("tecPATH_DRY");
+    let xrpl = mock.connect().await;
+
+    let validated = xrpl
+        .send_issued_token_and_wait(&sender_secret(), RECEIVER_ADDRESS, &tst(), &"100".parse().unwrap(), &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    assert!(!validated.is_success());
+    assert!(validated.engine_result.claimed_fee());
+    assert_eq!(validated.engine_result.code(), "tecPATH_DRY");
+}
+
+#[tokio::test]
+async fn malformed_transaction_fails_without_waiting() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.push_engine_result("temMALFORMED");
+    let xrpl = mock.connect().await;
+
+    let err = xrpl
+        .send_xrp_and_wait(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, RippleError::Rejected { .. }));
+    assert!(mock.requests("tx").is_empty());
+}
+
+#[tokio::test]
+async fn wait_gives_up_once_last_ledger_sequence_has_passed() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.delay_validation(u32::MAX);
+    let xrpl = mock.connect().await;
+    let params = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    let last_ledger_sequence = params.last_ledger_sequence;
+    let blob = XRPLManager::offline_sign_transaction(
+        &sender_secret(),
+        RECEIVER_ADDRESS,
+        xrpl::models::Amount::XRPAmount("75".into()),
+        params,
+        &PaymentOptions::default(),
+    )
+    .unwrap();
+    mock.set_result("ledger", ledger_fixture(last_ledger_sequence + 1));
+
+    let err = xrpl.submit_signed_blob_and_wait(&blob).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        RippleError::Expired {
+            current_ledger,
+            last_ledger_sequence: expired_at,
+            ..
+        } if current_ledger == last_ledger_sequence + 1 && expired_at == last_ledger_sequence
+    ));
+    assert_eq!(err.tx_hash(), Some(transaction_hash(&blob).as_str()));
+}
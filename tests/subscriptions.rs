@@ -0,0 +1,110 @@
+mod common;
+
+use common::*;
+use futures::StreamExt;
+use ripple_task::RippleError;
+use serde_json::json;
+use std::borrow::Cow;
+use xrpl::models::{Currency, IssuedCurrency, XRP};
+
+const HASH: &str = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+
+fn ledger_closed(ledger_index: u32) -> serde_json::Value {
+    json!({
+        "type": "ledgerClosed",
+        "fee_base": 10,
+        "fee_ref": 10,
+        "ledger_hash": "687F604EF6B2F67319E8DCC8C66EF49D84D18A1E18F948421FC24D2C7C3DB464",
+        "ledger_index": ledger_index,
+        "ledger_time": 798000000,
+        "reserve_base": 1000000,
+        "reserve_inc": 200000,
+        "txn_count": 2,
+        "validated_ledgers": "900-1001"
+    })
+}
+
+#[tokio::test]
+async fn ledger_stream_yields_typed_ledgers() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+
+    let mut ledgers = xrpl.subscribe_ledgers().await.unwrap();
+    assert_eq!(mock.requests("subscribe")[0]["streams"], json!(["ledger"]));
+
+    mock.publish(ledger_closed(VALIDATED_LEDGER_INDEX + 1));
+    mock.publish(ledger_closed(VALIDATED_LEDGER_INDEX + 2));
+
+    let first = ledgers.next().await.unwrap().unwrap();
+    assert_eq!(first.ledger_index, VALIDATED_LEDGER_INDEX + 1);
+    assert_eq!(first.txn_count, 2);
+    assert_eq!(first.validated_ledgers.as_deref(), Some("900-1001"));
+    let second = ledgers.next().await.unwrap().unwrap();
+    assert_eq!(second.ledger_index, VALIDATED_LEDGER_INDEX + 2);
+}
+
+#[tokio::test]
+async fn account_stream_yields_transactions_only() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+
+    let mut payments = xrpl.subscribe_accounts(&[RECEIVER_ADDRESS]).await.unwrap();
+    assert_eq!(mock.requests("subscribe")[0]["accounts"], json!([RECEIVER_ADDRESS]));
+
+    let mut transaction = xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000");
+    transaction["hash"] = json!(HASH);
+    mock.publish(ledger_closed(VALIDATED_LEDGER_INDEX + 1));
+    mock.publish(json!({
+        "type": "transaction",
+        "engine_result": "tesSUCCESS",
+        "engine_result_code": 0,
+        "ledger_index": VALIDATED_LEDGER_INDEX + 1,
+        "validated": true,
+        "status": "closed",
+        "transaction": transaction,
+        "meta": success_meta(json!("1000")),
+    }));
+
+    let event = payments.next().await.unwrap().unwrap();
+    assert!(event.is_success());
+    assert!(event.validated);
+    assert_eq!(event.tx_hash(), Some(HASH));
+    assert_eq!(event.transaction_type(), Some("Payment"));
+    assert_eq!(event.transaction["Destination"], RECEIVER_ADDRESS);
+    assert_eq!(event.meta.unwrap().transaction_result, "tesSUCCESS");
+}
+
+#[tokio::test]
+async fn order_book_subscription_uses_rippled_field_names() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+    let usd = Currency::IssuedCurrency(IssuedCurrency::new(Cow::Borrowed("USD"), Cow::Borrowed(SENDER_ADDRESS)));
+
+    xrpl.subscribe_order_book(&Currency::XRP(XRP::new()), &usd).await.unwrap();
+
+    let book = &mock.requests("subscribe")[0]["books"][0];
+    assert_eq!(book["taker_gets"], json!({ "currency": "XRP" }));
+    assert_eq!(book["taker_pays"], json!({ "currency": "USD", "issuer": SENDER_ADDRESS }));
+    assert_eq!(book["both"], true);
+}
+
+#[tokio::test]
+async fn stream_reports_disconnect_then_ends() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+    let mut ledgers = xrpl.subscribe_ledgers().await.unwrap();
+
+    mock.shutdown();
+
+    assert!(matches!(ledgers.next().await, Some(Err(RippleError::Network { .. }))));
+    assert!(ledgers.next().await.is_none());
+}
+
+#[tokio::test]
+async fn subscriptions_need_a_websocket_endpoint() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect_http().await;
+
+    let err = xrpl.subscribe_ledgers().await.unwrap_err();
+    assert!(matches!(err, RippleError::InvalidInput(_)), "{err:?}");
+}
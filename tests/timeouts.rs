@@ -0,0 +1,106 @@
+mod common;
+
+use common::*;
+use ripple_task::{DEFAULT_REQUEST_TIMEOUT, PaymentOptions, RippleError};
+use serde_json::json;
+use std::time::Duration;
+
+const HASH: &str = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+
+#[tokio::test]
+async fn unanswered_lookup_times_out() {
+    let mock = MockRippled::start().await;
+    mock.stall("account_info");
+    let xrpl = mock.connect().await.with_request_timeout(Duration::from_millis(100));
+
+    let err = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap_err();
+    assert!(err.is_timeout(), "{err:?}");
+    assert!(matches!(
+        &err,
+        RippleError::Timeout { command, timeout } if command == "account_info" && *timeout == Duration::from_millis(100)
+    ));
+    assert!(err.to_string().contains("Timed Out"));
+    assert_eq!(mock.requests("account_info").len(), 1);
+}
+
+#[tokio::test]
+async fn verify_transfer_times_out_over_json_rpc() {
+    let mock = MockRippled::start().await;
+    mock.stall("tx");
+    let xrpl = mock.connect_http().await.with_request_timeout(Duration::from_millis(100));
+
+    let err = xrpl
+        .verify_transfer(HASH, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &PaymentOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RippleError::Timeout { ref command, .. } if command == "tx"), "{err:?}");
+}
+
+#[tokio::test]
+async fn connection_is_usable_after_a_timeout() {
+    let mock = MockRippled::start().await;
+    mock.add_transaction(
+        HASH,
+        xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"),
+        success_meta(json!("1000")),
+        true,
+    );
+    mock.stall("account_info");
+    let xrpl = mock.connect().await.with_request_timeout(Duration::from_millis(100));
+    assert_eq!(xrpl.request_timeout(), Duration::from_millis(100));
+
+    assert!(xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap_err().is_timeout());
+    let verified = xrpl
+        .verify_transfer(HASH, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(verified.is_verified());
+}
+
+#[tokio::test]
+async fn second_connection_keeps_the_timeout() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+    assert_eq!(xrpl.request_timeout(), DEFAULT_REQUEST_TIMEOUT);
+
+    let xrpl = xrpl.with_request_timeout(Duration::from_secs(5));
+    let second = xrpl.create_second_connection(None).await.unwrap();
+    assert_eq!(second.request_timeout(), Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn unanswered_submit_times_out() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    mock.stall("submit");
+    let xrpl = mock.connect().await.with_request_timeout(Duration::from_millis(100));
+
+    let err = xrpl
+        .send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RippleError::Timeout { ref command, .. } if command == "submit"), "{err:?}");
+
+    let err = xrpl
+        .setup_trustline(&sender_secret(), RECEIVER_ADDRESS, &tst(), "1000")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RippleError::Timeout { ref command, .. } if command == "submit"), "{err:?}");
+
+    let err = xrpl.submit_signed_blob("1200").await.unwrap_err();
+    assert!(matches!(err, RippleError::Timeout { ref command, .. } if command == "submit"), "{err:?}");
+}
+
+#[tokio::test]
+async fn unanswered_autofill_times_out() {
+    let mock = MockRippled::start().await;
+    mock.stall("account_info");
+    let xrpl = mock.connect().await.with_request_timeout(Duration::from_millis(100));
+
+    let err = xrpl
+        .send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RippleError::Timeout { ref command, .. } if command == "autofill"), "{err:?}");
+    assert!(mock.submitted_blobs().is_empty());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::*;
+use ripple_task::{CheckKind, PaymentOptions};
+use serde_json::{Value, json};
+
+fn issuer_info(transfer_rate: Option<u32>) -> Value {
+    let mut info = account_info_fixture(ISSUER_ADDRESS, 1);
+    if let Some(rate) = transfer_rate {
+        info["account_data"]["TransferRate"] = json!(rate);
+    }
+    info
+}
+
+#[tokio::test]
+async fn third_party_transfer_adds_the_issuer_transfer_fee() {
+    let mock = MockRippled::start().await;
+    mock.push_reply("account_info", Reply::Result(issuer_info(Some(1_002_000_000))));
+    mock.push_reply("account_info", Reply::Result(account_info_fixture(SENDER_ADDRESS, ACCOUNT_SEQUENCE)));
+    let xrpl = mock.connect().await;
+
+    xrpl.send_token(&sender_secret(), RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "100"), None, &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(mock.requests("account_info")[0]["account"], ISSUER_ADDRESS);
+    let blob = &mock.submitted_blobs()[0];
+    assert!(blob.contains(&send_max_field(&tst_amount(ISSUER_ADDRESS, "100.2"))));
+}
+
+#[tokio::test]
+async fn explicit_send_max_skips_the_issuer_lookup() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let send_max = tst_amount(ISSUER_ADDRESS, "105");
+
+    xrpl.send_token(&sender_secret(), RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "100"), Some(&send_max), &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    let lookups = mock.requests("account_info");
+    assert!(lookups.iter().all(|request| request["account"] == SENDER_ADDRESS));
+    assert!(mock.submitted_blobs()[0].contains(&send_max_field(&send_max)));
+}
+
+#[tokio::test]
+async fn issuer_without_transfer_rate_needs_no_send_max() {
+    let mock = MockRippled::start().await;
+    mock.push_reply("account_info", Reply::Result(issuer_info(None)));
+    mock.push_reply("account_info", Reply::Result(account_info_fixture(SENDER_ADDRESS, ACCOUNT_SEQUENCE)));
+    let xrpl = mock.connect().await;
+
+    xrpl.send_token(&sender_secret(), RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "100"), None, &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    let blob = &mock.submitted_blobs()[0];
+    assert!(!blob.contains(&send_max_field(&tst_amount(ISSUER_ADDRESS, "100"))));
+    assert!(xrpl.send_token(&sender_secret(), RECEIVER_ADDRESS, &drops(10), None, &PaymentOptions::default()).await.is_err());
+}
+
+#[tokio::test]
+async fn verify_transfer_checks_the_issuer_separately_from_the_sender() {
+    let mock = MockRippled::start().await;
+    let hash = "2E4A6C8E0B2D4F6A8C1E3B5D7F9A0C2E4B6D8F1A3C5E7B9D0F2A4C6E8B1D3F5A";
+    mock.add_transaction(
+        hash,
+        issued_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "TST", ISSUER_ADDRESS, "100"),
+        success_meta(json!({ "currency": "TST", "issuer": ISSUER_ADDRESS, "value": "100" })),
+        true,
+    );
+    let xrpl = mock.connect().await;
+
+    let report = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &tst_amount(ISSUER_ADDRESS, "100"), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(report.is_verified(), "{report:?}");
+
+    let wrong_issuer = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &tst_amount(SENDER_ADDRESS, "100"), &PaymentOptions::default())
+        .await
+        .unwrap();
+    let failures: Vec<_> = wrong_issuer.failures().map(|check| check.kind).collect();
+    assert_eq!(failures, vec![CheckKind::Issuer]);
+}
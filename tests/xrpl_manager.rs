@@ -0,0 +1,392 @@
+mod common;
+
+use common::*;
+use ripple_task::offline_signing::OfflineTransactionParams;
+use ripple_task::{
+    CheckKind, CurrencyAmount, PaymentOptions, RippleError, Secret, XRPLAsyncClient, XRPLClient, XRPLManager,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn get_account_info_reads_sequence_from_fixture() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let params = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+
+    assert_eq!(params.sequence, ACCOUNT_SEQUENCE);
+    assert_eq!(params.current_ledger_index, VALIDATED_LEDGER_INDEX);
+    assert_eq!(params.last_ledger_sequence, VALIDATED_LEDGER_INDEX + 10);
+    assert_eq!(mock.requests("account_info")[0]["account"], SENDER_ADDRESS);
+}
+
+#[tokio::test]
+async fn send_xrp_submits_signed_payment() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let tx_hash = xrpl
+        .send_xrp(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    let blobs = mock.submitted_blobs();
+    assert_eq!(blobs.len(), 1);
+    assert_eq!(tx_hash, transaction_hash(&blobs[0]));
+}
+
+#[tokio::test]
+async fn trustline_and_token_issuance_are_submitted() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let trustline_hash = xrpl
+        .setup_trustline(&sender_secret(), RECEIVER_ADDRESS, &tst(), "1000")
+        .await
+        .unwrap();
+    let token_hash = xrpl
+        .send_issued_token(&sender_secret(), RECEIVER_ADDRESS, &tst(), &"100".parse().unwrap(), &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    let blobs = mock.submitted_blobs();
+    assert_eq!(blobs.len(), 2);
+    assert_eq!(trustline_hash, transaction_hash(&blobs[0]));
+    assert_eq!(token_hash, transaction_hash(&blobs[1]));
+}
+
+#[tokio::test]
+async fn token_issuance_of_zero_is_refused_before_submitting() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+
+    let err = xrpl
+        .send_issued_token(&sender_secret(), RECEIVER_ADDRESS, &tst(), &"0".parse().unwrap(), &PaymentOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, RippleError::InvalidInput(_)), "{err:?}");
+    assert!(mock.submitted_blobs().is_empty());
+}
+
+#[tokio::test]
+async fn verify_transfer_matches_xrp_payment() {
+    let mock = MockRippled::start().await;
+    let hash = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+    mock.add_transaction(
+        hash,
+        xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"),
+        success_meta(json!("1000")),
+        true,
+    );
+    let xrpl = mock.connect().await;
+
+    let verified = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(verified.is_verified());
+
+    let wrong_amount = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(999), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(!wrong_amount.is_verified());
+
+    let wrong_currency = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &tst_amount(SENDER_ADDRESS, "1000"), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(!wrong_currency.is_verified());
+}
+
+#[tokio::test]
+async fn verify_transfer_matches_issued_payment() {
+    let mock = MockRippled::start().await;
+    let hash = "7E7D1A7B1F4C0D2F8B6E2C1A9D3F5E7B0A2C4E6F8A1B3D5F7092A4C6E8F0B2D4";
+    mock.add_transaction(
+        hash,
+        issued_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "TST", SENDER_ADDRESS, "100"),
+        success_meta(json!({ "currency": "TST", "issuer": SENDER_ADDRESS, "value": "100" })),
+        true,
+    );
+    let xrpl = mock.connect().await;
+
+    for notation in ["100", "100.0", "1e2"] {
+        let verified = xrpl
+            .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &tst_amount(SENDER_ADDRESS, notation), &PaymentOptions::default())
+            .await
+            .unwrap();
+        assert!(verified.is_verified(), "{notation}");
+    }
+
+    let err = CurrencyAmount::issued(&tst(), SENDER_ADDRESS, "one hundred").unwrap_err();
+    assert!(matches!(err, RippleError::InvalidInput(_)));
+}
+
+#[tokio::test]
+async fn verify_transfer_uses_delivered_amount_of_partial_payment() {
+    let mock = MockRippled::start().await;
+    let hash = "3B1A7C5E9D2F4A6C8E0B1D3F5A7C9E2B4D6F8A0C1E3B5D7F9A2C4E6B8D0F1A3C";
+    let mut payment = xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000");
+    payment["Flags"] = json!(0x0002_0000);
+    mock.add_transaction(hash, payment, partial_payment_meta(json!("400")), true);
+    let xrpl = mock.connect().await;
+
+    let full_amount = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(!full_amount.is_verified());
+
+    let delivered = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(400), &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(delivered.is_verified());
+}
+
+#[tokio::test]
+async fn verify_transfer_reads_delivered_amount_from_the_metadata() {
+    let mock = MockRippled::start().await;
+    let recorded = "6D2F4A8C0E1B3D5F7A9C2E4B6D8F0A1C3E5B7D9F2A4C6E8B0D1F3A5C7E9B2D4F";
+    let unknown = "7E3A5C9E1F2B4D6A8C0E3B5D7F9A1C2E4B6D8F0A3C5E7B9D1F2A4C6E8B0D3F5A";
+    let mut payment = xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000");
+    payment["Flags"] = json!(0x0002_0000);
+    // Only the DeliveredAmount rippled records on ledger, no synthetic delivered_amount
+    let mut meta = success_meta(json!(null));
+    meta.as_object_mut().unwrap().remove("delivered_amount");
+    let mut recorded_meta = meta.clone();
+    recorded_meta["DeliveredAmount"] = json!("400");
+    mock.add_transaction(recorded, payment.clone(), recorded_meta, true);
+    mock.add_transaction(unknown, payment, meta, true);
+    let xrpl = mock.connect().await;
+
+    let options = PaymentOptions::default();
+    let delivered = xrpl.verify_transfer(recorded, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(400), &options).await.unwrap();
+    assert!(delivered.is_verified());
+    let full_amount = xrpl.verify_transfer(recorded, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &options).await.unwrap();
+    assert!(!full_amount.is_verified());
+
+    // A partial payment that records no delivered amount is never credited at its Amount
+    let report = xrpl.verify_transfer(unknown, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &options).await.unwrap();
+    assert!(!report.is_verified());
+    assert_eq!(report.check(CheckKind::Amount).unwrap().actual, None);
+}
+
+#[tokio::test]
+async fn verify_transfer_rejects_failed_and_unvalidated_payments() {
+    let mock = MockRippled::start().await;
+    let failed = "5D2E8A4C6F0B1D3E5A7C9F2B4D6E8A0C1F3B5D7E9A2C4F6B8D0E1A3C5F7B9D2E";
+    let mut meta = success_meta(json!("1000"));
+    meta["TransactionResult"] = json!("tecUNFUNDED_PAYMENT");
+    mock.add_transaction(failed, xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"), meta, true);
+    let pending = "8F4B0D6A2C8E1F3B5D7A9C2E4F6B8D0A1C3E5F7B9D2A4C6E8F0B1D3A5C7E9F2B";
+    mock.add_transaction(
+        pending,
+        xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"),
+        success_meta(json!("1000")),
+        false,
+    );
+    let xrpl = mock.connect().await;
+
+    for (hash, failing) in [(failed, CheckKind::Result), (pending, CheckKind::Validated)] {
+        let report = xrpl
+            .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1000), &PaymentOptions::default())
+            .await
+            .unwrap();
+        let failures: Vec<_> = report.failures().map(|check| check.kind).collect();
+        assert_eq!(failures, vec![failing], "{hash}");
+    }
+}
+
+#[tokio::test]
+async fn verification_report_lists_checks_and_serializes() {
+    let mock = MockRippled::start().await;
+    let hash = "C53ECF838647FA5A4C780377025FEC7999AB4182590510CA461444B207AB74A9";
+    mock.add_transaction(
+        hash,
+        xrp_payment(SENDER_ADDRESS, RECEIVER_ADDRESS, "1000"),
+        success_meta(json!("1000")),
+        true,
+    );
+    let xrpl = mock.connect().await;
+
+    let report = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, "rExpectedDestination", &drops(1000), &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    assert!(!report.is_verified());
+    assert_eq!(report.ledger_index, Some(VALIDATED_LEDGER_INDEX));
+    assert_eq!(report.close_time, Some(800000000));
+    assert_eq!(report.checks.len(), 8);
+    let destination = report.check(CheckKind::Destination).unwrap();
+    assert_eq!(destination.expected.as_deref(), Some("rExpectedDestination"));
+    assert_eq!(destination.actual.as_deref(), Some(RECEIVER_ADDRESS));
+    assert!(report.check(CheckKind::Amount).unwrap().passed);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["tx_hash"], hash);
+    assert_eq!(json["checks"][2]["kind"], "destination");
+    assert_eq!(json["checks"][2]["passed"], false);
+    assert_eq!(json["checks"][5], json!({ "kind": "issuer", "expected": null, "actual": null, "passed": true }));
+}
+
+#[tokio::test]
+async fn get_transaction_reports_missing_transaction() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+    let hash = "0000000000000000000000000000000000000000000000000000000000000001";
+
+    let err = xrpl
+        .verify_transfer(hash, SENDER_ADDRESS, RECEIVER_ADDRESS, &drops(1), &PaymentOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, RippleError::Transaction { .. }));
+    assert_eq!(err.tx_hash(), Some(hash));
+    assert_eq!(mock.requests("tx")[0]["transaction"], hash);
+}
+
+#[tokio::test]
+async fn account_lookup_failure_names_the_account() {
+    let mock = MockRippled::start().await;
+    mock.set_error("account_info", "actNotFound");
+    let xrpl = mock.connect().await;
+
+    let err = xrpl
+        .gather_transaction_params(RECEIVER_ADDRESS)
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.account_address(), Some(RECEIVER_ADDRESS));
+    assert!(err.to_string().contains("actNotFound"));
+}
+
+#[test]
+fn expired_params_are_rejected_with_ledger_details() {
+    let params = OfflineTransactionParams {
+        sequence: ACCOUNT_SEQUENCE,
+        fee: "12".to_string(),
+        last_ledger_sequence: 1010,
+        current_ledger_index: 1000,
+        network_id: None,
+    };
+
+    let err = params.validate_security(Some(1010)).unwrap_err();
+
+    assert!(matches!(
+        err,
+        RippleError::Expired {
+            current_ledger: 1010,
+            last_ledger_sequence: 1010,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn invalid_seed_surfaces_wallet_error_with_source() {
+    let mock = MockRippled::start().await;
+    let xrpl = mock.connect().await;
+
+    let err = xrpl
+        .send_xrp(&Secret::from("not-a-seed"), RECEIVER_ADDRESS, 1, &PaymentOptions::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, RippleError::Wallet { .. }));
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[tokio::test]
+async fn offline_xrp_workflow_submits_through_second_connection() {
+    let online = MockRippled::start().await;
+    let submission = MockRippled::start().await;
+    online.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+
+    let xrpl = online.connect().await;
+    let xrpl2 = xrpl
+        .create_second_connection(Some(submission.network()))
+        .await
+        .unwrap();
+
+    let tx_hash = xrpl
+        .offline_xrp_workflow(&xrpl2, &sender_secret(), RECEIVER_ADDRESS, 75, &PaymentOptions::default())
+        .await
+        .unwrap();
+
+    assert!(online.submitted_blobs().is_empty());
+    let blobs = submission.submitted_blobs();
+    assert_eq!(blobs.len(), 1);
+    assert_eq!(tx_hash, transaction_hash(&blobs[0]));
+}
+
+#[tokio::test]
+async fn offline_signing_makes_no_network_calls() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+    let xrpl = mock.connect().await;
+    let params = xrpl.gather_transaction_params(SENDER_ADDRESS).await.unwrap();
+    let requests_before = mock.requests("account_info").len() + mock.requests("ledger").len();
+
+    let blob = XRPLManager::offline_sign_transaction(
+        &sender_secret(),
+        RECEIVER_ADDRESS,
+        xrpl::models::Amount::XRPAmount("75".into()),
+        params,
+        &PaymentOptions::default(),
+    )
+    .unwrap();
+
+    assert!(!blob.is_empty());
+    assert_eq!(
+        mock.requests("account_info").len() + mock.requests("ledger").len(),
+        requests_before
+    );
+
+    let tx_hash = xrpl.submit_signed_blob(&blob).await.unwrap();
+    assert_eq!(tx_hash, transaction_hash(&blob));
+}
+
+#[tokio::test]
+async fn submit_blob_reports_the_node_error() {
+    let mock = MockRippled::start().await;
+    mock.set_error("submit", "invalidTransaction");
+    let xrpl = mock.connect().await;
+
+    let err = xrpl.submit_signed_blob("DEADBEEF").await.unwrap_err();
+
+    assert!(matches!(err, RippleError::Transaction { .. }));
+    assert!(err.to_string().contains("invalidTransaction"));
+}
+
+// Written once against the trait so it runs over any transport
+async fn send_and_verify<C: XRPLAsyncClient>(xrpl: &XRPLManager<C>) -> String {
+    let validated = xrpl
+        .send_xrp_and_wait(&sender_secret(), RECEIVER_ADDRESS, 1_000, &PaymentOptions::default())
+        .await
+        .unwrap();
+    assert!(validated.is_success());
+    validated.tx_hash
+}
+
+#[tokio::test]
+async fn manager_is_generic_over_the_client() {
+    let mock = MockRippled::start().await;
+    mock.set_account(SENDER_ADDRESS, ACCOUNT_SEQUENCE);
+
+    let over_websocket = send_and_verify(&mock.connect().await).await;
+    let in_memory = mock.in_memory();
+    let over_memory = send_and_verify(&in_memory).await;
+
+    let blobs = mock.submitted_blobs();
+    assert_eq!(over_websocket, transaction_hash(&blobs[0]));
+    assert_eq!(over_memory, transaction_hash(&blobs[1]));
+    assert_eq!(in_memory.client().get_host().scheme(), "memory");
+}